@@ -0,0 +1,190 @@
+use crate::client::Client;
+use crate::commands::{self, Context};
+use crate::db::{DB, Redis};
+use crate::resp::Value;
+use bytes::Bytes;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{Instant, sleep_until};
+
+/// Set on the client by a handler that found nothing to serve and wants the
+/// connection parked until one of `keys` is written to.
+#[derive(Debug, Clone)]
+pub struct BlockRequest {
+    pub keys: Vec<Bytes>,
+    /// `None` blocks forever, like a timeout of 0.
+    pub timeout: Option<Duration>,
+    /// Sent to the client if the timeout passes first.
+    pub timeout_reply: Value,
+    /// Re-run instead of the original command when a key is ready, for
+    /// arguments that must keep the meaning they had when the client blocked.
+    pub retry: Option<Value>,
+}
+
+struct Waiter {
+    client: Client,
+    command: Value,
+    keys: Vec<Bytes>,
+    reply: oneshot::Sender<Bytes>,
+}
+
+/// Clients parked by blocking commands, queued per key in arrival order.
+#[derive(Default)]
+pub struct Blocking {
+    next_id: u64,
+    waiters: HashMap<u64, Waiter>,
+    queues: HashMap<Bytes, VecDeque<u64>>,
+    /// Keys written since the last `serve_ready` that someone is waiting on.
+    ready: Vec<Bytes>,
+}
+
+impl Blocking {
+    /// Marks `key` as worth retrying the clients blocked on it. Cheap when
+    /// nobody is waiting, so write commands call it unconditionally.
+    pub fn signal(&mut self, key: &[u8]) {
+        if self.queues.contains_key(key) && !self.ready.iter().any(|ready| ready == key) {
+            self.ready.push(Bytes::copy_from_slice(key));
+        }
+    }
+
+    /// Marks every key someone is waiting on, for when the whole keyspace
+    /// changes at once.
+    pub fn signal_all(&mut self) {
+        for key in self.queues.keys() {
+            if !self.ready.contains(key) {
+                self.ready.push(key.clone());
+            }
+        }
+    }
+
+    /// Parks `client`, which will have `command` re-run once a key is ready.
+    pub fn register(&mut self, client: &Client, command: &Value, request: BlockRequest) -> Ticket {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let (sender, receiver) = oneshot::channel();
+        for key in &request.keys {
+            self.queues.entry(key.clone()).or_default().push_back(id);
+        }
+        self.waiters.insert(
+            id,
+            Waiter {
+                client: client.clone(),
+                command: request.retry.unwrap_or_else(|| command.clone()),
+                keys: request.keys,
+                reply: sender,
+            },
+        );
+
+        Ticket {
+            id,
+            reply: receiver,
+            // A deadline past what `Instant` can hold is as good as never.
+            deadline: request
+                .timeout
+                .and_then(|timeout| Instant::now().checked_add(timeout)),
+            timeout_reply: request.timeout_reply.serialize(client.protocol),
+        }
+    }
+
+    fn unregister(&mut self, id: u64, keys: &[Bytes]) {
+        for key in keys {
+            if let Some(queue) = self.queues.get_mut(key) {
+                queue.retain(|&waiting| waiting != id);
+                if queue.is_empty() {
+                    self.queues.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Removes a waiter that timed out or hung up; false if it was served first.
+    fn cancel(&mut self, id: u64) -> bool {
+        match self.waiters.remove(&id) {
+            Some(waiter) => {
+                self.unregister(id, &waiter.keys);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Retries the clients blocked on every ready key, oldest first. Runs under the
+/// same lock as the write that readied the keys, so nobody else can take the
+/// data in between.
+pub fn serve_ready(store: &mut DB) {
+    while !store.blocking.ready.is_empty() {
+        let key = store.blocking.ready.remove(0);
+        let waiting = match store.blocking.queues.get(&key) {
+            Some(queue) => queue.iter().copied().collect::<Vec<_>>(),
+            None => continue,
+        };
+
+        for id in waiting {
+            let Some(mut waiter) = store.blocking.waiters.remove(&id) else {
+                continue;
+            };
+            // The connection went away; don't hand it data nobody will read.
+            if waiter.reply.is_closed() {
+                store.blocking.unregister(id, &waiter.keys);
+                continue;
+            }
+
+            let mut ctx = Context {
+                store,
+                client: &mut waiter.client,
+            };
+            let response =
+                commands::eval_command(&waiter.command, &mut ctx).unwrap_or_else(Value::from);
+            if waiter.client.blocked.take().is_some() {
+                // Still nothing for this client, but an XREAD behind it may be
+                // after earlier entries, so keep going down the queue.
+                store.blocking.waiters.insert(id, waiter);
+                continue;
+            }
+
+            store.blocking.unregister(id, &waiter.keys);
+            let _ = waiter
+                .reply
+                .send(response.serialize(waiter.client.protocol));
+        }
+    }
+}
+
+/// Held by a parked connection while it waits for its reply.
+pub struct Ticket {
+    id: u64,
+    reply: oneshot::Receiver<Bytes>,
+    deadline: Option<Instant>,
+    timeout_reply: Bytes,
+}
+
+impl Ticket {
+    /// The reply to send, or `None` if `closed` resolved first, in which case
+    /// the waiter is dropped so nothing is served to a client that is gone.
+    pub async fn wait(mut self, store: &Redis, closed: impl Future<Output = ()>) -> Option<Bytes> {
+        let deadline = self.deadline;
+        let timer = async {
+            match deadline {
+                Some(deadline) => sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            reply = &mut self.reply => return Some(reply.unwrap_or(self.timeout_reply)),
+            _ = timer => {
+                // A wakeup may have raced the timer; whichever got the lock first wins.
+                if store.lock().unwrap().blocking.cancel(self.id) {
+                    return Some(self.timeout_reply);
+                }
+            }
+            _ = closed => {
+                store.lock().unwrap().blocking.cancel(self.id);
+                return None;
+            }
+        }
+        Some(self.reply.await.unwrap_or(self.timeout_reply))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::blocking::BlockRequest;
+use crate::resp::Protocol;
+use bytes::Bytes;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Per-connection state that outlives a single command.
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub id: u64,
+    pub protocol: Protocol,
+    pub name: Option<Bytes>,
+    /// Set by a blocking command that has to wait; taken by the server loop.
+    pub blocked: Option<BlockRequest>,
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Client {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            protocol: Protocol::default(),
+            name: None,
+            blocked: None,
+        }
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
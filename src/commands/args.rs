@@ -0,0 +1,128 @@
+use crate::error::CommandError;
+use crate::resp::Value;
+use bytes::Bytes;
+use std::str;
+use std::time::Duration;
+
+/// The raw bytes of an argument; clients only ever send bulk strings.
+pub fn bulk(arg: &Value) -> Result<&Bytes, CommandError> {
+    match arg {
+        Value::BulkString(bytes) => Ok(bytes),
+        _ => Err(CommandError::Syntax),
+    }
+}
+
+/// Case-insensitive match of an argument against an option name such as `NX`.
+pub fn is_option(arg: &Value, name: &str) -> bool {
+    matches!(arg, Value::BulkString(bytes) if bytes.eq_ignore_ascii_case(name.as_bytes()))
+}
+
+pub fn parse_i64(arg: &Value) -> Result<i64, CommandError> {
+    to_i64(bulk(arg)?).ok_or(CommandError::NotInteger)
+}
+
+/// Parses a signed 64-bit integer as strictly as Redis does: no `+` sign, no
+/// leading zeros and no surrounding whitespace.
+pub fn to_i64(bytes: &[u8]) -> Option<i64> {
+    let digits = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    match digits {
+        [] => None,
+        [b'0'] if digits.len() == bytes.len() => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {
+            str::from_utf8(bytes).ok()?.parse::<i64>().ok()
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_f64(arg: &Value) -> Result<f64, CommandError> {
+    to_f64(bulk(arg)?).ok_or(CommandError::NotFloat)
+}
+
+/// Parses a double the way Redis does: no surrounding whitespace and never NaN.
+/// Infinities parse, so callers that can't store them must check the result.
+pub fn to_f64(bytes: &[u8]) -> Option<f64> {
+    let text = str::from_utf8(bytes).ok()?;
+    if text.is_empty() || text.trim() != text {
+        return None;
+    }
+    text.parse::<f64>().ok().filter(|value| !value.is_nan())
+}
+
+/// Most members a negative count may ask HRANDFIELD, SRANDMEMBER or
+/// ZRANDMEMBER to repeat. Their replies are built whole while the store is
+/// locked, so a count near `i64::MAX` would otherwise stall the server for good.
+const MAX_RANDOM_REPEATS: u64 = 1 << 24;
+
+/// How many picks a negative RANDMEMBER-style count asks for.
+pub fn random_repeats(count: i64) -> Result<usize, CommandError> {
+    match count.unsigned_abs() {
+        repeats if repeats <= MAX_RANDOM_REPEATS => Ok(repeats as usize),
+        _ => Err(CommandError::Err("value is out of range".to_string())),
+    }
+}
+
+/// A blocking command's timeout in (possibly fractional) seconds; 0 means
+/// wait forever and comes back as `None`.
+pub fn parse_timeout(arg: &Value) -> Result<Option<Duration>, CommandError> {
+    let seconds = to_f64(bulk(arg)?)
+        .filter(|seconds| seconds.is_finite())
+        .ok_or_else(|| CommandError::Err("timeout is not a float or out of range".to_string()))?;
+    if seconds < 0.0 {
+        return Err(CommandError::Err("timeout is negative".to_string()));
+    }
+    if seconds == 0.0 {
+        return Ok(None);
+    }
+    // Redis keeps the timeout in milliseconds in a signed 64-bit integer.
+    Duration::try_from_secs_f64(seconds)
+        .ok()
+        .filter(|timeout| timeout.as_millis() <= i64::MAX as u128)
+        .map(Some)
+        .ok_or_else(|| CommandError::Err("timeout is out of range".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_i64_is_strict() {
+        assert_eq!(to_i64(b"0"), Some(0));
+        assert_eq!(to_i64(b"-42"), Some(-42));
+        assert_eq!(to_i64(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(to_i64(b"-9223372036854775808"), Some(i64::MIN));
+
+        assert_eq!(to_i64(b"9223372036854775808"), None);
+        assert_eq!(to_i64(b"+1"), None);
+        assert_eq!(to_i64(b"007"), None);
+        assert_eq!(to_i64(b"-0"), None);
+        assert_eq!(to_i64(b" 1"), None);
+        assert_eq!(to_i64(b""), None);
+    }
+
+    #[test]
+    fn test_to_f64() {
+        assert_eq!(to_f64(b"10.5"), Some(10.5));
+        assert_eq!(to_f64(b"5.0e3"), Some(5000.0));
+        assert_eq!(to_f64(b"-inf"), Some(f64::NEG_INFINITY));
+
+        assert_eq!(to_f64(b"nan"), None);
+        assert_eq!(to_f64(b" 1.5"), None);
+        assert_eq!(to_f64(b"1.5x"), None);
+        assert_eq!(to_f64(b""), None);
+    }
+    #[test]
+    fn test_parse_timeout() {
+        let timeout = |text: &str| parse_timeout(&Value::BulkString(Bytes::from(text.to_string())));
+        assert_eq!(timeout("0").unwrap(), None);
+        assert_eq!(timeout("1.5").unwrap(), Some(Duration::from_millis(1500)));
+        assert!(timeout("9000000000000000").is_ok());
+
+        let out_of_range = CommandError::Err("timeout is out of range".to_string());
+        assert_eq!(timeout("1e300").unwrap_err(), out_of_range);
+        assert_eq!(timeout("1e16").unwrap_err(), out_of_range);
+        assert!(timeout("inf").is_err());
+        assert!(timeout("-1").is_err());
+    }
+}
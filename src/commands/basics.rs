@@ -1,45 +1,125 @@
-use crate::db::DB;
-use crate::resp::Value;
-use std::sync::MutexGuard;
+use super::registry::{CommandSpec, Flag};
+use super::{Context, args};
+use crate::error::CommandError;
+use crate::resp::{Protocol, Value};
+use bytes::Bytes;
 
-pub fn eval_echo(params: &[Value]) -> Result<Value, String> {
-    Ok(params[0].clone())
-}
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("echo", "connection", 2, eval_echo)
+        .flags(&[Flag::Fast])
+        .summary("Returns the given string."),
+    CommandSpec::new("ping", "connection", -1, eval_ping)
+        .flags(&[Flag::Fast])
+        .summary("Returns the server's liveliness response."),
+    CommandSpec::new("hello", "connection", -1, eval_hello)
+        .flags(&[Flag::NoScript, Flag::Loading, Flag::Stale, Flag::Fast])
+        .summary("Handshakes with the Redis server."),
+    CommandSpec::new("config", "server", -2, eval_config_get)
+        .summary("A container for server configuration commands.")
+        .subcommands(&[CommandSpec::new("get", "server", -3, eval_config_get)
+            .flags(&[Flag::Admin, Flag::NoScript, Flag::Loading, Flag::Stale])
+            .summary("Returns the effective values of configuration parameters.")]),
+];
 
-pub fn eval_ping(params: &[Value]) -> Result<Value, String> {
-    let Some(message) = params.first() else {
-        return Ok(Value::SimpleString("PONG".to_string()));
+pub fn eval_echo(params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    let [message] = params else {
+        return Err(CommandError::WrongArity("echo".to_string()));
     };
     Ok(message.clone())
 }
 
-pub fn eval_config(params: &[Value], store: MutexGuard<DB>) -> Result<Value, String> {
-    // Assumed GET, so skipping past [0].
-    let field = params[1].clone();
-    let config_value = match &field {
-        Value::BulkString(tar) if tar == "dir" => &store.config.dir,
-        Value::BulkString(tar) if tar == "dbfilename" => &store.config.dbfilename,
-        bad_tar => {
-            return Err(format!("unknown config: {}", bad_tar.serialize()));
-        }
+pub fn eval_ping(params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    match params {
+        [] => Ok(Value::SimpleString("PONG".to_string())),
+        [message] => Ok(message.clone()),
+        _ => Err(CommandError::WrongArity("ping".to_string())),
+    }
+}
+
+pub const SERVER_VERSION: &str = "7.4.0";
+
+/// HELLO [protover [AUTH username password] [SETNAME clientname]]
+pub fn eval_hello(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let client = &mut *ctx.client;
+    let mut args = params.iter();
+    let protocol = match args.next() {
+        None => client.protocol,
+        Some(Value::BulkString(version)) if version == "2" => Protocol::Resp2,
+        Some(Value::BulkString(version)) if version == "3" => Protocol::Resp3,
+        Some(_) => return Err(CommandError::NoProto),
     };
 
-    Ok(Value::Array(vec![
-        field,
-        Value::BulkString(config_value.clone()),
+    let mut name = None;
+    while let Some(option) = args.next() {
+        match option {
+            Value::BulkString(opt) if opt.eq_ignore_ascii_case(b"AUTH") => {
+                let (Some(Value::BulkString(username)), Some(_password)) =
+                    (args.next(), args.next())
+                else {
+                    return Err(CommandError::Err(
+                        "Syntax error in HELLO option 'AUTH'".to_string(),
+                    ));
+                };
+                // Only the passwordless default user exists.
+                if username != "default" {
+                    return Err(CommandError::WrongPass);
+                }
+            }
+            Value::BulkString(opt) if opt.eq_ignore_ascii_case(b"SETNAME") => {
+                let Some(Value::BulkString(client_name)) = args.next() else {
+                    return Err(CommandError::Err(
+                        "Syntax error in HELLO option 'SETNAME'".to_string(),
+                    ));
+                };
+                name = Some(client_name.clone());
+            }
+            other => {
+                return Err(CommandError::Err(format!(
+                    "Syntax error in HELLO option '{}'",
+                    String::from_utf8_lossy(args::bulk(other)?)
+                )));
+            }
+        }
+    }
+
+    client.protocol = protocol;
+    if name.is_some() {
+        client.name = name;
+    }
+
+    let proto = match protocol {
+        Protocol::Resp2 => 2,
+        Protocol::Resp3 => 3,
+    };
+    let field = |name: &'static str| Value::BulkString(Bytes::from_static(name.as_bytes()));
+    Ok(Value::Map(vec![
+        (field("server"), field("redis")),
+        (field("version"), field(SERVER_VERSION)),
+        (field("proto"), Value::Integer(proto)),
+        (field("id"), Value::Integer(client.id as i64)),
+        (field("mode"), field("standalone")),
+        (field("role"), field("master")),
+        (field("modules"), Value::Array(vec![])),
     ]))
 }
 
-pub fn eval_keys(params: &[Value], store: MutexGuard<DB>) -> Result<Value, String> {
-    match &params[0] {
-        Value::BulkString(all) if all == "*" => {
-            let all = store
-                .db
-                .keys()
-                .map(|key| Value::BulkString(key.clone()))
-                .collect::<Vec<Value>>();
-            Ok(Value::Array(all))
-        }
-        _ => panic!("eval_keys: only * is supported: {params:?}"),
+/// CONFIG GET parameter [parameter ...]
+///
+/// Unknown parameters are left out of the reply rather than rejected.
+pub fn eval_config_get(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let config = &ctx.store.config;
+    let mut pairs = Vec::new();
+    for field in params {
+        let config_value = match field {
+            Value::BulkString(tar) if tar.eq_ignore_ascii_case(b"dir") => &config.dir,
+            Value::BulkString(tar) if tar.eq_ignore_ascii_case(b"dbfilename") => &config.dbfilename,
+            _ => continue,
+        };
+        pairs.push((
+            field.clone(),
+            Value::BulkString(Bytes::from(config_value.clone())),
+        ));
     }
+
+    Ok(Value::Map(pairs))
 }
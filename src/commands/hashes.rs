@@ -0,0 +1,831 @@
+use super::numbers::format_float;
+use super::registry::{CommandSpec, Flag};
+use super::scan::{self, ScanArgs, Target};
+use super::strings::{is_expiry_option, parse_expiry};
+use super::{Context, args};
+use crate::db::{Data, StoredValue};
+use crate::error::CommandError;
+use crate::hash::Hash;
+use crate::resp::{Protocol, Value};
+use bytes::Bytes;
+use rand::seq::IteratorRandom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("hset", "hash", -4, hset)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Creates or modifies the value of a field in a hash."),
+    CommandSpec::new("hsetnx", "hash", 4, hsetnx)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Sets the value of a field in a hash only when the field doesn't exist."),
+    CommandSpec::new("hget", "hash", 3, hget)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the value of a field in a hash."),
+    CommandSpec::new("hmget", "hash", -3, hmget)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the values of all fields in a hash."),
+    CommandSpec::new("hdel", "hash", -3, hdel)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain."),
+    CommandSpec::new("hexists", "hash", 3, hexists)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Determines whether a field exists in a hash."),
+    CommandSpec::new("hlen", "hash", 2, hlen)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of fields in a hash."),
+    CommandSpec::new("hkeys", "hash", 2, hkeys)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns all fields in a hash."),
+    CommandSpec::new("hvals", "hash", 2, hvals)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns all values in a hash."),
+    CommandSpec::new("hgetall", "hash", 2, hgetall)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns all fields and values in a hash."),
+    CommandSpec::new("hincrby", "hash", 4, hincrby)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist."),
+    CommandSpec::new("hincrbyfloat", "hash", 4, hincrbyfloat)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist."),
+    CommandSpec::new("hstrlen", "hash", 3, hstrlen)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the length of the value of a field."),
+    CommandSpec::new("hrandfield", "hash", -2, hrandfield)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns one or more random fields from a hash."),
+    CommandSpec::new("hscan", "hash", -3, hscan)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Iterates over fields and values of a hash."),
+    CommandSpec::new("hexpire", "hash", -6, hexpire)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set expiry for hash field using relative time to expire (seconds)"),
+    CommandSpec::new("hpexpire", "hash", -6, hpexpire)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set expiry for hash field using relative time to expire (milliseconds)"),
+    CommandSpec::new("hexpireat", "hash", -6, hexpireat)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set expiry for hash field using an absolute Unix timestamp (seconds)"),
+    CommandSpec::new("hpexpireat", "hash", -6, hpexpireat)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set expiry for hash field using an absolute Unix timestamp (milliseconds)"),
+    CommandSpec::new("httl", "hash", -5, httl)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the TTL in seconds of a hash field."),
+    CommandSpec::new("hpttl", "hash", -5, hpttl)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the TTL in milliseconds of a hash field."),
+    CommandSpec::new("hexpiretime", "hash", -5, hexpiretime)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the expiration time of a hash field as a Unix timestamp, in seconds."),
+    CommandSpec::new("hpexpiretime", "hash", -5, hpexpiretime)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the expiration time of a hash field as a Unix timestamp, in msec."),
+    CommandSpec::new("hpersist", "hash", -5, hpersist)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Removes the expiration time for each specified field"),
+    CommandSpec::new("hgetex", "hash", -5, hgetex)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Get the value of one or more fields of a given hash key, and optionally set their expiration."),
+    CommandSpec::new("hsetex", "hash", -6, hsetex)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set the value of one or more fields of a given hash key, and optionally set their expiration."),
+];
+
+/// The hash stored at `key`, `None` if it is missing, or WRONGTYPE. Fields
+/// whose TTL has passed are dropped first, and so is the key if none are left.
+fn get_hash<'a>(ctx: &'a mut Context, key: &[u8]) -> Result<Option<&'a mut Hash>, CommandError> {
+    let emptied = match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        None => return Ok(None),
+        Some(Data::Hash(hash)) => hash.purge_expired(SystemTime::now()) > 0 && hash.is_empty(),
+        Some(_) => return Err(CommandError::WrongType),
+    };
+    if emptied {
+        ctx.store.remove(key);
+        return Ok(None);
+    }
+    match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        Some(Data::Hash(hash)) => Ok(Some(hash)),
+        _ => Ok(None),
+    }
+}
+
+/// Like `get_hash`, but creates an empty hash when the key is missing.
+fn get_or_create_hash<'a>(ctx: &'a mut Context, key: &Bytes) -> Result<&'a mut Hash, CommandError> {
+    if get_hash(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::Hash(Hash::new()),
+                expiry: None,
+            },
+        );
+    }
+    Ok(get_hash(ctx, key)?.expect("hash was just created"))
+}
+
+/// Hashes never exist empty: removing the last field removes the key.
+fn remove_if_empty(ctx: &mut Context, key: &[u8]) {
+    if matches!(get_hash(ctx, key), Ok(Some(hash)) if hash.is_empty()) {
+        ctx.store.remove(key);
+    }
+}
+
+fn bulk_or_null(value: Option<&Bytes>) -> Value {
+    value.map_or(Value::NullString, |value| Value::BulkString(value.clone()))
+}
+
+/// HSET key field value [field value ...]
+pub fn hset(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, pairs)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hset".to_string()));
+    };
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity("hset".to_string()));
+    }
+    let pairs = pairs
+        .chunks(2)
+        .map(|pair| Ok((args::bulk(&pair[0])?, args::bulk(&pair[1])?)))
+        .collect::<Result<Vec<_>, CommandError>>()?;
+
+    let hash = get_or_create_hash(ctx, args::bulk(key)?)?;
+    let added = pairs
+        .into_iter()
+        .filter(|(field, value)| hash.insert((*field).clone(), (*value).clone()).is_none())
+        .count();
+    Ok(Value::Integer(added as i64))
+}
+
+/// HSETNX key field value
+pub fn hsetnx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field, value] = params else {
+        return Err(CommandError::WrongArity("hsetnx".to_string()));
+    };
+    let (field, value) = (args::bulk(field)?, args::bulk(value)?);
+
+    let hash = get_or_create_hash(ctx, args::bulk(key)?)?;
+    if hash.contains_key(field) {
+        return Ok(Value::Integer(0));
+    }
+    hash.insert(field.clone(), value.clone());
+    Ok(Value::Integer(1))
+}
+
+/// HGET key field
+pub fn hget(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field] = params else {
+        return Err(CommandError::WrongArity("hget".to_string()));
+    };
+    let field = args::bulk(field)?;
+    let hash = get_hash(ctx, args::bulk(key)?)?;
+    Ok(bulk_or_null(hash.and_then(|hash| hash.get(field))))
+}
+
+/// HMGET key field [field ...]
+pub fn hmget(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, fields)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hmget".to_string()));
+    };
+    let hash = get_hash(ctx, args::bulk(key)?)?;
+    let values = fields
+        .iter()
+        .map(|field| {
+            let field = args::bulk(field)?;
+            Ok(bulk_or_null(hash.as_ref().and_then(|hash| hash.get(field))))
+        })
+        .collect::<Result<_, CommandError>>()?;
+    Ok(Value::Array(values))
+}
+
+/// HDEL key field [field ...]
+pub fn hdel(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, fields)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hdel".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let fields = fields
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(hash) = get_hash(ctx, key)? else {
+        return Ok(Value::Integer(0));
+    };
+    let removed = fields
+        .into_iter()
+        .filter(|field| hash.remove(field).is_some())
+        .count();
+    remove_if_empty(ctx, key);
+    Ok(Value::Integer(removed as i64))
+}
+
+/// HEXISTS key field
+pub fn hexists(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field] = params else {
+        return Err(CommandError::WrongArity("hexists".to_string()));
+    };
+    let field = args::bulk(field)?;
+    let exists = get_hash(ctx, args::bulk(key)?)?.is_some_and(|hash| hash.contains_key(field));
+    Ok(Value::Integer(exists as i64))
+}
+
+/// HLEN key
+pub fn hlen(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("hlen".to_string()));
+    };
+    let len = get_hash(ctx, args::bulk(key)?)?.map_or(0, |hash| hash.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// HKEYS key
+pub fn hkeys(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("hkeys".to_string()));
+    };
+    let fields = get_hash(ctx, args::bulk(key)?)?
+        .map(|hash| hash.keys().cloned().map(Value::BulkString).collect())
+        .unwrap_or_default();
+    Ok(Value::Array(fields))
+}
+
+/// HVALS key
+pub fn hvals(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("hvals".to_string()));
+    };
+    let values = get_hash(ctx, args::bulk(key)?)?
+        .map(|hash| hash.values().cloned().map(Value::BulkString).collect())
+        .unwrap_or_default();
+    Ok(Value::Array(values))
+}
+
+/// HGETALL key
+pub fn hgetall(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("hgetall".to_string()));
+    };
+    let pairs = get_hash(ctx, args::bulk(key)?)?
+        .map(|hash| {
+            hash.iter()
+                .map(|(field, value)| {
+                    (
+                        Value::BulkString(field.clone()),
+                        Value::BulkString(value.clone()),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(Value::Map(pairs))
+}
+
+/// HINCRBY key field increment
+pub fn hincrby(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field, increment] = params else {
+        return Err(CommandError::WrongArity("hincrby".to_string()));
+    };
+    let field = args::bulk(field)?;
+    let increment = args::parse_i64(increment)?;
+
+    let hash = get_or_create_hash(ctx, args::bulk(key)?)?;
+    let current = match hash.get(field) {
+        Some(value) => args::to_i64(value)
+            .ok_or_else(|| CommandError::Err("hash value is not an integer".to_string()))?,
+        None => 0,
+    };
+    let updated = current
+        .checked_add(increment)
+        .ok_or_else(|| CommandError::Err("increment or decrement would overflow".to_string()))?;
+    hash.update(field, Bytes::from(updated.to_string()));
+    Ok(Value::Integer(updated))
+}
+
+/// HINCRBYFLOAT key field increment
+pub fn hincrbyfloat(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field, increment] = params else {
+        return Err(CommandError::WrongArity("hincrbyfloat".to_string()));
+    };
+    let field = args::bulk(field)?;
+    let increment = args::parse_f64(increment)?;
+
+    let key = args::bulk(key)?;
+    // Check the result before creating the hash, so a failed increment
+    // doesn't leave an empty one behind.
+    let current = match get_hash(ctx, key)?.and_then(|hash| hash.get(field)) {
+        Some(value) => args::to_f64(value)
+            .ok_or_else(|| CommandError::Err("hash value is not a float".to_string()))?,
+        None => 0.0,
+    };
+    let updated = current + increment;
+    if !updated.is_finite() {
+        return Err(CommandError::Err(
+            "increment would produce NaN or Infinity".to_string(),
+        ));
+    }
+    let formatted = Bytes::from(format_float(updated));
+    get_or_create_hash(ctx, key)?.update(field, formatted.clone());
+    Ok(Value::BulkString(formatted))
+}
+
+/// HSTRLEN key field
+pub fn hstrlen(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, field] = params else {
+        return Err(CommandError::WrongArity("hstrlen".to_string()));
+    };
+    let field = args::bulk(field)?;
+    let len = get_hash(ctx, args::bulk(key)?)?
+        .and_then(|hash| hash.get(field))
+        .map_or(0, |value| value.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// HRANDFIELD key [count [WITHVALUES]]
+pub fn hrandfield(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let (key, count, with_values) = match params {
+        [key] => (key, None, false),
+        [key, count] => (key, Some(count), false),
+        [key, count, option] if args::is_option(option, "WITHVALUES") => (key, Some(count), true),
+        _ => return Err(CommandError::Syntax),
+    };
+    let count = count.map(args::parse_i64).transpose()?;
+    let protocol = ctx.client.protocol;
+    let mut rng = rand::thread_rng();
+
+    let Some(hash) = get_hash(ctx, args::bulk(key)?)? else {
+        return Ok(match count {
+            Some(_) => Value::Array(vec![]),
+            None => Value::NullString,
+        });
+    };
+    let Some(count) = count else {
+        return Ok(bulk_or_null(hash.random(&mut rng).map(|(field, _)| field)));
+    };
+
+    // A negative count may repeat fields; a positive one returns distinct ones.
+    let picked: Vec<(&Bytes, &Bytes)> = if count < 0 {
+        (0..args::random_repeats(count)?)
+            .map(|_| hash.random(&mut rng).expect("hashes are never empty"))
+            .collect()
+    } else {
+        let count = (count as u64).min(hash.len() as u64) as usize;
+        hash.iter().choose_multiple(&mut rng, count)
+    };
+
+    let entries = picked
+        .into_iter()
+        .flat_map(|(field, value)| {
+            let field = Value::BulkString(field.clone());
+            let value = Value::BulkString(value.clone());
+            match (with_values, protocol) {
+                (false, _) => vec![field],
+                (true, Protocol::Resp2) => vec![field, value],
+                (true, Protocol::Resp3) => vec![Value::Array(vec![field, value])],
+            }
+        })
+        .collect();
+    Ok(Value::Array(entries))
+}
+
+/// HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+pub fn hscan(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hscan".to_string()));
+    };
+    let scan_args = ScanArgs::parse(rest, Target::Hash)?;
+    let Some(hash) = get_hash(ctx, args::bulk(key)?)? else {
+        return Ok(scan::reply(0, vec![]));
+    };
+    let (cursor, found) = scan_args.walk_collection(hash.len(), |cursor, found| {
+        hash.scan(cursor, |field, value| {
+            found.push((field.clone(), value.clone()))
+        })
+    });
+    let items = found
+        .into_iter()
+        .filter(|(field, _)| scan_args.matches(field))
+        .flat_map(|(field, value)| {
+            let value = (!scan_args.no_values).then_some(Value::BulkString(value));
+            std::iter::once(Value::BulkString(field)).chain(value)
+        })
+        .collect();
+    Ok(scan::reply(cursor, items))
+}
+
+/// Field TTLs can't go past this many milliseconds since the epoch, as in Redis.
+const MAX_FIELD_EXPIRY_MS: u64 = (1 << 48) - 1;
+
+/// Parses `FIELDS numfields field [field ...]`, where each field takes `width`
+/// arguments (2 for HSETEX's field-value pairs).
+fn parse_fields(params: &[Value], width: usize) -> Result<&[Value], CommandError> {
+    let [keyword, numfields, rest @ ..] = params else {
+        return Err(CommandError::Err(
+            "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+        ));
+    };
+    if !args::is_option(keyword, "FIELDS") {
+        return Err(CommandError::Err(
+            "Mandatory argument FIELDS is missing or not at the right position".to_string(),
+        ));
+    }
+    let numfields = args::parse_i64(numfields)?;
+    if numfields <= 0 {
+        return Err(CommandError::Err(
+            "Parameter `numFields` should be greater than 0".to_string(),
+        ));
+    }
+    if rest.len() != numfields as usize * width {
+        return Err(CommandError::Err(
+            "The `numfields` parameter must match the number of arguments".to_string(),
+        ));
+    }
+    Ok(rest)
+}
+
+/// Drops `fields` from the hash at `key`, and the key too if that empties it.
+fn delete_fields(ctx: &mut Context, key: &[u8], fields: &[&Bytes]) {
+    if let Ok(Some(hash)) = get_hash(ctx, key) {
+        for field in fields {
+            hash.remove(field);
+        }
+        if hash.is_empty() {
+            ctx.store.remove(key);
+        }
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[derive(Clone, Copy)]
+enum ExpireCondition {
+    Always,
+    NoTtl,
+    HasTtl,
+    Greater,
+    Less,
+}
+
+impl ExpireCondition {
+    fn allows(self, current: Option<SystemTime>, new: SystemTime) -> bool {
+        match (self, current) {
+            (ExpireCondition::Always, _) => true,
+            (ExpireCondition::NoTtl, current) => current.is_none(),
+            (ExpireCondition::HasTtl, current) => current.is_some(),
+            // No TTL counts as an infinite one.
+            (ExpireCondition::Greater, current) => current.is_some_and(|current| new > current),
+            (ExpireCondition::Less, current) => current.is_none_or(|current| new < current),
+        }
+    }
+}
+
+/// HEXPIRE, HPEXPIRE, HEXPIREAT and HPEXPIREAT: `key time [NX | XX | GT | LT]
+/// FIELDS numfields field [field ...]`, with `time` in `unit_ms` milliseconds.
+fn expire_fields(
+    params: &[Value],
+    ctx: &mut Context,
+    unit_ms: u64,
+    absolute: bool,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let [key, time, rest @ ..] = params else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let key = args::bulk(key)?;
+    let (condition, rest) = match rest.split_first() {
+        Some((option, rest)) if args::is_option(option, "NX") => (ExpireCondition::NoTtl, rest),
+        Some((option, rest)) if args::is_option(option, "XX") => (ExpireCondition::HasTtl, rest),
+        Some((option, rest)) if args::is_option(option, "GT") => (ExpireCondition::Greater, rest),
+        Some((option, rest)) if args::is_option(option, "LT") => (ExpireCondition::Less, rest),
+        _ => (ExpireCondition::Always, rest),
+    };
+    let fields = parse_fields(rest, 1)?
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let invalid = || {
+        CommandError::Err(format!(
+            "invalid expire time, must be >= 0 and <= {MAX_FIELD_EXPIRY_MS}"
+        ))
+    };
+    let time = u64::try_from(args::parse_i64(time)?).map_err(|_| invalid())?;
+    let now_ms = millis_since_epoch(SystemTime::now());
+    let deadline_ms = time
+        .checked_mul(unit_ms)
+        .and_then(|ms| {
+            if absolute {
+                Some(ms)
+            } else {
+                ms.checked_add(now_ms)
+            }
+        })
+        .filter(|&ms| ms <= MAX_FIELD_EXPIRY_MS)
+        .ok_or_else(invalid)?;
+    let deadline = UNIX_EPOCH + Duration::from_millis(deadline_ms);
+
+    let Some(hash) = get_hash(ctx, key)? else {
+        return Ok(Value::Array(vec![Value::Integer(-2); fields.len()]));
+    };
+    let mut expired = vec![];
+    let results = fields
+        .iter()
+        .map(|field| {
+            let Some(current) = hash.expiry(field) else {
+                return Value::Integer(-2);
+            };
+            if !condition.allows(current, deadline) {
+                return Value::Integer(0);
+            }
+            if deadline_ms <= now_ms {
+                expired.push(*field);
+                return Value::Integer(2);
+            }
+            hash.set_expiry(field, Some(deadline));
+            Value::Integer(1)
+        })
+        .collect();
+
+    let volatile = hash.has_expiring_fields();
+    delete_fields(ctx, key, &expired);
+    if volatile {
+        ctx.store.volatile_hashes.insert(key.clone());
+    }
+    Ok(Value::Array(results))
+}
+
+/// HEXPIRE key seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+pub fn hexpire(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    expire_fields(params, ctx, 1000, false, "hexpire")
+}
+
+/// HPEXPIRE key milliseconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+pub fn hpexpire(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    expire_fields(params, ctx, 1, false, "hpexpire")
+}
+
+/// HEXPIREAT key unix-time-seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+pub fn hexpireat(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    expire_fields(params, ctx, 1000, true, "hexpireat")
+}
+
+/// HPEXPIREAT key unix-time-milliseconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+pub fn hpexpireat(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    expire_fields(params, ctx, 1, true, "hpexpireat")
+}
+
+#[derive(Clone, Copy)]
+enum TtlReply {
+    Seconds,
+    Millis,
+    UnixSeconds,
+    UnixMillis,
+}
+
+/// HTTL, HPTTL, HEXPIRETIME and HPEXPIRETIME: `key FIELDS numfields field [field ...]`.
+/// Each field answers -2 if missing, -1 without a TTL, or the TTL in `reply` form.
+fn field_ttls(
+    params: &[Value],
+    ctx: &mut Context,
+    reply: TtlReply,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let fields = parse_fields(rest, 1)?;
+    let now_ms = millis_since_epoch(SystemTime::now());
+
+    let hash = get_hash(ctx, args::bulk(key)?)?;
+    let results = fields
+        .iter()
+        .map(|field| {
+            let field = args::bulk(field)?;
+            let ttl = match hash.as_ref().and_then(|hash| hash.expiry(field)) {
+                None => -2,
+                Some(None) => -1,
+                Some(Some(deadline)) => {
+                    let deadline_ms = millis_since_epoch(deadline);
+                    let left_ms = deadline_ms.saturating_sub(now_ms);
+                    let ttl = match reply {
+                        TtlReply::Seconds => (left_ms + 500) / 1000,
+                        TtlReply::Millis => left_ms,
+                        TtlReply::UnixSeconds => deadline_ms / 1000,
+                        TtlReply::UnixMillis => deadline_ms,
+                    };
+                    ttl as i64
+                }
+            };
+            Ok(Value::Integer(ttl))
+        })
+        .collect::<Result<_, CommandError>>()?;
+    Ok(Value::Array(results))
+}
+
+/// HTTL key FIELDS numfields field [field ...]
+pub fn httl(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    field_ttls(params, ctx, TtlReply::Seconds, "httl")
+}
+
+/// HPTTL key FIELDS numfields field [field ...]
+pub fn hpttl(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    field_ttls(params, ctx, TtlReply::Millis, "hpttl")
+}
+
+/// HEXPIRETIME key FIELDS numfields field [field ...]
+pub fn hexpiretime(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    field_ttls(params, ctx, TtlReply::UnixSeconds, "hexpiretime")
+}
+
+/// HPEXPIRETIME key FIELDS numfields field [field ...]
+pub fn hpexpiretime(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    field_ttls(params, ctx, TtlReply::UnixMillis, "hpexpiretime")
+}
+
+/// HPERSIST key FIELDS numfields field [field ...]
+pub fn hpersist(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hpersist".to_string()));
+    };
+    let fields = parse_fields(rest, 1)?;
+
+    let mut hash = get_hash(ctx, args::bulk(key)?)?;
+    let results = fields
+        .iter()
+        .map(|field| {
+            let field = args::bulk(field)?;
+            let Some(hash) = hash.as_deref_mut() else {
+                return Ok(Value::Integer(-2));
+            };
+            Ok(Value::Integer(match hash.expiry(field) {
+                None => -2,
+                Some(None) => -1,
+                Some(Some(_)) => {
+                    hash.set_expiry(field, None);
+                    1
+                }
+            }))
+        })
+        .collect::<Result<_, CommandError>>()?;
+    Ok(Value::Array(results))
+}
+
+/// HGETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+///   PXAT unix-time-milliseconds | PERSIST] FIELDS numfields field [field ...]
+pub fn hgetex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hgetex".to_string()));
+    };
+    let key = args::bulk(key)?;
+
+    // `None` leaves TTLs alone, `Some(None)` persists the fields.
+    let (new_expiry, rest) = match rest {
+        [option, rest @ ..] if args::is_option(option, "PERSIST") => (Some(None), rest),
+        [option, arg, rest @ ..] if is_expiry_option(option) => {
+            (Some(Some(parse_expiry(option, arg, "hgetex")?)), rest)
+        }
+        rest => (None, rest),
+    };
+    let fields = parse_fields(rest, 1)?
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(hash) = get_hash(ctx, key)? else {
+        return Ok(Value::Array(vec![Value::NullString; fields.len()]));
+    };
+    let values = fields
+        .iter()
+        .map(|field| bulk_or_null(hash.get(field)))
+        .collect();
+
+    if let Some(expiry) = new_expiry {
+        let now = SystemTime::now();
+        let mut expired = vec![];
+        for field in &fields {
+            match expiry {
+                Some(deadline) if deadline <= now && hash.contains_key(field) => {
+                    expired.push(*field)
+                }
+                _ => {
+                    hash.set_expiry(field, expiry);
+                }
+            }
+        }
+        let volatile = hash.has_expiring_fields();
+        delete_fields(ctx, key, &expired);
+        if volatile {
+            ctx.store.volatile_hashes.insert(key.clone());
+        }
+    }
+    Ok(Value::Array(values))
+}
+
+/// HSETEX key [FNX | FXX] [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+///   PXAT unix-time-milliseconds | KEEPTTL] FIELDS numfields field value [field value ...]
+pub fn hsetex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, mut rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("hsetex".to_string()));
+    };
+    let key = args::bulk(key)?;
+
+    let (mut only_new, mut only_existing, mut keep_ttl, mut expiry) = (false, false, false, None);
+    loop {
+        match rest {
+            [option, tail @ ..] if args::is_option(option, "FNX") && !only_existing => {
+                only_new = true;
+                rest = tail;
+            }
+            [option, tail @ ..] if args::is_option(option, "FXX") && !only_new => {
+                only_existing = true;
+                rest = tail;
+            }
+            [option, tail @ ..]
+                if args::is_option(option, "KEEPTTL") && !keep_ttl && expiry.is_none() =>
+            {
+                keep_ttl = true;
+                rest = tail;
+            }
+            [option, arg, tail @ ..]
+                if is_expiry_option(option) && !keep_ttl && expiry.is_none() =>
+            {
+                expiry = Some(parse_expiry(option, arg, "hsetex")?);
+                rest = tail;
+            }
+            [option, ..] if !args::is_option(option, "FIELDS") => {
+                return Err(CommandError::Syntax);
+            }
+            _ => break,
+        }
+    }
+    let pairs = parse_fields(rest, 2)?
+        .chunks(2)
+        .map(|pair| Ok((args::bulk(&pair[0])?, args::bulk(&pair[1])?)))
+        .collect::<Result<Vec<_>, CommandError>>()?;
+
+    let hash = get_or_create_hash(ctx, key)?;
+    let allowed = if only_new {
+        pairs.iter().all(|(field, _)| !hash.contains_key(field))
+    } else if only_existing {
+        pairs.iter().all(|(field, _)| hash.contains_key(field))
+    } else {
+        true
+    };
+    if !allowed {
+        if hash.is_empty() {
+            ctx.store.remove(key);
+        }
+        return Ok(Value::Integer(0));
+    }
+
+    let now = SystemTime::now();
+    let mut expired = vec![];
+    for (field, value) in &pairs {
+        if keep_ttl {
+            hash.update(field, (*value).clone());
+        } else {
+            hash.insert((*field).clone(), (*value).clone());
+        }
+        match expiry {
+            Some(deadline) if deadline <= now => expired.push(*field),
+            Some(deadline) => {
+                hash.set_expiry(field, Some(deadline));
+            }
+            None => {}
+        }
+    }
+    let volatile = hash.has_expiring_fields();
+    delete_fields(ctx, key, &expired);
+    if volatile {
+        ctx.store.volatile_hashes.insert(key.clone());
+    }
+    Ok(Value::Integer(1))
+}
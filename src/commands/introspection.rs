@@ -0,0 +1,279 @@
+use super::Context;
+use super::registry::{self, CommandSpec, Flag};
+use crate::error::CommandError;
+use crate::glob;
+use crate::resp::Value;
+use bytes::Bytes;
+
+pub(super) const COMMANDS: &[CommandSpec] =
+    &[CommandSpec::new("command", "server", -1, eval_command)
+        .flags(&[Flag::Loading, Flag::Stale])
+        .summary("Returns detailed information about all commands.")
+        .subcommands(&[
+            CommandSpec::new("count", "server", 2, eval_count)
+                .flags(&[Flag::Loading, Flag::Stale])
+                .summary("Returns a count of commands."),
+            CommandSpec::new("docs", "server", -2, eval_docs)
+                .flags(&[Flag::Loading, Flag::Stale])
+                .summary("Returns documentary information about one, multiple or all commands."),
+            CommandSpec::new("getkeys", "server", -3, eval_getkeys)
+                .flags(&[Flag::Loading, Flag::Stale])
+                .summary("Extracts the key names from an arbitrary command."),
+            CommandSpec::new("info", "server", -2, eval_info)
+                .flags(&[Flag::Loading, Flag::Stale])
+                .summary("Returns information about one, multiple or all commands."),
+            CommandSpec::new("list", "server", -2, eval_list)
+                .flags(&[Flag::Loading, Flag::Stale])
+                .summary("Returns a list of command names."),
+        ])];
+
+fn bulk(s: &str) -> Value {
+    Value::BulkString(Bytes::copy_from_slice(s.as_bytes()))
+}
+
+fn full_name(spec: &CommandSpec, parent: Option<&CommandSpec>) -> String {
+    match parent {
+        Some(parent) => format!("{}|{}", parent.name, spec.name),
+        None => spec.name.to_string(),
+    }
+}
+
+/// Each top-level command, followed by its subcommands, with their parent.
+fn every_command() -> impl Iterator<Item = (&'static CommandSpec, Option<&'static CommandSpec>)> {
+    registry::all().flat_map(|spec| {
+        std::iter::once((spec, None))
+            .chain(spec.subcommands.iter().map(move |sub| (sub, Some(spec))))
+    })
+}
+
+/// Looks up `container|sub` as well as plain names.
+fn find_command(name: &[u8]) -> Option<(&'static CommandSpec, Option<&'static CommandSpec>)> {
+    match name.iter().position(|&b| b == b'|') {
+        Some(split) => {
+            let parent = registry::lookup(&name[..split])?;
+            Some((parent.subcommand(&name[split + 1..])?, Some(parent)))
+        }
+        None => Some((registry::lookup(name)?, None)),
+    }
+}
+
+fn key_specs(spec: &CommandSpec) -> Value {
+    if spec.first_key <= 0 && spec.key_finder.is_none() {
+        return Value::Array(vec![]);
+    }
+
+    let (begin_search, find_keys) = if spec.key_finder.is_some() {
+        (
+            Value::Map(vec![
+                (bulk("type"), bulk("unknown")),
+                (bulk("spec"), Value::Map(vec![])),
+            ]),
+            Value::Map(vec![
+                (bulk("type"), bulk("unknown")),
+                (bulk("spec"), Value::Map(vec![])),
+            ]),
+        )
+    } else {
+        let last_key = if spec.last_key < 0 {
+            spec.last_key
+        } else {
+            spec.last_key - spec.first_key
+        };
+        (
+            Value::Map(vec![
+                (bulk("type"), bulk("index")),
+                (
+                    bulk("spec"),
+                    Value::Map(vec![(bulk("index"), Value::Integer(spec.first_key))]),
+                ),
+            ]),
+            Value::Map(vec![
+                (bulk("type"), bulk("range")),
+                (
+                    bulk("spec"),
+                    Value::Map(vec![
+                        (bulk("lastkey"), Value::Integer(last_key)),
+                        (bulk("keystep"), Value::Integer(spec.step)),
+                        (bulk("limit"), Value::Integer(0)),
+                    ]),
+                ),
+            ]),
+        )
+    };
+
+    Value::Array(vec![Value::Map(vec![
+        (bulk("flags"), Value::Set(vec![])),
+        (bulk("begin_search"), begin_search),
+        (bulk("find_keys"), find_keys),
+    ])])
+}
+
+fn command_info(spec: &CommandSpec, parent: Option<&CommandSpec>) -> Value {
+    let mut flags = spec
+        .flags
+        .iter()
+        .map(|flag| Value::SimpleString(flag.as_str().to_string()))
+        .collect::<Vec<_>>();
+    if spec.key_finder.is_some() {
+        flags.push(Value::SimpleString("movablekeys".to_string()));
+    }
+    let categories = spec
+        .acl_categories()
+        .into_iter()
+        .map(|category| Value::SimpleString(category.to_string()))
+        .collect();
+    let subcommands = spec
+        .subcommands
+        .iter()
+        .map(|sub| command_info(sub, Some(spec)))
+        .collect();
+
+    Value::Array(vec![
+        bulk(&full_name(spec, parent)),
+        Value::Integer(spec.arity),
+        Value::Set(flags),
+        Value::Integer(spec.first_key),
+        Value::Integer(spec.last_key),
+        Value::Integer(spec.step),
+        Value::Set(categories),
+        Value::Set(vec![]),
+        key_specs(spec),
+        Value::Array(subcommands),
+    ])
+}
+
+fn command_docs(spec: &CommandSpec) -> Value {
+    let mut docs = vec![
+        (bulk("summary"), bulk(spec.summary)),
+        (bulk("group"), bulk(spec.group)),
+    ];
+    if !spec.subcommands.is_empty() {
+        let subcommands = spec
+            .subcommands
+            .iter()
+            .map(|sub| (bulk(&full_name(sub, Some(spec))), command_docs(sub)))
+            .collect();
+        docs.push((bulk("subcommands"), Value::Map(subcommands)));
+    }
+    Value::Map(docs)
+}
+
+/// COMMAND
+pub fn eval_command(_params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    let all = registry::all()
+        .map(|spec| command_info(spec, None))
+        .collect();
+    Ok(Value::Array(all))
+}
+
+/// COMMAND COUNT
+pub fn eval_count(_params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    Ok(Value::Integer(registry::count() as i64))
+}
+
+/// COMMAND INFO [command-name ...]
+pub fn eval_info(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    if params.is_empty() {
+        return eval_command(params, ctx);
+    }
+    let infos = params
+        .iter()
+        .map(|param| match param {
+            Value::BulkString(name) => match find_command(name) {
+                Some((spec, parent)) => command_info(spec, parent),
+                None => Value::NullString,
+            },
+            _ => Value::NullString,
+        })
+        .collect();
+    Ok(Value::Array(infos))
+}
+
+/// COMMAND DOCS [command-name ...]
+pub fn eval_docs(params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    let docs = if params.is_empty() {
+        registry::all()
+            .map(|spec| (bulk(spec.name), command_docs(spec)))
+            .collect()
+    } else {
+        // Unknown names are left out of the reply.
+        params
+            .iter()
+            .filter_map(|param| match param {
+                Value::BulkString(name) => find_command(name),
+                _ => None,
+            })
+            .map(|(spec, parent)| (bulk(&full_name(spec, parent)), command_docs(spec)))
+            .collect()
+    };
+    Ok(Value::Map(docs))
+}
+
+/// COMMAND LIST [FILTERBY <MODULE module-name | ACLCAT category | PATTERN pattern>]
+pub fn eval_list(params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    let filter = match params {
+        [] => ListFilter::All,
+        [
+            Value::BulkString(filterby),
+            Value::BulkString(kind),
+            Value::BulkString(arg),
+        ] if filterby.eq_ignore_ascii_case(b"FILTERBY") => {
+            let arg = String::from_utf8_lossy(arg).to_ascii_lowercase();
+            if kind.eq_ignore_ascii_case(b"MODULE") {
+                ListFilter::Module
+            } else if kind.eq_ignore_ascii_case(b"ACLCAT") {
+                ListFilter::AclCategory(format!("@{arg}"))
+            } else if kind.eq_ignore_ascii_case(b"PATTERN") {
+                ListFilter::Pattern(arg)
+            } else {
+                return Err(CommandError::Syntax);
+            }
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+
+    let names = every_command()
+        .map(|(spec, parent)| (spec, full_name(spec, parent)))
+        .filter(|(spec, name)| match &filter {
+            ListFilter::All => true,
+            // No modules can be loaded, so nothing belongs to one.
+            ListFilter::Module => false,
+            ListFilter::AclCategory(category) => spec.acl_categories().contains(&category.as_str()),
+            ListFilter::Pattern(pattern) => glob::matches(pattern.as_bytes(), name.as_bytes()),
+        })
+        .map(|(_, name)| bulk(&name))
+        .collect();
+    Ok(Value::Array(names))
+}
+
+enum ListFilter {
+    All,
+    Module,
+    AclCategory(String),
+    Pattern(String),
+}
+
+/// COMMAND GETKEYS command [arg ...]
+pub fn eval_getkeys(params: &[Value], _ctx: &mut Context) -> Result<Value, CommandError> {
+    let spec = match registry::resolve(params) {
+        Ok((spec, _)) => spec,
+        Err(CommandError::WrongArity(_)) => {
+            return Err(CommandError::Err(
+                "Invalid number of arguments specified for command".to_string(),
+            ));
+        }
+        Err(_) => return Err(CommandError::Err("Invalid command specified".to_string())),
+    };
+
+    let positions = spec.key_positions(params).ok_or_else(|| {
+        CommandError::Err("Invalid number of arguments specified for command".to_string())
+    })?;
+    if positions.is_empty() {
+        return Err(CommandError::Err(
+            "The command has no key arguments".to_string(),
+        ));
+    }
+    Ok(Value::Array(
+        positions.into_iter().map(|i| params[i].clone()).collect(),
+    ))
+}
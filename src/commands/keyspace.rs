@@ -0,0 +1,308 @@
+use super::registry::{CommandSpec, Flag};
+use super::scan::{self, ScanArgs, Target};
+use super::{Context, args};
+use crate::db::{self, Data, StoredValue};
+use crate::error::CommandError;
+use crate::glob;
+use crate::resp::Value;
+use bytes::Bytes;
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("del", "generic", -2, eval_del)
+        .flags(&[Flag::Write])
+        .keys(1, -1, 1)
+        .summary("Deletes one or more keys."),
+    CommandSpec::new("unlink", "generic", -2, eval_unlink)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, -1, 1)
+        .summary("Asynchronously deletes one or more keys."),
+    CommandSpec::new("exists", "generic", -2, eval_exists)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, -1, 1)
+        .summary("Determines whether one or more keys exist."),
+    CommandSpec::new("touch", "generic", -2, eval_touch)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, -1, 1)
+        .summary("Returns the number of existing keys out of those specified after updating the time they were last accessed."),
+    CommandSpec::new("type", "generic", 2, eval_type)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Determines the type of value stored at a key."),
+    CommandSpec::new("rename", "generic", 3, eval_rename)
+        .flags(&[Flag::Write])
+        .keys(1, 2, 1)
+        .summary("Renames a key and overwrites the destination."),
+    CommandSpec::new("renamenx", "generic", 3, eval_renamenx)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 2, 1)
+        .summary("Renames a key only when the target key name doesn't exist."),
+    CommandSpec::new("copy", "generic", -3, eval_copy)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 2, 1)
+        .summary("Copies the value of a key to a new key."),
+    CommandSpec::new("randomkey", "generic", 1, eval_randomkey)
+        .flags(&[Flag::ReadOnly])
+        .summary("Returns a random key name from the database."),
+    CommandSpec::new("keys", "generic", 2, eval_keys)
+        .flags(&[Flag::ReadOnly])
+        .summary("Returns all key names that match a pattern."),
+    CommandSpec::new("scan", "generic", -2, eval_scan)
+        .flags(&[Flag::ReadOnly])
+        .summary("Iterates over the key names in the database."),
+    CommandSpec::new("dbsize", "server", 1, eval_dbsize)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .summary("Returns the number of keys in the database."),
+    CommandSpec::new("flushdb", "server", -1, eval_flushdb)
+        .flags(&[Flag::Write])
+        .summary("Removes all keys from the current database."),
+    CommandSpec::new("flushall", "server", -1, eval_flushall)
+        .flags(&[Flag::Write])
+        .summary("Removes all keys from all databases."),
+];
+
+fn ok() -> Value {
+    Value::SimpleString("OK".to_string())
+}
+
+/// Removes `keys`, returning how many existed. Blocked clients are retried,
+/// so XREADGROUP can tell its stream went away.
+fn delete(params: &[Value], ctx: &mut Context, lazy: bool) -> Result<Value, CommandError> {
+    let mut deleted = 0;
+    for key in params {
+        let key = args::bulk(key)?;
+        if let Some(stored) = ctx.store.remove(key) {
+            deleted += 1;
+            ctx.store.blocking.signal(key);
+            if lazy {
+                db::free_lazily(stored);
+            }
+        }
+    }
+    Ok(Value::Integer(deleted))
+}
+
+/// DEL key [key ...]
+pub fn eval_del(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    delete(params, ctx, false)
+}
+
+/// UNLINK key [key ...]
+///
+/// Like DEL, but big values are freed on another thread.
+pub fn eval_unlink(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    delete(params, ctx, true)
+}
+
+/// How many of `keys` exist, counting a key each time it is named.
+fn count_existing(keys: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let mut found = 0;
+    for key in keys {
+        if ctx.store.get(args::bulk(key)?).is_some() {
+            found += 1;
+        }
+    }
+    Ok(Value::Integer(found))
+}
+
+/// EXISTS key [key ...]
+pub fn eval_exists(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    count_existing(params, ctx)
+}
+
+/// TOUCH key [key ...]
+///
+/// Nothing tracks access times, so this only counts the keys, like EXISTS.
+pub fn eval_touch(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    count_existing(params, ctx)
+}
+
+/// TYPE key
+pub fn eval_type(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("type".to_string()));
+    };
+    let type_name = ctx
+        .store
+        .get(args::bulk(key)?)
+        .map_or("none", |stored| stored.value.type_name());
+    Ok(Value::SimpleString(type_name.to_string()))
+}
+
+/// Stores a renamed or copied value at `key`, keeping hash fields with TTLs
+/// in sight of the expiry cycle and retrying clients blocked on `key`.
+fn put(ctx: &mut Context, key: &Bytes, stored: StoredValue) {
+    if let Data::Hash(hash) = &stored.value
+        && hash.has_expiring_fields()
+    {
+        ctx.store.volatile_hashes.insert(key.clone());
+    }
+    ctx.store.insert(key.clone(), stored);
+    ctx.store.blocking.signal(key);
+}
+
+/// Moves `key` to `newkey` with its TTL, unless `nx` and `newkey` exists.
+/// Returns whether it moved.
+fn rename(ctx: &mut Context, key: &Bytes, newkey: &Bytes, nx: bool) -> Result<bool, CommandError> {
+    if ctx.store.get(key).is_none() {
+        return Err(CommandError::NoSuchKey);
+    }
+    if key == newkey {
+        return Ok(!nx);
+    }
+    if nx && ctx.store.get(newkey).is_some() {
+        return Ok(false);
+    }
+    let stored = ctx.store.remove(key).expect("checked above");
+    ctx.store.blocking.signal(key);
+    put(ctx, newkey, stored);
+    Ok(true)
+}
+
+/// RENAME key newkey
+pub fn eval_rename(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, newkey] = params else {
+        return Err(CommandError::WrongArity("rename".to_string()));
+    };
+    rename(ctx, args::bulk(key)?, args::bulk(newkey)?, false)?;
+    Ok(ok())
+}
+
+/// RENAMENX key newkey
+pub fn eval_renamenx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, newkey] = params else {
+        return Err(CommandError::WrongArity("renamenx".to_string()));
+    };
+    let renamed = rename(ctx, args::bulk(key)?, args::bulk(newkey)?, true)?;
+    Ok(Value::Integer(renamed as i64))
+}
+
+/// COPY source destination [DB destination-db] [REPLACE]
+///
+/// There is only database 0, so that is the only DB accepted.
+pub fn eval_copy(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination, options @ ..] = params else {
+        return Err(CommandError::WrongArity("copy".to_string()));
+    };
+    let (source, destination) = (args::bulk(source)?, args::bulk(destination)?);
+    let mut replace = false;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        if args::is_option(option, "REPLACE") {
+            replace = true;
+        } else if args::is_option(option, "DB") {
+            let db = options.next().ok_or(CommandError::Syntax)?;
+            if args::parse_i64(db)? != 0 {
+                return Err(CommandError::Err("DB index is out of range".to_string()));
+            }
+        } else {
+            return Err(CommandError::Syntax);
+        }
+    }
+    if source == destination {
+        return Err(CommandError::Err(
+            "source and destination objects are the same".to_string(),
+        ));
+    }
+
+    let Some(stored) = ctx.store.get(source) else {
+        return Ok(Value::Integer(0));
+    };
+    let copy = StoredValue {
+        value: stored.value.clone(),
+        expiry: stored.expiry,
+    };
+    if !replace && ctx.store.get(destination).is_some() {
+        return Ok(Value::Integer(0));
+    }
+    put(ctx, destination, copy);
+    Ok(Value::Integer(1))
+}
+
+/// RANDOMKEY
+pub fn eval_randomkey(_params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let mut rng = rand::thread_rng();
+    // Expired keys drawn along the way are deleted, so this always ends.
+    loop {
+        let Some((key, _)) = ctx.store.db.random(&mut rng) else {
+            return Ok(Value::NullString);
+        };
+        let key = key.clone();
+        if ctx.store.get(&key).is_some() {
+            return Ok(Value::BulkString(key));
+        }
+    }
+}
+
+/// KEYS pattern
+pub fn eval_keys(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [pattern] = params else {
+        return Err(CommandError::WrongArity("keys".to_string()));
+    };
+    let pattern = args::bulk(pattern)?;
+    // Expired keys linger until something touches them, but no longer exist.
+    let keys = ctx
+        .store
+        .db
+        .iter()
+        .filter(|(key, stored)| !stored.is_expired() && glob::matches(pattern, key))
+        .map(|(key, _)| Value::BulkString(key.clone()))
+        .collect();
+    Ok(Value::Array(keys))
+}
+
+/// SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]
+pub fn eval_scan(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let scan_args = ScanArgs::parse(params, Target::Keyspace)?;
+    let (cursor, found) =
+        scan_args.walk(|cursor, found| ctx.store.db.scan(cursor, |key, _| found.push(key.clone())));
+
+    // Filter once the cursor is settled; looking a key up may expire it.
+    let mut keys = vec![];
+    for key in found {
+        if !scan_args.matches(&key) {
+            continue;
+        }
+        let Some(stored) = ctx.store.get(&key) else {
+            continue;
+        };
+        if scan_args
+            .type_name
+            .is_none_or(|type_name| stored.value.type_name() == type_name)
+        {
+            keys.push(Value::BulkString(key));
+        }
+    }
+    Ok(scan::reply(cursor, keys))
+}
+
+/// DBSIZE
+///
+/// Like Redis, counts expired keys nothing has removed yet, rather than
+/// walking the keyspace to skip them.
+pub fn eval_dbsize(_params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    Ok(Value::Integer(ctx.store.db.len() as i64))
+}
+
+/// Parses the `[ASYNC | SYNC]` of FLUSHDB and FLUSHALL into whether to free lazily.
+fn parse_flush_mode(params: &[Value]) -> Result<bool, CommandError> {
+    match params {
+        [] => Ok(false),
+        [mode] if args::is_option(mode, "ASYNC") => Ok(true),
+        [mode] if args::is_option(mode, "SYNC") => Ok(false),
+        _ => Err(CommandError::Syntax),
+    }
+}
+
+/// FLUSHDB [ASYNC | SYNC]
+pub fn eval_flushdb(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    ctx.store.flush(parse_flush_mode(params)?);
+    Ok(ok())
+}
+
+/// FLUSHALL [ASYNC | SYNC]
+///
+/// The same as FLUSHDB, with only database 0 to flush.
+pub fn eval_flushall(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    ctx.store.flush(parse_flush_mode(params)?);
+    Ok(ok())
+}
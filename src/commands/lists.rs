@@ -1,33 +1,679 @@
-use crate::db::{DB, StoredValue};
+use super::registry::{self, CommandSpec, Flag};
+use super::{Context, args};
+use crate::db::{Data, StoredValue};
+use crate::error::CommandError;
+use crate::quicklist::QuickList;
 use crate::resp::Value;
-use std::sync::MutexGuard;
+use bytes::Bytes;
 
-pub fn rpush(params: &[Value], mut store: MutexGuard<DB>) -> Result<Value, String> {
-    if params.len() < 2 {
-        return Err("RPUSH requires at least 2 arguments".to_string());
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("rpush", "list", -3, rpush)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Appends one or more elements to a list. Creates the key if it doesn't exist."),
+    CommandSpec::new("lpush", "list", -3, lpush)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Prepends one or more elements to a list. Creates the key if it doesn't exist."),
+    CommandSpec::new("rpushx", "list", -3, rpushx)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Appends an element to a list only when the list exists."),
+    CommandSpec::new("lpushx", "list", -3, lpushx)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Prepends one or more elements to a list only when the list exists."),
+    CommandSpec::new("lpop", "list", -2, lpop)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the first elements in a list after removing it. Deletes the list if the last element was popped."),
+    CommandSpec::new("rpop", "list", -2, rpop)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns and removes the last elements of a list. Deletes the list if the last element was popped."),
+    CommandSpec::new("lrange", "list", 4, lrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns a range of elements from a list."),
+    CommandSpec::new("llen", "list", 2, llen)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the length of a list."),
+    CommandSpec::new("lindex", "list", 3, lindex)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns an element from a list by its index."),
+    CommandSpec::new("lset", "list", 4, lset)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Sets the value of an element in a list by its index."),
+    CommandSpec::new("linsert", "list", 5, linsert)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Inserts an element before or after another element in a list."),
+    CommandSpec::new("lrem", "list", 4, lrem)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Removes elements from a list. Deletes the list if the last element was removed."),
+    CommandSpec::new("ltrim", "list", 4, ltrim)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Removes elements from both ends a list. Deletes the list if all elements were trimmed."),
+    CommandSpec::new("lpos", "list", -3, lpos)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns the index of matching elements in a list."),
+    CommandSpec::new("lmove", "list", 5, lmove)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 2, 1)
+        .summary("Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved."),
+    CommandSpec::new("rpoplpush", "list", 3, rpoplpush)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 2, 1)
+        .summary("Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped."),
+    CommandSpec::new("blpop", "list", -3, blpop)
+        .flags(&[Flag::Write, Flag::Blocking])
+        .keys(1, -2, 1)
+        .summary("Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+    CommandSpec::new("brpop", "list", -3, brpop)
+        .flags(&[Flag::Write, Flag::Blocking])
+        .keys(1, -2, 1)
+        .summary("Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+    CommandSpec::new("blmove", "list", 6, blmove)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Blocking])
+        .keys(1, 2, 1)
+        .summary("Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved."),
+    CommandSpec::new("brpoplpush", "list", 4, brpoplpush)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Blocking])
+        .keys(1, 2, 1)
+        .summary("Pops an element from a list, pushes it to another list and returns it. Block until an element is available otherwise. Deletes the list if the last element was popped."),
+    CommandSpec::new("blmpop", "list", -5, blmpop)
+        .flags(&[Flag::Write, Flag::Blocking])
+        .key_finder(blmpop_keys)
+        .summary("Pops the first element from one of multiple lists. Blocks until an element is available otherwise. Deletes the list if the last element was popped."),
+    CommandSpec::new("lmpop", "list", -4, lmpop)
+        .flags(&[Flag::Write])
+        .key_finder(lmpop_keys)
+        .summary("Returns multiple elements from a list after removing them. Deletes the list if the last element was popped."),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum End {
+    Left,
+    Right,
+}
+
+impl End {
+    fn parse(arg: &Value) -> Result<Self, CommandError> {
+        if args::is_option(arg, "LEFT") {
+            Ok(End::Left)
+        } else if args::is_option(arg, "RIGHT") {
+            Ok(End::Right)
+        } else {
+            Err(CommandError::Syntax)
+        }
     }
+}
 
-    let Value::BulkString(list_name) = &params[0] else {
-        return Err("Bad args given to RPUSH".to_string());
-    };
+/// The list stored at `key`, `None` if it is missing, or WRONGTYPE.
+fn get_list<'a>(
+    ctx: &'a mut Context,
+    key: &[u8],
+) -> Result<Option<&'a mut QuickList>, CommandError> {
+    match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        None => Ok(None),
+        Some(Data::List(list)) => Ok(Some(list)),
+        Some(_) => Err(CommandError::WrongType),
+    }
+}
 
-    // Get or create the list in one operation
-    let entry = store
-        .db
-        .entry(list_name.to_string())
-        .or_insert(StoredValue {
-            value: Value::Array(vec![]),
-            expiry: None,
-        });
-    let Value::Array(list) = &mut entry.value else {
-        return Err(
-            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+/// Like `get_list`, but creates an empty list when the key is missing.
+fn get_or_create_list<'a>(
+    ctx: &'a mut Context,
+    key: &Value,
+) -> Result<&'a mut QuickList, CommandError> {
+    let key = args::bulk(key)?;
+    if get_list(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::List(QuickList::new()),
+                expiry: None,
+            },
         );
+    }
+    Ok(get_list(ctx, key)?.expect("list was just created"))
+}
+
+/// Lists never exist empty: removing the last element removes the key.
+fn remove_if_empty(ctx: &mut Context, key: &[u8]) {
+    if matches!(get_list(ctx, key), Ok(Some(list)) if list.is_empty()) {
+        ctx.store.remove(key);
+    }
+}
+
+fn push(list: &mut QuickList, end: End, element: &[u8]) {
+    match end {
+        End::Left => list.push_front(element),
+        End::Right => list.push_back(element),
+    }
+}
+
+fn pop(list: &mut QuickList, end: End) -> Option<Bytes> {
+    match end {
+        End::Left => list.pop_front(),
+        End::Right => list.pop_back(),
+    }
+}
+
+fn bulk_strings<'a>(elements: impl Iterator<Item = &'a [u8]>) -> Value {
+    Value::Array(
+        elements
+            .map(|element| Value::BulkString(Bytes::copy_from_slice(element)))
+            .collect(),
+    )
+}
+
+/// Resolves a possibly negative index against `len`, `None` when out of range.
+fn element_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+/// Clamps an inclusive `start..=stop` pair the way LRANGE and LTRIM do,
+/// `None` when the range is empty.
+fn clamp_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
     };
+    let stop = if stop < 0 { len + stop } else { stop }.min(len - 1);
+    (start <= stop && start < len).then_some((start as usize, stop as usize))
+}
 
-    // Push all elements
-    for elem in &params[1..] {
-        list.push(elem.clone());
+fn push_command(
+    params: &[Value],
+    ctx: &mut Context,
+    end: End,
+    only_existing: bool,
+) -> Result<Value, CommandError> {
+    let Some((key, elements)) = params.split_first() else {
+        return Err(CommandError::Syntax);
+    };
+    let elements = elements
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+    let list = if only_existing {
+        match get_list(ctx, args::bulk(key)?)? {
+            Some(list) => list,
+            None => return Ok(Value::Integer(0)),
+        }
+    } else {
+        get_or_create_list(ctx, key)?
+    };
+    for element in elements {
+        push(list, end, element);
     }
+    let len = list.len();
+    ctx.store.blocking.signal(args::bulk(key)?);
+    Ok(Value::Integer(len as i64))
+}
+
+/// RPUSH key element [element ...]
+pub fn rpush(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    push_command(params, ctx, End::Right, false)
+}
+
+/// LPUSH key element [element ...]
+pub fn lpush(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    push_command(params, ctx, End::Left, false)
+}
+
+/// RPUSHX key element [element ...]
+pub fn rpushx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    push_command(params, ctx, End::Right, true)
+}
+
+/// LPUSHX key element [element ...]
+pub fn lpushx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    push_command(params, ctx, End::Left, true)
+}
+
+/// Pops up to `count` elements from one end, deleting the key once it's empty.
+fn pop_many(
+    ctx: &mut Context,
+    key: &[u8],
+    end: End,
+    count: usize,
+) -> Result<Option<Vec<Value>>, CommandError> {
+    let Some(list) = get_list(ctx, key)? else {
+        return Ok(None);
+    };
+    let popped = (0..count)
+        .map_while(|_| pop(list, end))
+        .map(Value::BulkString)
+        .collect();
+    remove_if_empty(ctx, key);
+    Ok(Some(popped))
+}
+
+fn pop_command(params: &[Value], ctx: &mut Context, end: End) -> Result<Value, CommandError> {
+    match params {
+        [key] => {
+            let popped = pop_many(ctx, args::bulk(key)?, end, 1)?;
+            Ok(popped
+                .and_then(|mut popped| popped.pop())
+                .unwrap_or(Value::NullString))
+        }
+        [key, count] => {
+            let count = args::parse_i64(count)?;
+            if count < 0 {
+                return Err(CommandError::Err(
+                    "value is out of range, must be positive".to_string(),
+                ));
+            }
+            let popped = pop_many(ctx, args::bulk(key)?, end, count as usize)?;
+            Ok(popped.map_or(Value::NullArray, Value::Array))
+        }
+        _ => Err(CommandError::Syntax),
+    }
+}
+
+/// LPOP key [count]
+pub fn lpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    pop_command(params, ctx, End::Left)
+}
+
+/// RPOP key [count]
+pub fn rpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    pop_command(params, ctx, End::Right)
+}
+
+/// LRANGE key start stop
+pub fn lrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, start, stop] = params else {
+        return Err(CommandError::WrongArity("lrange".to_string()));
+    };
+    let (start, stop) = (args::parse_i64(start)?, args::parse_i64(stop)?);
+    let Some(list) = get_list(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Array(vec![]));
+    };
+    Ok(match clamp_range(start, stop, list.len()) {
+        Some((start, stop)) => bulk_strings(list.iter().skip(start).take(stop - start + 1)),
+        None => Value::Array(vec![]),
+    })
+}
+
+/// LLEN key
+pub fn llen(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("llen".to_string()));
+    };
+    let len = get_list(ctx, args::bulk(key)?)?.map_or(0, |list| list.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// LINDEX key index
+pub fn lindex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, index] = params else {
+        return Err(CommandError::WrongArity("lindex".to_string()));
+    };
+    let index = args::parse_i64(index)?;
+    let Some(list) = get_list(ctx, args::bulk(key)?)? else {
+        return Ok(Value::NullString);
+    };
+    Ok(element_index(index, list.len())
+        .and_then(|index| list.get(index))
+        .map_or(Value::NullString, |element| {
+            Value::BulkString(Bytes::copy_from_slice(element))
+        }))
+}
+
+/// LSET key index element
+pub fn lset(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, index, element] = params else {
+        return Err(CommandError::WrongArity("lset".to_string()));
+    };
+    let index = args::parse_i64(index)?;
+    let element = args::bulk(element)?;
+    let list = get_list(ctx, args::bulk(key)?)?.ok_or(CommandError::NoSuchKey)?;
+    let index = element_index(index, list.len()).ok_or(CommandError::OutOfRange)?;
+    list.set(index, element);
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+/// LINSERT key <BEFORE | AFTER> pivot element
+pub fn linsert(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, position, pivot, element] = params else {
+        return Err(CommandError::WrongArity("linsert".to_string()));
+    };
+    let after = if args::is_option(position, "AFTER") {
+        true
+    } else if args::is_option(position, "BEFORE") {
+        false
+    } else {
+        return Err(CommandError::Syntax);
+    };
+
+    let (pivot, element) = (args::bulk(pivot)?, args::bulk(element)?);
+
+    let Some(list) = get_list(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Integer(0));
+    };
+    let Some(found) = list.iter().position(|value| value == pivot) else {
+        return Ok(Value::Integer(-1));
+    };
+    list.insert(if after { found + 1 } else { found }, element);
     Ok(Value::Integer(list.len() as i64))
 }
+
+/// LREM key count element
+pub fn lrem(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, count, element] = params else {
+        return Err(CommandError::WrongArity("lrem".to_string()));
+    };
+    let (key, element) = (args::bulk(key)?, args::bulk(element)?);
+    let count = args::parse_i64(count)?;
+    let Some(list) = get_list(ctx, key)? else {
+        return Ok(Value::Integer(0));
+    };
+
+    let matches = list.iter().filter(|value| value == element).count();
+    let removed = match count {
+        0 => matches,
+        count => matches.min(count.unsigned_abs() as usize),
+    };
+    // Removing the last `removed` matches is the same as keeping the first others.
+    let mut skip = if count < 0 { matches - removed } else { 0 };
+    let mut left = removed;
+    list.retain(|value| {
+        if value != element || left == 0 {
+            return true;
+        }
+        if skip > 0 {
+            skip -= 1;
+            return true;
+        }
+        left -= 1;
+        false
+    });
+
+    remove_if_empty(ctx, key);
+    Ok(Value::Integer(removed as i64))
+}
+
+/// LTRIM key start stop
+pub fn ltrim(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, start, stop] = params else {
+        return Err(CommandError::WrongArity("ltrim".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let (start, stop) = (args::parse_i64(start)?, args::parse_i64(stop)?);
+    if let Some(list) = get_list(ctx, key)? {
+        *list = match clamp_range(start, stop, list.len()) {
+            Some((start, stop)) => list.iter().skip(start).take(stop - start + 1).collect(),
+            None => QuickList::new(),
+        };
+        remove_if_empty(ctx, key);
+    }
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+/// LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+pub fn lpos(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, element, options @ ..] = params else {
+        return Err(CommandError::WrongArity("lpos".to_string()));
+    };
+
+    let (mut rank, mut count, mut max_len) = (1i64, None, 0usize);
+    for pair in options.chunks(2) {
+        let [option, arg] = pair else {
+            return Err(CommandError::Syntax);
+        };
+        if args::is_option(option, "RANK") {
+            rank = args::parse_i64(arg)?;
+            if rank == 0 {
+                return Err(CommandError::Err(
+                    "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string(),
+                ));
+            }
+            if rank == i64::MIN {
+                return Err(CommandError::Err(
+                    "value is out of range, value must between -9223372036854775807 and 9223372036854775807".to_string(),
+                ));
+            }
+        } else if args::is_option(option, "COUNT") {
+            let value = args::parse_i64(arg)?;
+            if value < 0 {
+                return Err(CommandError::Err("COUNT can't be negative".to_string()));
+            }
+            count = Some(value as usize);
+        } else if args::is_option(option, "MAXLEN") {
+            let value = args::parse_i64(arg)?;
+            if value < 0 {
+                return Err(CommandError::Err("MAXLEN can't be negative".to_string()));
+            }
+            max_len = value as usize;
+        } else {
+            return Err(CommandError::Syntax);
+        }
+    }
+
+    let no_match = || match count {
+        Some(_) => Value::Array(vec![]),
+        None => Value::NullString,
+    };
+    let element = args::bulk(element)?;
+    let Some(list) = get_list(ctx, args::bulk(key)?)? else {
+        return Ok(no_match());
+    };
+
+    // COUNT 0 means every match; MAXLEN 0 means scan the whole list.
+    let wanted = match count {
+        Some(0) => usize::MAX,
+        Some(count) => count,
+        None => 1,
+    };
+    let scan = if max_len == 0 { list.len() } else { max_len };
+    let len = list.len();
+    let indexed: Box<dyn Iterator<Item = (usize, &[u8])>> = if rank > 0 {
+        Box::new(list.iter().enumerate())
+    } else {
+        Box::new(
+            list.iter()
+                .rev()
+                .enumerate()
+                .map(|(i, value)| (len - 1 - i, value)),
+        )
+    };
+    let matches = indexed
+        .take(scan)
+        .filter(|&(_, value)| value == element)
+        .map(|(i, _)| i)
+        .skip(rank.unsigned_abs() as usize - 1)
+        .take(wanted)
+        .map(|i| Value::Integer(i as i64))
+        .collect::<Vec<_>>();
+
+    match count {
+        Some(_) => Ok(Value::Array(matches)),
+        None => Ok(matches.into_iter().next().unwrap_or_else(no_match)),
+    }
+}
+
+/// Pops from `source` and pushes onto `destination`, both checked for type first
+/// so a WRONGTYPE destination never loses the element.
+fn move_element(
+    ctx: &mut Context,
+    source: &Value,
+    destination: &Value,
+    from: End,
+    to: End,
+) -> Result<Value, CommandError> {
+    let (source, destination_key) = (args::bulk(source)?, args::bulk(destination)?);
+    if get_list(ctx, source)?.is_none() {
+        return Ok(Value::NullString);
+    }
+    get_list(ctx, destination_key)?;
+
+    let Some(element) = get_list(ctx, source)?.and_then(|list| pop(list, from)) else {
+        return Ok(Value::NullString);
+    };
+    remove_if_empty(ctx, source);
+    push(get_or_create_list(ctx, destination)?, to, &element);
+    ctx.store.blocking.signal(destination_key);
+    Ok(Value::BulkString(element))
+}
+
+/// LMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT>
+pub fn lmove(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination, from, to] = params else {
+        return Err(CommandError::WrongArity("lmove".to_string()));
+    };
+    let (from, to) = (End::parse(from)?, End::parse(to)?);
+    move_element(ctx, source, destination, from, to)
+}
+
+/// RPOPLPUSH source destination
+pub fn rpoplpush(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination] = params else {
+        return Err(CommandError::WrongArity("rpoplpush".to_string()));
+    };
+    move_element(ctx, source, destination, End::Right, End::Left)
+}
+
+fn lmpop_keys(argv: &[Value]) -> Vec<usize> {
+    registry::numkeys_positions(argv, 1)
+}
+
+/// LMPOP numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+pub fn lmpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let request = MpopRequest::parse(params, "lmpop")?;
+    request.pop(ctx)
+}
+
+/// The `numkeys key [key ...] <LEFT | RIGHT> [COUNT count]` tail of LMPOP and BLMPOP.
+struct MpopRequest<'a> {
+    keys: &'a [Value],
+    end: End,
+    count: usize,
+}
+
+impl<'a> MpopRequest<'a> {
+    fn parse(params: &'a [Value], cmd: &str) -> Result<Self, CommandError> {
+        let Some((numkeys, rest)) = params.split_first() else {
+            return Err(CommandError::WrongArity(cmd.to_string()));
+        };
+        let numkeys = args::parse_i64(numkeys)?;
+        if numkeys <= 0 {
+            return Err(CommandError::Err(
+                "numkeys should be greater than 0".to_string(),
+            ));
+        }
+        let numkeys = numkeys as usize;
+        if rest.len() <= numkeys {
+            return Err(CommandError::Syntax);
+        }
+        let (keys, options) = rest.split_at(numkeys);
+        let end = End::parse(&options[0])?;
+        let count = match &options[1..] {
+            [] => 1,
+            [option, count] if args::is_option(option, "COUNT") => {
+                let count = args::parse_i64(count)?;
+                if count <= 0 {
+                    return Err(CommandError::Err(
+                        "count should be greater than 0".to_string(),
+                    ));
+                }
+                count as usize
+            }
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(MpopRequest { keys, end, count })
+    }
+
+    /// Pops from the first non-empty list, or replies with a null array.
+    fn pop(&self, ctx: &mut Context) -> Result<Value, CommandError> {
+        for key in self.keys {
+            let key = args::bulk(key)?;
+            if let Some(popped) = pop_many(ctx, key, self.end, self.count)? {
+                return Ok(Value::Array(vec![
+                    Value::BulkString(key.clone()),
+                    Value::Array(popped),
+                ]));
+            }
+        }
+        Ok(Value::NullArray)
+    }
+}
+
+/// Pops one element from the first non-empty list as a `[key, element]` pair.
+fn bpop(params: &[Value], ctx: &mut Context, end: End, cmd: &str) -> Result<Value, CommandError> {
+    let Some((timeout, keys)) = params.split_last() else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let timeout = args::parse_timeout(timeout)?;
+    for key in keys {
+        let key = args::bulk(key)?;
+        if let Some(mut popped) = pop_many(ctx, key, end, 1)? {
+            let element = popped.pop().expect("lists are never empty");
+            return Ok(Value::Array(vec![Value::BulkString(key.clone()), element]));
+        }
+    }
+    ctx.block(keys, timeout, Value::NullArray)
+}
+
+/// BLPOP key [key ...] timeout
+pub fn blpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    bpop(params, ctx, End::Left, "blpop")
+}
+
+/// BRPOP key [key ...] timeout
+pub fn brpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    bpop(params, ctx, End::Right, "brpop")
+}
+
+/// BLMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT> timeout
+pub fn blmove(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination, from, to, timeout] = params else {
+        return Err(CommandError::WrongArity("blmove".to_string()));
+    };
+    let (from, to) = (End::parse(from)?, End::parse(to)?);
+    let timeout = args::parse_timeout(timeout)?;
+    match move_element(ctx, source, destination, from, to)? {
+        Value::NullString => ctx.block(std::slice::from_ref(source), timeout, Value::NullString),
+        element => Ok(element),
+    }
+}
+
+/// BRPOPLPUSH source destination timeout
+pub fn brpoplpush(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination, timeout] = params else {
+        return Err(CommandError::WrongArity("brpoplpush".to_string()));
+    };
+    let timeout = args::parse_timeout(timeout)?;
+    match move_element(ctx, source, destination, End::Right, End::Left)? {
+        Value::NullString => ctx.block(std::slice::from_ref(source), timeout, Value::NullString),
+        element => Ok(element),
+    }
+}
+
+fn blmpop_keys(argv: &[Value]) -> Vec<usize> {
+    registry::numkeys_positions(argv, 2)
+}
+
+/// BLMPOP timeout numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+pub fn blmpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((timeout, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("blmpop".to_string()));
+    };
+    let timeout = args::parse_timeout(timeout)?;
+    let request = MpopRequest::parse(rest, "blmpop")?;
+    match request.pop(ctx)? {
+        Value::NullArray => ctx.block(request.keys, timeout, Value::NullArray),
+        popped => Ok(popped),
+    }
+}
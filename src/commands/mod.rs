@@ -1,31 +1,92 @@
+mod args;
 mod basics;
+mod hashes;
+mod introspection;
+mod keyspace;
 mod lists;
 mod numbers;
+mod registry;
+mod scan;
+mod sets;
+mod sorted_sets;
+mod streams;
 mod strings;
 
+use crate::blocking::BlockRequest;
+use crate::client::Client;
 use crate::db::DB;
+use crate::error::CommandError;
 use crate::resp::Value;
-use std::sync::MutexGuard;
+use registry::CommandSpec;
+use std::time::Duration;
 
-pub fn eval_command(segments: &Value, store: MutexGuard<DB>) -> Result<Value, String> {
-    match segments {
-        Value::Array(arr) => {
-            match &arr[0] {
-                Value::BulkString(cmd) if cmd == "ECHO" => basics::eval_echo(&arr[1..]),
-                Value::BulkString(cmd) if cmd == "PING" => basics::eval_ping(&arr[1..]),
-                Value::BulkString(cmd) if cmd == "CONFIG" => basics::eval_config(&arr[1..], store),
-                Value::BulkString(cmd) if cmd == "KEYS" => basics::eval_keys(&arr[1..], store),
-
-                Value::BulkString(cmd) if cmd == "SET" => strings::eval_set(&arr[1..], store),
-                Value::BulkString(cmd) if cmd == "GET" => strings::eval_get(&arr[1..], store),
+/// What a handler may touch while it runs: the keyspace, under the server lock,
+/// and the state of the connection that sent the command.
+pub struct Context<'a> {
+    pub store: &'a mut DB,
+    pub client: &'a mut Client,
+}
 
-                Value::BulkString(cmd) if cmd == "RPUSH" => lists::rpush(&arr[1..], store),
+impl Context<'_> {
+    /// Parks the client on `keys` instead of replying. The returned value is
+    /// what the handler should return; the server discards it.
+    pub fn block(
+        &mut self,
+        keys: &[Value],
+        timeout: Option<Duration>,
+        timeout_reply: Value,
+    ) -> Result<Value, CommandError> {
+        let keys = keys
+            .iter()
+            .map(|key| args::bulk(key).cloned())
+            .collect::<Result<_, _>>()?;
+        self.client.blocked = Some(BlockRequest {
+            keys,
+            timeout,
+            timeout_reply: timeout_reply.clone(),
+            retry: None,
+        });
+        Ok(timeout_reply)
+    }
 
-                // Value::BulkString(cmd) if cmd == "INCR" => eval_incr(&arr[1..], store),
-                Value::BulkString(cmd) => Err(format!("Not a valid command: {cmd}")),
-                _ => Err(format!("non-BulkString first: {}", &arr[0].serialize())),
-            }
+    /// Like `block`, but re-runs `retry` rather than the original command once
+    /// a key is ready, e.g. XREAD with `$` pinned to the ID it stood for.
+    pub fn block_with_retry(
+        &mut self,
+        keys: &[Value],
+        timeout: Option<Duration>,
+        timeout_reply: Value,
+        retry: Value,
+    ) -> Result<Value, CommandError> {
+        let reply = self.block(keys, timeout, timeout_reply)?;
+        if let Some(request) = &mut self.client.blocked {
+            request.retry = Some(retry);
         }
-        _ => Err("non-array command".to_string()),
+        Ok(reply)
     }
 }
+
+/// Every command the server knows, grouped by the module that implements it.
+const COMMAND_TABLES: &[&[CommandSpec]] = &[
+    basics::COMMANDS,
+    introspection::COMMANDS,
+    keyspace::COMMANDS,
+    strings::COMMANDS,
+    numbers::COMMANDS,
+    lists::COMMANDS,
+    hashes::COMMANDS,
+    sets::COMMANDS,
+    sorted_sets::COMMANDS,
+    streams::COMMANDS,
+];
+
+pub fn eval_command(segments: &Value, ctx: &mut Context) -> Result<Value, CommandError> {
+    let Value::Array(arr) = segments else {
+        return Err(CommandError::Err(
+            "Protocol error: expected an array of bulk strings".to_string(),
+        ));
+    };
+
+    let (spec, params) = registry::resolve(arr)?;
+    (spec.handler)(params, ctx)
+}
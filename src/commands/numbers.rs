@@ -1,22 +1,135 @@
-use crate::db;
-use crate::db::Redis;
+use super::registry::{CommandSpec, Flag};
+use super::strings::{get_string, set_string};
+use super::{Context, args};
+use crate::db::Data;
+use crate::error::CommandError;
 use crate::resp::Value;
+use bytes::Bytes;
 
-pub fn eval_incr(params: &[Value], store: &Redis) -> String {
-    let store = store.lock().unwrap();
-    let key = params.get(0);
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("incr", "string", 2, eval_incr)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist."),
+    CommandSpec::new("decr", "string", 2, eval_decr)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist."),
+    CommandSpec::new("incrby", "string", 3, eval_incrby)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist."),
+    CommandSpec::new("decrby", "string", 3, eval_decrby)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist."),
+    CommandSpec::new("incrbyfloat", "string", 3, eval_incrbyfloat)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist."),
+];
 
-    let _value: Option<&db::StoredValue> = match key {
-        Some(Value::BulkString(name)) => store.db.get(name),
-        _ => None,
+/// Most significant digits a double holds reliably (C's `DBL_DIG`).
+const DOUBLE_DIGITS: i32 = 15;
+
+/// Formats the result of INCRBYFLOAT or HINCRBYFLOAT like Redis: fixed point
+/// with up to 17 decimals and no trailing zeros. Redis has a long double's
+/// extra digits to round away, so this also stops at what a double can hold,
+/// which is what turns 0.1 + 0.2 back into `0.3`.
+pub(super) fn format_float(value: f64) -> String {
+    let scientific = format!("{:.*e}", DOUBLE_DIGITS as usize - 1, value);
+    let exponent: i32 = scientific
+        .rsplit_once('e')
+        .and_then(|(_, exponent)| exponent.parse().ok())
+        .expect("Rust always writes an exponent");
+    let decimals = (DOUBLE_DIGITS - 1 - exponent).clamp(0, 17) as usize;
+    let fixed = format!("{value:.decimals$}");
+    match fixed.contains('.') {
+        true => fixed
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string(),
+        false => fixed,
+    }
+}
+
+/// Replaces the value at `key`, keeping its TTL if it already exists.
+fn store_number(ctx: &mut Context, key: &Bytes, value: Bytes) {
+    match ctx.store.get_mut(key) {
+        Some(stored) => stored.value = Data::String(value),
+        None => set_string(ctx, key, value, None),
+    }
+}
+
+fn increment_by(ctx: &mut Context, key: &Value, delta: i64) -> Result<Value, CommandError> {
+    let key = args::bulk(key)?;
+    let current = match get_string(ctx, key)? {
+        Some(value) => args::to_i64(value).ok_or(CommandError::NotInteger)?,
+        None => 0,
+    };
+    let updated = current
+        .checked_add(delta)
+        .ok_or_else(|| CommandError::Err("increment or decrement would overflow".to_string()))?;
+
+    store_number(ctx, key, Bytes::from(updated.to_string()));
+    Ok(Value::Integer(updated))
+}
+
+/// INCR key
+pub fn eval_incr(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("incr".to_string()));
+    };
+    increment_by(ctx, key, 1)
+}
+
+/// DECR key
+pub fn eval_decr(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("decr".to_string()));
     };
-    unimplemented!()
-    // match value {
-    //     None => {
-    //         store.db.insert(params.get(0), 1);
-    //     }
-    //     Some(Value::Integer(integer)) => {
-    //         store.db.insert(params.get(0), integer + 1);
-    //     }
-    // }
+    increment_by(ctx, key, -1)
+}
+
+/// INCRBY key increment
+pub fn eval_incrby(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, increment] = params else {
+        return Err(CommandError::WrongArity("incrby".to_string()));
+    };
+    increment_by(ctx, key, args::parse_i64(increment)?)
+}
+
+/// DECRBY key decrement
+pub fn eval_decrby(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, decrement] = params else {
+        return Err(CommandError::WrongArity("decrby".to_string()));
+    };
+    // -i64::MIN doesn't fit, so it can never be a valid decrement.
+    let delta = args::parse_i64(decrement)?
+        .checked_neg()
+        .ok_or_else(|| CommandError::Err("decrement would overflow".to_string()))?;
+    increment_by(ctx, key, delta)
+}
+
+/// INCRBYFLOAT key increment
+pub fn eval_incrbyfloat(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, increment] = params else {
+        return Err(CommandError::WrongArity("incrbyfloat".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let increment = args::parse_f64(increment)?;
+    let current = match get_string(ctx, key)? {
+        Some(value) => args::to_f64(value).ok_or(CommandError::NotFloat)?,
+        None => 0.0,
+    };
+
+    let updated = current + increment;
+    if !updated.is_finite() {
+        return Err(CommandError::Err(
+            "increment would produce NaN or Infinity".to_string(),
+        ));
+    }
+    let formatted = Bytes::from(format_float(updated));
+    store_number(ctx, key, formatted.clone());
+    Ok(Value::BulkString(formatted))
 }
@@ -0,0 +1,332 @@
+use super::Context;
+use crate::error::CommandError;
+use crate::resp::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+pub type Handler = fn(&[Value], &mut Context) -> Result<Value, CommandError>;
+/// Finds key positions in a full argv for commands whose keys can't be described
+/// by first/last/step alone, e.g. ones with a `numkeys` argument.
+pub type KeyFinder = fn(&[Value]) -> Vec<usize>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    Write,
+    ReadOnly,
+    Admin,
+    PubSub,
+    Blocking,
+    Fast,
+    DenyOom,
+    NoScript,
+    Loading,
+    Stale,
+}
+
+impl Flag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Flag::Write => "write",
+            Flag::ReadOnly => "readonly",
+            Flag::Admin => "admin",
+            Flag::PubSub => "pubsub",
+            Flag::Blocking => "blocking",
+            Flag::Fast => "fast",
+            Flag::DenyOom => "denyoom",
+            Flag::NoScript => "noscript",
+            Flag::Loading => "loading",
+            Flag::Stale => "stale",
+        }
+    }
+}
+
+/// One entry of the command table: everything COMMAND reports about a command,
+/// plus the handler dispatch calls once the arity has been checked.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub group: &'static str,
+    /// Counts the command name itself: positive is exact, negative is a minimum.
+    pub arity: i64,
+    pub flags: &'static [Flag],
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+    pub key_finder: Option<KeyFinder>,
+    pub summary: &'static str,
+    pub handler: Handler,
+    pub subcommands: &'static [CommandSpec],
+}
+
+impl CommandSpec {
+    pub const fn new(
+        name: &'static str,
+        group: &'static str,
+        arity: i64,
+        handler: Handler,
+    ) -> Self {
+        CommandSpec {
+            name,
+            group,
+            arity,
+            flags: &[],
+            first_key: 0,
+            last_key: 0,
+            step: 0,
+            key_finder: None,
+            summary: "",
+            handler,
+            subcommands: &[],
+        }
+    }
+
+    pub const fn flags(mut self, flags: &'static [Flag]) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub const fn keys(mut self, first_key: i64, last_key: i64, step: i64) -> Self {
+        self.first_key = first_key;
+        self.last_key = last_key;
+        self.step = step;
+        self
+    }
+
+    pub const fn key_finder(mut self, key_finder: KeyFinder) -> Self {
+        self.key_finder = Some(key_finder);
+        self
+    }
+
+    pub const fn summary(mut self, summary: &'static str) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub const fn subcommands(mut self, subcommands: &'static [CommandSpec]) -> Self {
+        self.subcommands = subcommands;
+        self
+    }
+
+    pub fn has_flag(&self, flag: Flag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Subcommands count their container as the first argument, like Redis.
+    pub fn arity_matches(&self, argc: usize) -> bool {
+        if self.arity >= 0 {
+            argc as i64 == self.arity
+        } else {
+            argc as i64 >= -self.arity
+        }
+    }
+
+    pub fn subcommand(&self, name: &[u8]) -> Option<&'static CommandSpec> {
+        self.subcommands
+            .iter()
+            .find(|sub| sub.name.as_bytes().eq_ignore_ascii_case(name))
+    }
+
+    /// ACL categories derived from the flags and group, as COMMAND reports them.
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        let mut categories = Vec::new();
+        for flag in self.flags {
+            match flag {
+                Flag::Write => categories.push("@write"),
+                Flag::ReadOnly => categories.push("@read"),
+                Flag::Admin => categories.extend(["@admin", "@dangerous"]),
+                Flag::PubSub => categories.push("@pubsub"),
+                Flag::Blocking => categories.push("@blocking"),
+                _ => {}
+            }
+        }
+        categories.push(if self.has_flag(Flag::Fast) {
+            "@fast"
+        } else {
+            "@slow"
+        });
+        match self.group {
+            "generic" => categories.push("@keyspace"),
+            "string" => categories.push("@string"),
+            "list" => categories.push("@list"),
+            "hash" => categories.push("@hash"),
+            "set" => categories.push("@set"),
+            "sorted-set" => categories.push("@sortedset"),
+            "stream" => categories.push("@stream"),
+            "connection" => categories.push("@connection"),
+            _ => {}
+        }
+        categories
+    }
+
+    /// Positions of the keys in `argv` (command name at 0), or `None` when the
+    /// arguments are too short to hold them.
+    pub fn key_positions(&self, argv: &[Value]) -> Option<Vec<usize>> {
+        if let Some(key_finder) = self.key_finder {
+            return Some(key_finder(argv));
+        }
+        if self.first_key <= 0 {
+            return Some(vec![]);
+        }
+
+        let first = self.first_key as usize;
+        let last = if self.last_key < 0 {
+            argv.len() as i64 + self.last_key
+        } else {
+            self.last_key
+        };
+        if last < self.first_key || last as usize >= argv.len() {
+            return None;
+        }
+        Some(
+            (first..=last as usize)
+                .step_by(self.step.max(1) as usize)
+                .collect(),
+        )
+    }
+}
+
+static REGISTRY: LazyLock<HashMap<&'static str, &'static CommandSpec>> = LazyLock::new(|| {
+    super::COMMAND_TABLES
+        .iter()
+        .flat_map(|table| table.iter())
+        .map(|spec| (spec.name, spec))
+        .collect()
+});
+
+pub fn lookup(name: &[u8]) -> Option<&'static CommandSpec> {
+    let name = std::str::from_utf8(name).ok()?.to_ascii_lowercase();
+    REGISTRY.get(name.as_str()).copied()
+}
+
+/// Every top-level command, in table order.
+pub fn all() -> impl Iterator<Item = &'static CommandSpec> {
+    super::COMMAND_TABLES.iter().flat_map(|table| table.iter())
+}
+
+pub fn count() -> usize {
+    REGISTRY.len()
+}
+
+/// Resolves `argv` (command name at 0) to the spec that should run it, descending
+/// into subcommands and checking arity on the way.
+pub fn resolve(argv: &[Value]) -> Result<(&'static CommandSpec, &[Value]), CommandError> {
+    let Some((Value::BulkString(name), params)) = argv.split_first() else {
+        return Err(CommandError::Err(
+            "Protocol error: expected a command name".to_string(),
+        ));
+    };
+    let Some(spec) = lookup(name) else {
+        return Err(unknown_command(name, params));
+    };
+    if !spec.arity_matches(argv.len()) {
+        return Err(CommandError::WrongArity(spec.name.to_string()));
+    }
+    if spec.subcommands.is_empty() {
+        return Ok((spec, params));
+    }
+
+    // A bare container, e.g. COMMAND with no subcommand, runs its own handler.
+    let Some((Value::BulkString(sub_name), sub_params)) = params.split_first() else {
+        return Ok((spec, params));
+    };
+    let Some(sub) = spec.subcommand(sub_name) else {
+        return Err(CommandError::UnknownSubcommand(
+            String::from_utf8_lossy(sub_name).to_string(),
+            spec.name.to_ascii_uppercase(),
+        ));
+    };
+    if !sub.arity_matches(argv.len()) {
+        return Err(CommandError::WrongArity(format!(
+            "{}|{}",
+            spec.name, sub.name
+        )));
+    }
+    Ok((sub, sub_params))
+}
+
+/// Key positions for commands shaped like `CMD ... numkeys key [key ...] ...`,
+/// where `numkeys` sits at `argv[at]`. Malformed counts yield no keys.
+pub fn numkeys_positions(argv: &[Value], at: usize) -> Vec<usize> {
+    let numkeys = match argv.get(at) {
+        Some(Value::BulkString(numkeys)) => super::args::to_i64(numkeys).unwrap_or(0),
+        _ => 0,
+    };
+    let first = at + 1;
+    if numkeys <= 0 || first + numkeys as usize > argv.len() {
+        return vec![];
+    }
+    (first..first + numkeys as usize).collect()
+}
+
+fn unknown_command(cmd: &[u8], params: &[Value]) -> CommandError {
+    let args = params
+        .iter()
+        .map(|param| match param {
+            Value::BulkString(arg) => format!("'{}' ", String::from_utf8_lossy(arg)),
+            other => format!("'{other:?}' "),
+        })
+        .collect::<String>();
+    CommandError::UnknownCommand(String::from_utf8_lossy(cmd).to_string(), args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+
+    fn argv(args: &[&str]) -> Vec<Value> {
+        args.iter()
+            .map(|arg| Value::BulkString(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn test_table_names_are_unique_and_lowercase() {
+        let mut names = all().map(|spec| spec.name).collect::<Vec<_>>();
+        assert!(names.iter().all(|name| *name == name.to_ascii_lowercase()));
+
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+        assert_eq!(count(), total);
+    }
+
+    #[test]
+    fn test_resolve_subcommands() {
+        let args = argv(&["Config", "gEt", "dir"]);
+        let (spec, params) = resolve(&args).unwrap();
+        assert_eq!(spec.name, "get");
+        assert_eq!(params.len(), 1);
+
+        assert_eq!(
+            resolve(&argv(&["config", "get"])).err(),
+            Some(CommandError::WrongArity("config|get".to_string()))
+        );
+        assert_eq!(
+            resolve(&argv(&["config", "nope"])).err(),
+            Some(CommandError::UnknownSubcommand(
+                "nope".to_string(),
+                "CONFIG".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_key_positions() {
+        let set = lookup(b"set").unwrap();
+        assert_eq!(set.key_positions(&argv(&["set", "k", "v"])), Some(vec![1]));
+
+        let ping = lookup(b"ping").unwrap();
+        assert_eq!(ping.key_positions(&argv(&["ping"])), Some(vec![]));
+
+        let lmpop = lookup(b"lmpop").unwrap();
+        assert_eq!(
+            lmpop.key_positions(&argv(&["lmpop", "2", "a", "b", "LEFT"])),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            lmpop.key_positions(&argv(&["lmpop", "5", "a", "LEFT"])),
+            Some(vec![])
+        );
+    }
+}
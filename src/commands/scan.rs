@@ -0,0 +1,138 @@
+use super::args;
+use crate::db::TYPE_NAMES;
+use crate::error::CommandError;
+use crate::glob;
+use crate::resp::Value;
+use bytes::Bytes;
+
+/// Redis keeps hashes, sets and sorted sets up to this size in a compact
+/// encoding, which its SCAN commands return whole with a cursor of 0.
+const COMPACT_MAX_LEN: usize = 128;
+
+/// What a SCAN-family command walks, which decides the options it takes
+/// beyond MATCH and COUNT.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    /// SCAN, which also takes `TYPE type`.
+    Keyspace,
+    /// HSCAN, which also takes `NOVALUES`.
+    Hash,
+    /// SSCAN and ZSCAN.
+    Members,
+}
+
+/// The `cursor [MATCH pattern] [COUNT count]` arguments shared by the SCAN family.
+pub struct ScanArgs {
+    pub cursor: u64,
+    pub pattern: Option<Bytes>,
+    pub count: usize,
+    /// SCAN's `TYPE type`, as a `Data::type_name`.
+    pub type_name: Option<&'static str>,
+    /// HSCAN's `NOVALUES`.
+    pub no_values: bool,
+}
+
+impl ScanArgs {
+    pub fn parse(params: &[Value], target: Target) -> Result<Self, CommandError> {
+        let Some((cursor, options)) = params.split_first() else {
+            return Err(CommandError::Syntax);
+        };
+        let cursor = std::str::from_utf8(args::bulk(cursor)?)
+            .ok()
+            .and_then(|cursor| cursor.parse().ok())
+            .ok_or_else(|| CommandError::Err("invalid cursor".to_string()))?;
+
+        let mut scan = ScanArgs {
+            cursor,
+            pattern: None,
+            count: 10,
+            type_name: None,
+            no_values: false,
+        };
+        let mut options = options.iter();
+        while let Some(option) = options.next() {
+            if target == Target::Hash && args::is_option(option, "NOVALUES") {
+                scan.no_values = true;
+                continue;
+            }
+            let arg = options.next().ok_or(CommandError::Syntax)?;
+            if args::is_option(option, "MATCH") {
+                scan.pattern = Some(args::bulk(arg)?.clone());
+            } else if args::is_option(option, "COUNT") {
+                let count = args::parse_i64(arg)?;
+                if count < 1 {
+                    return Err(CommandError::Syntax);
+                }
+                scan.count = count as usize;
+            } else if target == Target::Keyspace && args::is_option(option, "TYPE") {
+                let name = args::bulk(arg)?;
+                scan.type_name = Some(
+                    TYPE_NAMES
+                        .iter()
+                        .find(|type_name| name.eq_ignore_ascii_case(type_name.as_bytes()))
+                        .ok_or_else(|| {
+                            CommandError::Err(format!(
+                                "unknown type name '{}'",
+                                String::from_utf8_lossy(name)
+                            ))
+                        })?,
+                );
+            } else {
+                return Err(CommandError::Syntax);
+            }
+        }
+        Ok(scan)
+    }
+
+    pub fn matches(&self, name: &[u8]) -> bool {
+        self.pattern
+            .as_ref()
+            .is_none_or(|pattern| glob::matches(pattern, name))
+    }
+
+    /// Runs one call's worth of a scan from the cursor. `scan` visits the
+    /// bucket under a cursor, collecting what it holds into the vector, and
+    /// returns the next cursor, like `Dict::scan`.
+    ///
+    /// COUNT is only a hint: this stops once that many items have turned up,
+    /// but also after ten buckets per item asked for, so a sparse table can't
+    /// hold up the server. Returns the cursor to reply with and the items,
+    /// which still have to be filtered with MATCH.
+    pub fn walk<T>(&self, mut scan: impl FnMut(u64, &mut Vec<T>) -> u64) -> (u64, Vec<T>) {
+        let mut found = vec![];
+        let mut cursor = self.cursor;
+        for _ in 0..self.count.saturating_mul(10) {
+            cursor = scan(cursor, &mut found);
+            if cursor == 0 || found.len() >= self.count {
+                break;
+            }
+        }
+        (cursor, found)
+    }
+
+    /// Like `walk`, for a hash, set or sorted set of `len` entries. Small
+    /// ones are returned whole, whatever the cursor.
+    pub fn walk_collection<T>(
+        &self,
+        len: usize,
+        mut scan: impl FnMut(u64, &mut Vec<T>) -> u64,
+    ) -> (u64, Vec<T>) {
+        if len > COMPACT_MAX_LEN {
+            return self.walk(scan);
+        }
+        let mut found = Vec::with_capacity(len);
+        let mut cursor = scan(0, &mut found);
+        while cursor != 0 {
+            cursor = scan(cursor, &mut found);
+        }
+        (0, found)
+    }
+}
+
+/// `[next-cursor, [items ...]]`, where a cursor of 0 ends the iteration.
+pub fn reply(cursor: u64, items: Vec<Value>) -> Value {
+    Value::Array(vec![
+        Value::BulkString(Bytes::from(cursor.to_string())),
+        Value::Array(items),
+    ])
+}
@@ -0,0 +1,494 @@
+use super::registry::{self, CommandSpec, Flag};
+use super::scan::{self, ScanArgs, Target};
+use super::{Context, args};
+use crate::db::{Data, StoredValue};
+use crate::error::CommandError;
+use crate::resp::Value;
+use crate::set::Set;
+use bytes::Bytes;
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("sadd", "set", -3, sadd)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Adds one or more members to a set. Creates the key if it doesn't exist."),
+    CommandSpec::new("srem", "set", -3, srem)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Removes one or more members from a set. Deletes the set if the last member was removed."),
+    CommandSpec::new("sismember", "set", 3, sismember)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Determines whether a member belongs to a set."),
+    CommandSpec::new("smismember", "set", -3, smismember)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Determines whether multiple members belong to a set."),
+    CommandSpec::new("smembers", "set", 2, smembers)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns all members of a set."),
+    CommandSpec::new("scard", "set", 2, scard)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of members in a set."),
+    CommandSpec::new("spop", "set", -2, spop)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns one or more random members from a set after removing them. Deletes the set if the last member was popped."),
+    CommandSpec::new("srandmember", "set", -2, srandmember)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Get one or multiple random members from a set"),
+    CommandSpec::new("smove", "set", 4, smove)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 2, 1)
+        .summary("Moves a member from one set to another."),
+    CommandSpec::new("sinter", "set", -2, sinter)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, -1, 1)
+        .summary("Returns the intersect of multiple sets."),
+    CommandSpec::new("sintercard", "set", -3, sintercard)
+        .flags(&[Flag::ReadOnly])
+        .key_finder(sintercard_keys)
+        .summary("Returns the number of members of the intersect of multiple sets."),
+    CommandSpec::new("sinterstore", "set", -3, sinterstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, -1, 1)
+        .summary("Stores the intersect of multiple sets in a key."),
+    CommandSpec::new("sunion", "set", -2, sunion)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, -1, 1)
+        .summary("Returns the union of multiple sets."),
+    CommandSpec::new("sunionstore", "set", -3, sunionstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, -1, 1)
+        .summary("Stores the union of multiple sets in a key."),
+    CommandSpec::new("sdiff", "set", -2, sdiff)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, -1, 1)
+        .summary("Returns the difference of multiple sets."),
+    CommandSpec::new("sdiffstore", "set", -3, sdiffstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, -1, 1)
+        .summary("Stores the difference of multiple sets in a key."),
+    CommandSpec::new("sscan", "set", -3, sscan)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Iterates over members of a set."),
+];
+
+/// The set stored at `key`, `None` if it is missing, or WRONGTYPE.
+fn get_set<'a>(ctx: &'a mut Context, key: &[u8]) -> Result<Option<&'a mut Set>, CommandError> {
+    match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        None => Ok(None),
+        Some(Data::Set(set)) => Ok(Some(set)),
+        Some(_) => Err(CommandError::WrongType),
+    }
+}
+
+/// Like `get_set`, but creates an empty set when the key is missing.
+fn get_or_create_set<'a>(ctx: &'a mut Context, key: &Bytes) -> Result<&'a mut Set, CommandError> {
+    if get_set(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::Set(Set::new()),
+                expiry: None,
+            },
+        );
+    }
+    Ok(get_set(ctx, key)?.expect("set was just created"))
+}
+
+/// Sets never exist empty: removing the last member removes the key.
+fn remove_if_empty(ctx: &mut Context, key: &[u8]) {
+    if matches!(get_set(ctx, key), Ok(Some(set)) if set.is_empty()) {
+        ctx.store.remove(key);
+    }
+}
+
+fn members_reply<'a>(members: impl IntoIterator<Item = &'a Bytes>) -> Vec<Value> {
+    members
+        .into_iter()
+        .map(|member| Value::BulkString(member.clone()))
+        .collect()
+}
+
+/// SADD key member [member ...]
+pub fn sadd(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, members)) = params.split_first() else {
+        return Err(CommandError::WrongArity("sadd".to_string()));
+    };
+    let members = members
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let set = get_or_create_set(ctx, args::bulk(key)?)?;
+    let added = members
+        .into_iter()
+        .filter(|member| set.insert((*member).clone()))
+        .count();
+    Ok(Value::Integer(added as i64))
+}
+
+/// SREM key member [member ...]
+pub fn srem(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, members)) = params.split_first() else {
+        return Err(CommandError::WrongArity("srem".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let members = members
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(set) = get_set(ctx, key)? else {
+        return Ok(Value::Integer(0));
+    };
+    let removed = members
+        .into_iter()
+        .filter(|member| set.remove(member))
+        .count();
+    remove_if_empty(ctx, key);
+    Ok(Value::Integer(removed as i64))
+}
+
+/// SISMEMBER key member
+pub fn sismember(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, member] = params else {
+        return Err(CommandError::WrongArity("sismember".to_string()));
+    };
+    let member = args::bulk(member)?;
+    let found = get_set(ctx, args::bulk(key)?)?.is_some_and(|set| set.contains(member));
+    Ok(Value::Integer(found as i64))
+}
+
+/// SMISMEMBER key member [member ...]
+pub fn smismember(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, members)) = params.split_first() else {
+        return Err(CommandError::WrongArity("smismember".to_string()));
+    };
+    let members = members
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let set = get_set(ctx, args::bulk(key)?)?;
+    let found = members
+        .into_iter()
+        .map(|member| {
+            let found = set.as_ref().is_some_and(|set| set.contains(member));
+            Value::Integer(found as i64)
+        })
+        .collect();
+    Ok(Value::Array(found))
+}
+
+/// SMEMBERS key
+pub fn smembers(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("smembers".to_string()));
+    };
+    let members = get_set(ctx, args::bulk(key)?)?.map_or_else(Vec::new, |set| members_reply(&*set));
+    Ok(Value::Set(members))
+}
+
+/// SCARD key
+pub fn scard(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("scard".to_string()));
+    };
+    let len = get_set(ctx, args::bulk(key)?)?.map_or(0, |set| set.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// SPOP key [count]
+pub fn spop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let (key, count) = match params {
+        [key] => (args::bulk(key)?, None),
+        [key, count] => {
+            let count = args::parse_i64(count)?;
+            if count < 0 {
+                return Err(CommandError::Err(
+                    "value is out of range, must be positive".to_string(),
+                ));
+            }
+            (args::bulk(key)?, Some(count as usize))
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+    let mut rng = rand::thread_rng();
+
+    let Some(set) = get_set(ctx, key)? else {
+        return Ok(match count {
+            Some(_) => Value::Set(vec![]),
+            None => Value::NullString,
+        });
+    };
+    let picked: Vec<Bytes> = match count {
+        None => set.random(&mut rng).cloned().into_iter().collect(),
+        Some(count) => set
+            .random_distinct(&mut rng, count)
+            .into_iter()
+            .cloned()
+            .collect(),
+    };
+    for member in &picked {
+        set.remove(member);
+    }
+    remove_if_empty(ctx, key);
+
+    Ok(match count {
+        Some(_) => Value::Set(members_reply(&picked)),
+        None => picked
+            .into_iter()
+            .next()
+            .map_or(Value::NullString, Value::BulkString),
+    })
+}
+
+/// SRANDMEMBER key [count]
+pub fn srandmember(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let (key, count) = match params {
+        [key] => (key, None),
+        [key, count] => (key, Some(args::parse_i64(count)?)),
+        _ => return Err(CommandError::Syntax),
+    };
+    let mut rng = rand::thread_rng();
+
+    let Some(set) = get_set(ctx, args::bulk(key)?)? else {
+        return Ok(match count {
+            Some(_) => Value::Array(vec![]),
+            None => Value::NullString,
+        });
+    };
+    let Some(count) = count else {
+        return Ok(set.random(&mut rng).map_or(Value::NullString, |member| {
+            Value::BulkString(member.clone())
+        }));
+    };
+
+    // A negative count may repeat members; a positive one returns distinct ones.
+    let picked: Vec<&Bytes> = if count < 0 {
+        (0..args::random_repeats(count)?)
+            .map(|_| set.random(&mut rng).expect("sets are never empty"))
+            .collect()
+    } else {
+        set.random_distinct(&mut rng, count.try_into().unwrap_or(usize::MAX))
+    };
+    Ok(Value::Array(members_reply(picked)))
+}
+
+/// SMOVE source destination member
+pub fn smove(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [source, destination, member] = params else {
+        return Err(CommandError::WrongArity("smove".to_string()));
+    };
+    let (source, destination) = (args::bulk(source)?, args::bulk(destination)?);
+    let member = args::bulk(member)?;
+
+    let Some(set) = get_set(ctx, source)? else {
+        return Ok(Value::Integer(0));
+    };
+    let present = set.contains(member);
+    // The destination must be a set even when there is nothing to move.
+    get_set(ctx, destination)?;
+    if !present {
+        return Ok(Value::Integer(0));
+    }
+    if source == destination {
+        return Ok(Value::Integer(1));
+    }
+
+    if let Some(set) = get_set(ctx, source)? {
+        set.remove(member);
+    }
+    remove_if_empty(ctx, source);
+    get_or_create_set(ctx, destination)?.insert(member.clone());
+    Ok(Value::Integer(1))
+}
+
+#[derive(Clone, Copy)]
+enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+/// The sets at `keys`, `None` for missing ones. Every key must hold a set,
+/// even ones that can't change the outcome.
+fn lookup_sets<'a>(
+    ctx: &'a mut Context,
+    keys: &[Value],
+) -> Result<Vec<Option<&'a Set>>, CommandError> {
+    let keys = keys.iter().map(args::bulk).collect::<Result<Vec<_>, _>>()?;
+    for key in &keys {
+        get_set(ctx, key)?;
+    }
+    // Expired keys are gone after the pass above, so plain lookups are safe.
+    Ok(keys
+        .iter()
+        .map(|key| match ctx.store.db.get(key.as_ref()) {
+            Some(StoredValue {
+                value: Data::Set(set),
+                ..
+            }) => Some(set),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The members common to all of `sets`, found one at a time so callers can
+/// stop early. A missing set makes the intersection empty.
+fn intersect(sets: Vec<Option<&Set>>) -> impl Iterator<Item = &Bytes> {
+    let mut sets = sets
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+    // Probing the smallest set against the others does the least work.
+    sets.sort_by_key(|set| set.len());
+    let smallest = (!sets.is_empty()).then(|| sets.remove(0));
+    smallest
+        .into_iter()
+        .flat_map(Set::iter)
+        .filter(move |member| sets.iter().all(|set| set.contains(member)))
+}
+
+/// Applies `op` across the sets at `keys`, where a missing key is an empty set.
+fn combine(ctx: &mut Context, keys: &[Value], op: SetOp) -> Result<Set, CommandError> {
+    let sets = lookup_sets(ctx, keys)?;
+    Ok(match op {
+        SetOp::Inter => intersect(sets).cloned().collect(),
+        SetOp::Union => sets.into_iter().flatten().flatten().cloned().collect(),
+        SetOp::Diff => {
+            let (first, others) = sets.split_first().expect("at least one key");
+            first
+                .iter()
+                .flat_map(|set| set.iter())
+                .filter(|member| !others.iter().flatten().any(|set| set.contains(member)))
+                .cloned()
+                .collect()
+        }
+    })
+}
+
+fn combine_command(params: &[Value], ctx: &mut Context, op: SetOp) -> Result<Value, CommandError> {
+    let result = combine(ctx, params, op)?;
+    Ok(Value::Set(members_reply(&result)))
+}
+
+/// Stores the result of `op` over `keys` at `destination`, replacing whatever
+/// was there; an empty result deletes it instead.
+fn store_command(params: &[Value], ctx: &mut Context, op: SetOp) -> Result<Value, CommandError> {
+    let Some((destination, keys)) = params.split_first() else {
+        return Err(CommandError::Syntax);
+    };
+    let destination = args::bulk(destination)?;
+    let result = combine(ctx, keys, op)?;
+    let len = result.len();
+    if result.is_empty() {
+        ctx.store.remove(destination);
+    } else {
+        ctx.store.insert(
+            destination.clone(),
+            StoredValue {
+                value: Data::Set(result),
+                expiry: None,
+            },
+        );
+    }
+    Ok(Value::Integer(len as i64))
+}
+
+/// SINTER key [key ...]
+pub fn sinter(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Inter)
+}
+
+/// SINTERSTORE destination key [key ...]
+pub fn sinterstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Inter)
+}
+
+/// SUNION key [key ...]
+pub fn sunion(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Union)
+}
+
+/// SUNIONSTORE destination key [key ...]
+pub fn sunionstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Union)
+}
+
+/// SDIFF key [key ...]
+pub fn sdiff(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Diff)
+}
+
+/// SDIFFSTORE destination key [key ...]
+pub fn sdiffstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Diff)
+}
+
+fn sintercard_keys(argv: &[Value]) -> Vec<usize> {
+    registry::numkeys_positions(argv, 1)
+}
+
+/// SINTERCARD numkeys key [key ...] [LIMIT limit]
+pub fn sintercard(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((numkeys, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("sintercard".to_string()));
+    };
+    let numkeys = args::parse_i64(numkeys)?;
+    if numkeys <= 0 {
+        return Err(CommandError::Err(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    if numkeys as usize > rest.len() {
+        return Err(CommandError::Err(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    let (keys, options) = rest.split_at(numkeys as usize);
+
+    // LIMIT 0 means no limit.
+    let mut limit = 0;
+    for pair in options.chunks(2) {
+        match pair {
+            [option, value] if args::is_option(option, "LIMIT") => {
+                limit = args::parse_i64(value)?;
+                if limit < 0 {
+                    return Err(CommandError::Err("LIMIT can't be negative".to_string()));
+                }
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+    }
+
+    let len = intersect(lookup_sets(ctx, keys)?)
+        .take(match limit {
+            0 => usize::MAX,
+            limit => limit as usize,
+        })
+        .count();
+    Ok(Value::Integer(len as i64))
+}
+
+/// SSCAN key cursor [MATCH pattern] [COUNT count]
+pub fn sscan(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("sscan".to_string()));
+    };
+    let scan_args = ScanArgs::parse(rest, Target::Members)?;
+    let Some(set) = get_set(ctx, args::bulk(key)?)? else {
+        return Ok(scan::reply(0, vec![]));
+    };
+    let (cursor, found) = scan_args.walk_collection(set.len(), |cursor, found| {
+        set.scan(cursor, |member| found.push(member.clone()))
+    });
+    let items = members_reply(found.iter().filter(|member| scan_args.matches(member)));
+    Ok(scan::reply(cursor, items))
+}
@@ -0,0 +1,1365 @@
+use super::registry::{self, CommandSpec, Flag};
+use super::scan::{self, ScanArgs, Target};
+use super::{Context, args};
+use crate::db::{Data, StoredValue};
+use crate::error::CommandError;
+use crate::resp::{self, Protocol, Value};
+use crate::set::Set;
+use crate::sortedset::SortedSet;
+use bytes::Bytes;
+use rand::Rng;
+use rand::seq::IteratorRandom;
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("zadd", "sorted-set", -4, zadd)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist."),
+    CommandSpec::new("zrem", "sorted-set", -3, zrem)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Removes one or more members from a sorted set. Deletes the sorted set if all members were removed."),
+    CommandSpec::new("zscore", "sorted-set", 3, zscore)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the score of a member in a sorted set."),
+    CommandSpec::new("zmscore", "sorted-set", -3, zmscore)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the score of one or more members in a sorted set."),
+    CommandSpec::new("zincrby", "sorted-set", 4, zincrby)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Increments the score of a member in a sorted set."),
+    CommandSpec::new("zcard", "sorted-set", 2, zcard)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of members in a sorted set."),
+    CommandSpec::new("zcount", "sorted-set", 4, zcount)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the count of members in a sorted set that have scores within a range."),
+    CommandSpec::new("zrank", "sorted-set", -3, zrank)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the index of a member in a sorted set ordered by ascending scores."),
+    CommandSpec::new("zrevrank", "sorted-set", -3, zrevrank)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the index of a member in a sorted set ordered by descending scores."),
+    CommandSpec::new("zrange", "sorted-set", -4, zrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns members in a sorted set within a range of indexes."),
+    CommandSpec::new("zpopmin", "sorted-set", -2, zpopmin)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    CommandSpec::new("zpopmax", "sorted-set", -2, zpopmax)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    CommandSpec::new("zrandmember", "sorted-set", -2, zrandmember)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns one or more random members from a sorted set."),
+    CommandSpec::new("zscan", "sorted-set", -3, zscan)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Iterates over members and scores of a sorted set."),
+    CommandSpec::new("zunion", "sorted-set", -3, zunion)
+        .flags(&[Flag::ReadOnly])
+        .key_finder(numkeys_at_1)
+        .summary("Returns the union of multiple sorted sets."),
+    CommandSpec::new("zunionstore", "sorted-set", -4, zunionstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .key_finder(destination_and_numkeys_at_2)
+        .summary("Stores the union of multiple sorted sets in a key."),
+    CommandSpec::new("zinter", "sorted-set", -3, zinter)
+        .flags(&[Flag::ReadOnly])
+        .key_finder(numkeys_at_1)
+        .summary("Returns the intersect of multiple sorted sets."),
+    CommandSpec::new("zinterstore", "sorted-set", -4, zinterstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .key_finder(destination_and_numkeys_at_2)
+        .summary("Stores the intersect of multiple sorted sets in a key."),
+    CommandSpec::new("zintercard", "sorted-set", -3, zintercard)
+        .flags(&[Flag::ReadOnly])
+        .key_finder(numkeys_at_1)
+        .summary("Returns the number of members of the intersect of multiple sorted sets."),
+    CommandSpec::new("zdiff", "sorted-set", -3, zdiff)
+        .flags(&[Flag::ReadOnly])
+        .key_finder(numkeys_at_1)
+        .summary("Returns the difference between multiple sorted sets."),
+    CommandSpec::new("zdiffstore", "sorted-set", -4, zdiffstore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .key_finder(destination_and_numkeys_at_2)
+        .summary("Stores the difference of multiple sorted sets in a key."),
+    CommandSpec::new("zrangestore", "sorted-set", -5, zrangestore)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 2, 1)
+        .summary("Stores a range of members from sorted set in a key."),
+    CommandSpec::new("zlexcount", "sorted-set", 4, zlexcount)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of members in a sorted set within a lexicographical range."),
+    CommandSpec::new("zremrangebyscore", "sorted-set", 4, zremrangebyscore)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Removes members in a sorted set within a range of scores. Deletes the sorted set if all members were removed."),
+    CommandSpec::new("zremrangebyrank", "sorted-set", 4, zremrangebyrank)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Removes members in a sorted set within a range of indexes. Deletes the sorted set if all members were removed."),
+    CommandSpec::new("zremrangebylex", "sorted-set", 4, zremrangebylex)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Removes members in a sorted set within a lexicographical range. Deletes the sorted set if all members were removed."),
+    CommandSpec::new("zmpop", "sorted-set", -4, zmpop)
+        .flags(&[Flag::Write])
+        .key_finder(numkeys_at_1)
+        .summary("Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped."),
+    CommandSpec::new("bzpopmin", "sorted-set", -3, bzpopmin)
+        .flags(&[Flag::Write, Flag::Fast, Flag::Blocking])
+        .keys(1, -2, 1)
+        .summary("Removes and returns the member with the lowest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+    CommandSpec::new("bzpopmax", "sorted-set", -3, bzpopmax)
+        .flags(&[Flag::Write, Flag::Fast, Flag::Blocking])
+        .keys(1, -2, 1)
+        .summary("Removes and returns the member with the highest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+    CommandSpec::new("bzmpop", "sorted-set", -5, bzmpop)
+        .flags(&[Flag::Write, Flag::Blocking])
+        .key_finder(numkeys_at_2)
+        .summary("Removes and returns a member by score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped."),
+];
+
+/// The sorted set stored at `key`, `None` if it is missing, or WRONGTYPE.
+fn get_zset<'a>(
+    ctx: &'a mut Context,
+    key: &[u8],
+) -> Result<Option<&'a mut SortedSet>, CommandError> {
+    match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        None => Ok(None),
+        Some(Data::SortedSet(zset)) => Ok(Some(zset)),
+        Some(_) => Err(CommandError::WrongType),
+    }
+}
+
+/// Like `get_zset`, but creates an empty sorted set when the key is missing.
+fn get_or_create_zset<'a>(
+    ctx: &'a mut Context,
+    key: &Bytes,
+) -> Result<&'a mut SortedSet, CommandError> {
+    if get_zset(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::SortedSet(SortedSet::new()),
+                expiry: None,
+            },
+        );
+    }
+    Ok(get_zset(ctx, key)?.expect("sorted set was just created"))
+}
+
+/// Sorted sets never exist empty: removing the last member removes the key.
+fn remove_if_empty(ctx: &mut Context, key: &[u8]) {
+    if matches!(get_zset(ctx, key), Ok(Some(zset)) if zset.is_empty()) {
+        ctx.store.remove(key);
+    }
+}
+
+fn score_or_null(score: Option<f64>) -> Value {
+    score.map_or(Value::NullString, Value::Double)
+}
+
+/// Members, each followed by its score if `with_scores` is set. RESP3 clients
+/// get the pairs as nested arrays.
+fn scored_reply<'a>(
+    entries: impl IntoIterator<Item = (&'a Bytes, f64)>,
+    with_scores: bool,
+    protocol: Protocol,
+) -> Value {
+    let items = entries
+        .into_iter()
+        .flat_map(|(member, score)| {
+            let member = Value::BulkString(member.clone());
+            match (with_scores, protocol) {
+                (false, _) => vec![member],
+                (true, Protocol::Resp2) => vec![member, Value::Double(score)],
+                (true, Protocol::Resp3) => vec![Value::Array(vec![member, Value::Double(score)])],
+            }
+        })
+        .collect();
+    Value::Array(items)
+}
+
+fn nan_score() -> CommandError {
+    CommandError::Err("resulting score is not a number (NaN)".to_string())
+}
+
+/// ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]
+pub fn zadd(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, mut rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zadd".to_string()));
+    };
+    let key = args::bulk(key)?;
+
+    let (mut nx, mut xx, mut gt, mut lt, mut ch, mut incr) =
+        (false, false, false, false, false, false);
+    while let Some((option, tail)) = rest.split_first() {
+        let flag = match option {
+            option if args::is_option(option, "NX") => &mut nx,
+            option if args::is_option(option, "XX") => &mut xx,
+            option if args::is_option(option, "GT") => &mut gt,
+            option if args::is_option(option, "LT") => &mut lt,
+            option if args::is_option(option, "CH") => &mut ch,
+            option if args::is_option(option, "INCR") => &mut incr,
+            _ => break,
+        };
+        *flag = true;
+        rest = tail;
+    }
+    if rest.is_empty() || !rest.len().is_multiple_of(2) {
+        return Err(CommandError::Syntax);
+    }
+    if nx && xx {
+        return Err(CommandError::Err(
+            "XX and NX options at the same time are not compatible".to_string(),
+        ));
+    }
+    if (gt && lt) || (nx && (gt || lt)) {
+        return Err(CommandError::Err(
+            "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+        ));
+    }
+    if incr && rest.len() > 2 {
+        return Err(CommandError::Err(
+            "INCR option supports a single increment-element pair".to_string(),
+        ));
+    }
+    let pairs = rest
+        .chunks(2)
+        .map(|pair| Ok((args::parse_f64(&pair[0])?, args::bulk(&pair[1])?)))
+        .collect::<Result<Vec<_>, CommandError>>()?;
+
+    let zset = get_or_create_zset(ctx, key)?;
+    let (mut added, mut changed) = (0, 0);
+    let mut incr_result = None;
+    for (score, member) in pairs {
+        let new = match zset.score(member) {
+            None if xx => continue,
+            None => {
+                zset.insert(member.clone(), score);
+                added += 1;
+                incr_result = Some(score);
+                continue;
+            }
+            Some(_) if nx => continue,
+            Some(current) => {
+                let new = if incr { current + score } else { score };
+                if new.is_nan() {
+                    remove_if_empty(ctx, key);
+                    return Err(nan_score());
+                }
+                if (gt && new <= current) || (lt && new >= current) {
+                    continue;
+                }
+                if new != current {
+                    changed += 1;
+                }
+                new
+            }
+        };
+        zset.insert(member.clone(), new);
+        incr_result = Some(new);
+    }
+    remove_if_empty(ctx, key);
+    if added > 0 {
+        ctx.store.blocking.signal(key);
+    }
+
+    if incr {
+        return Ok(score_or_null(incr_result));
+    }
+    Ok(Value::Integer(if ch { added + changed } else { added }))
+}
+
+/// ZREM key member [member ...]
+pub fn zrem(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, members)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zrem".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let members = members
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(zset) = get_zset(ctx, key)? else {
+        return Ok(Value::Integer(0));
+    };
+    let removed = members
+        .into_iter()
+        .filter(|member| zset.remove(member).is_some())
+        .count();
+    remove_if_empty(ctx, key);
+    Ok(Value::Integer(removed as i64))
+}
+
+/// ZSCORE key member
+pub fn zscore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, member] = params else {
+        return Err(CommandError::WrongArity("zscore".to_string()));
+    };
+    let member = args::bulk(member)?;
+    let score = get_zset(ctx, args::bulk(key)?)?.and_then(|zset| zset.score(member));
+    Ok(score_or_null(score))
+}
+
+/// ZMSCORE key member [member ...]
+pub fn zmscore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, members)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zmscore".to_string()));
+    };
+    let members = members
+        .iter()
+        .map(args::bulk)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let zset = get_zset(ctx, args::bulk(key)?)?;
+    let scores = members
+        .into_iter()
+        .map(|member| score_or_null(zset.as_ref().and_then(|zset| zset.score(member))))
+        .collect();
+    Ok(Value::Array(scores))
+}
+
+/// ZINCRBY key increment member
+pub fn zincrby(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, increment, member] = params else {
+        return Err(CommandError::WrongArity("zincrby".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let increment = args::parse_f64(increment)?;
+    let member = args::bulk(member)?;
+
+    let zset = get_or_create_zset(ctx, key)?;
+    let score = zset.score(member).unwrap_or(0.0) + increment;
+    if score.is_nan() {
+        remove_if_empty(ctx, key);
+        return Err(nan_score());
+    }
+    zset.insert(member.clone(), score);
+    ctx.store.blocking.signal(key);
+    Ok(Value::Double(score))
+}
+
+/// ZCARD key
+pub fn zcard(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("zcard".to_string()));
+    };
+    let len = get_zset(ctx, args::bulk(key)?)?.map_or(0, |zset| zset.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// A `min max` score interval, where `(` makes an end exclusive.
+struct ScoreRange {
+    min: f64,
+    min_exclusive: bool,
+    max: f64,
+    max_exclusive: bool,
+}
+
+impl ScoreRange {
+    fn parse(min: &Value, max: &Value) -> Result<Self, CommandError> {
+        let parse = |arg: &Value| {
+            let arg = args::bulk(arg)?;
+            let (exclusive, number) = match arg.strip_prefix(b"(") {
+                Some(number) => (true, number),
+                None => (false, &arg[..]),
+            };
+            args::to_f64(number)
+                .map(|value| (value, exclusive))
+                .ok_or_else(|| CommandError::Err("min or max is not a float".to_string()))
+        };
+        let ((min, min_exclusive), (max, max_exclusive)) = (parse(min)?, parse(max)?);
+        Ok(ScoreRange {
+            min,
+            min_exclusive,
+            max,
+            max_exclusive,
+        })
+    }
+
+    /// The ranks of the members whose scores fall in the range.
+    fn ranks(&self, zset: &SortedSet) -> Range<usize> {
+        let start = zset.partition_point(|score, _| {
+            score < self.min || (self.min_exclusive && score == self.min)
+        });
+        let end = zset.partition_point(|score, _| {
+            score < self.max || (!self.max_exclusive && score == self.max)
+        });
+        start..end.max(start)
+    }
+}
+
+/// One end of a lexicographic range: `-`, `+`, `[member` or `(member`.
+enum LexBound {
+    NegativeInfinity,
+    PositiveInfinity,
+    Inclusive(Bytes),
+    Exclusive(Bytes),
+}
+
+/// A `min max` member interval, meaningful when all scores are equal.
+struct LexRange {
+    min: LexBound,
+    max: LexBound,
+}
+
+impl LexRange {
+    fn parse(min: &Value, max: &Value) -> Result<Self, CommandError> {
+        let parse = |arg: &Value| {
+            let arg = args::bulk(arg)?;
+            match arg.first() {
+                Some(b'-') if arg.len() == 1 => Ok(LexBound::NegativeInfinity),
+                Some(b'+') if arg.len() == 1 => Ok(LexBound::PositiveInfinity),
+                Some(b'[') => Ok(LexBound::Inclusive(arg.slice(1..))),
+                Some(b'(') => Ok(LexBound::Exclusive(arg.slice(1..))),
+                _ => Err(CommandError::Err(
+                    "min or max not valid string range item".to_string(),
+                )),
+            }
+        };
+        Ok(LexRange {
+            min: parse(min)?,
+            max: parse(max)?,
+        })
+    }
+
+    /// The ranks of the members that fall in the range.
+    fn ranks(&self, zset: &SortedSet) -> Range<usize> {
+        let start = zset.partition_point(|_, member| match &self.min {
+            LexBound::NegativeInfinity => false,
+            LexBound::PositiveInfinity => true,
+            LexBound::Inclusive(min) => member < min.as_ref(),
+            LexBound::Exclusive(min) => member <= min.as_ref(),
+        });
+        let end = zset.partition_point(|_, member| match &self.max {
+            LexBound::NegativeInfinity => false,
+            LexBound::PositiveInfinity => true,
+            LexBound::Inclusive(max) => member <= max.as_ref(),
+            LexBound::Exclusive(max) => member < max.as_ref(),
+        });
+        start..end.max(start)
+    }
+}
+
+/// ZCOUNT key min max
+pub fn zcount(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, min, max] = params else {
+        return Err(CommandError::WrongArity("zcount".to_string()));
+    };
+    let range = ScoreRange::parse(min, max)?;
+    let count = get_zset(ctx, args::bulk(key)?)?.map_or(0, |zset| range.ranks(zset).len());
+    Ok(Value::Integer(count as i64))
+}
+
+fn rank_command(params: &[Value], ctx: &mut Context, reverse: bool) -> Result<Value, CommandError> {
+    let (key, member, with_score) = match params {
+        [key, member] => (key, member, false),
+        [key, member, option] if args::is_option(option, "WITHSCORE") => (key, member, true),
+        _ => return Err(CommandError::Syntax),
+    };
+    let member = args::bulk(member)?;
+
+    let found = get_zset(ctx, args::bulk(key)?)?.and_then(|zset| {
+        let rank = zset.rank(member)?;
+        let rank = if reverse { zset.len() - 1 - rank } else { rank };
+        Some((rank, zset.score(member)?))
+    });
+    Ok(match (found, with_score) {
+        (None, false) => Value::NullString,
+        (None, true) => Value::NullArray,
+        (Some((rank, _)), false) => Value::Integer(rank as i64),
+        (Some((rank, score)), true) => {
+            Value::Array(vec![Value::Integer(rank as i64), Value::Double(score)])
+        }
+    })
+}
+
+/// ZRANK key member [WITHSCORE]
+pub fn zrank(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    rank_command(params, ctx, false)
+}
+
+/// ZREVRANK key member [WITHSCORE]
+pub fn zrevrank(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    rank_command(params, ctx, true)
+}
+
+/// The ranks `start..=stop` name once negative ones count from the end and both
+/// are clamped to the set, as for ZRANGE and ZREMRANGEBYRANK.
+fn rank_range(len: usize, start: i64, stop: i64) -> Range<usize> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (start + len).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        stop + len
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop {
+        return 0..0;
+    }
+    start as usize..stop as usize + 1
+}
+
+enum RangeBy {
+    Rank(i64, i64),
+    Score(ScoreRange),
+    Lex(LexRange),
+}
+
+/// The `start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]`
+/// arguments of ZRANGE and its relatives.
+struct RangeQuery {
+    by: RangeBy,
+    rev: bool,
+    /// A negative count means no limit.
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+impl RangeQuery {
+    fn parse(params: &[Value]) -> Result<Self, CommandError> {
+        let [start, stop, options @ ..] = params else {
+            return Err(CommandError::Syntax);
+        };
+        let mut options = options;
+        let (mut by_score, mut by_lex, mut rev, mut limit, mut with_scores) =
+            (false, false, false, None, false);
+        while let Some((option, rest)) = options.split_first() {
+            options = rest;
+            if args::is_option(option, "BYSCORE") {
+                by_score = true;
+            } else if args::is_option(option, "BYLEX") {
+                by_lex = true;
+            } else if args::is_option(option, "REV") {
+                rev = true;
+            } else if args::is_option(option, "WITHSCORES") {
+                with_scores = true;
+            } else if args::is_option(option, "LIMIT")
+                && let [offset, count, rest @ ..] = options
+            {
+                limit = Some((args::parse_i64(offset)?, args::parse_i64(count)?));
+                options = rest;
+            } else {
+                return Err(CommandError::Syntax);
+            }
+        }
+        if by_score && by_lex {
+            return Err(CommandError::Syntax);
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return Err(CommandError::Err(
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                    .to_string(),
+            ));
+        }
+        if with_scores && by_lex {
+            return Err(CommandError::Err(
+                "syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+            ));
+        }
+
+        // With REV the interval is given from its upper end.
+        let (min, max) = if rev { (stop, start) } else { (start, stop) };
+        let by = if by_score {
+            RangeBy::Score(ScoreRange::parse(min, max)?)
+        } else if by_lex {
+            RangeBy::Lex(LexRange::parse(min, max)?)
+        } else {
+            RangeBy::Rank(args::parse_i64(start)?, args::parse_i64(stop)?)
+        };
+        Ok(RangeQuery {
+            by,
+            rev,
+            limit,
+            with_scores,
+        })
+    }
+
+    /// The selected members and scores, in reply order.
+    fn select<'a>(&self, zset: &'a SortedSet) -> Vec<(&'a Bytes, f64)> {
+        let Range { mut start, mut end } = match &self.by {
+            RangeBy::Rank(start, stop) => {
+                let ranks = rank_range(zset.len(), *start, *stop);
+                // Ranks given with REV count from the highest score.
+                if self.rev {
+                    zset.len() - ranks.end..zset.len() - ranks.start
+                } else {
+                    ranks
+                }
+            }
+            RangeBy::Score(range) => range.ranks(zset),
+            RangeBy::Lex(range) => range.ranks(zset),
+        };
+        if let Some((offset, count)) = self.limit {
+            if offset < 0 {
+                return vec![];
+            }
+            let offset = (offset as usize).min(end - start);
+            if self.rev {
+                end -= offset;
+                if count >= 0 {
+                    start = start.max(end.saturating_sub(count as usize));
+                }
+            } else {
+                start += offset;
+                if count >= 0 {
+                    end = end.min(start + count as usize);
+                }
+            }
+        }
+        if self.rev {
+            zset.range_rev(start, end).collect()
+        } else {
+            zset.range(start, end).collect()
+        }
+    }
+}
+
+/// ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+pub fn zrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zrange".to_string()));
+    };
+    let query = RangeQuery::parse(rest)?;
+    let protocol = ctx.client.protocol;
+
+    let Some(zset) = get_zset(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Array(vec![]));
+    };
+    Ok(scored_reply(
+        query.select(zset),
+        query.with_scores,
+        protocol,
+    ))
+}
+
+#[derive(Clone, Copy)]
+enum Extreme {
+    Min,
+    Max,
+}
+
+/// Pops up to `count` members from the low or high end, deleting the key once
+/// it's empty.
+fn pop_many(
+    ctx: &mut Context,
+    key: &[u8],
+    end: Extreme,
+    count: usize,
+) -> Result<Option<Vec<(Bytes, f64)>>, CommandError> {
+    let Some(zset) = get_zset(ctx, key)? else {
+        return Ok(None);
+    };
+    let popped = (0..count)
+        .map_while(|_| match end {
+            Extreme::Min => zset.pop_first(),
+            Extreme::Max => zset.pop_last(),
+        })
+        .collect();
+    remove_if_empty(ctx, key);
+    Ok(Some(popped))
+}
+
+fn pop_command(params: &[Value], ctx: &mut Context, end: Extreme) -> Result<Value, CommandError> {
+    let (key, count) = match params {
+        [key] => (key, None),
+        [key, count] => {
+            let count = args::parse_i64(count)?;
+            if count < 0 {
+                return Err(CommandError::Err(
+                    "value is out of range, must be positive".to_string(),
+                ));
+            }
+            (key, Some(count as usize))
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+    let protocol = ctx.client.protocol;
+    let popped = pop_many(ctx, args::bulk(key)?, end, count.unwrap_or(1))?.unwrap_or_default();
+
+    // Without a count even RESP3 gets a flat member-score pair.
+    let protocol = if count.is_some() {
+        protocol
+    } else {
+        Protocol::Resp2
+    };
+    Ok(scored_reply(
+        popped.iter().map(|(member, score)| (member, *score)),
+        true,
+        protocol,
+    ))
+}
+
+/// ZPOPMIN key [count]
+pub fn zpopmin(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    pop_command(params, ctx, Extreme::Min)
+}
+
+/// ZPOPMAX key [count]
+pub fn zpopmax(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    pop_command(params, ctx, Extreme::Max)
+}
+
+/// ZRANDMEMBER key [count [WITHSCORES]]
+pub fn zrandmember(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let (key, count, with_scores) = match params {
+        [key] => (key, None, false),
+        [key, count] => (key, Some(count), false),
+        [key, count, option] if args::is_option(option, "WITHSCORES") => (key, Some(count), true),
+        _ => return Err(CommandError::Syntax),
+    };
+    let count = count.map(args::parse_i64).transpose()?;
+    let protocol = ctx.client.protocol;
+    let mut rng = rand::thread_rng();
+
+    let Some(zset) = get_zset(ctx, args::bulk(key)?)? else {
+        return Ok(match count {
+            Some(_) => Value::Array(vec![]),
+            None => Value::NullString,
+        });
+    };
+    let Some(count) = count else {
+        let (member, _) = zset
+            .get_by_rank(rng.gen_range(0..zset.len()))
+            .expect("sorted sets are never empty");
+        return Ok(Value::BulkString(member.clone()));
+    };
+
+    // A negative count may repeat members; a positive one returns distinct ones.
+    let picked: Vec<(&Bytes, f64)> = if count < 0 {
+        (0..args::random_repeats(count)?)
+            .filter_map(|_| zset.get_by_rank(rng.gen_range(0..zset.len())))
+            .collect()
+    } else {
+        let count = (count as u64).min(zset.len() as u64) as usize;
+        zset.iter().choose_multiple(&mut rng, count)
+    };
+    Ok(scored_reply(picked, with_scores, protocol))
+}
+
+/// ZSCAN key cursor [MATCH pattern] [COUNT count]
+///
+/// Scores come back as strings under either protocol, as in Redis.
+pub fn zscan(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zscan".to_string()));
+    };
+    let scan_args = ScanArgs::parse(rest, Target::Members)?;
+    let Some(zset) = get_zset(ctx, args::bulk(key)?)? else {
+        return Ok(scan::reply(0, vec![]));
+    };
+    let (cursor, found) = scan_args.walk_collection(zset.len(), |cursor, found| {
+        zset.scan(cursor, |member, score| found.push((member.clone(), score)))
+    });
+    let items = found
+        .into_iter()
+        .filter(|(member, _)| scan_args.matches(member))
+        .flat_map(|(member, score)| {
+            [
+                Value::BulkString(member),
+                Value::BulkString(Bytes::from(resp::format_double(score))),
+            ]
+        })
+        .collect();
+    Ok(scan::reply(cursor, items))
+}
+
+fn numkeys_at_1(argv: &[Value]) -> Vec<usize> {
+    registry::numkeys_positions(argv, 1)
+}
+
+fn numkeys_at_2(argv: &[Value]) -> Vec<usize> {
+    registry::numkeys_positions(argv, 2)
+}
+
+/// `CMD destination numkeys key [key ...] ...`
+fn destination_and_numkeys_at_2(argv: &[Value]) -> Vec<usize> {
+    let keys = registry::numkeys_positions(argv, 2);
+    if keys.is_empty() {
+        return keys;
+    }
+    [1].into_iter().chain(keys).collect()
+}
+
+/// Replaces `destination` with `zset`, or deletes it if `zset` is empty, and
+/// returns the new cardinality.
+fn store_zset(ctx: &mut Context, destination: &Bytes, zset: SortedSet) -> usize {
+    let len = zset.len();
+    if zset.is_empty() {
+        ctx.store.remove(destination);
+    } else {
+        ctx.store.insert(
+            destination.clone(),
+            StoredValue {
+                value: Data::SortedSet(zset),
+                expiry: None,
+            },
+        );
+        ctx.store.blocking.signal(destination);
+    }
+    len
+}
+
+/// An input of ZUNION and friends. Plain sets count as members scored 1.
+enum Input<'a> {
+    Set(&'a Set),
+    Sorted(&'a SortedSet),
+}
+
+impl Input<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Input::Set(set) => set.len(),
+            Input::Sorted(zset) => zset.len(),
+        }
+    }
+
+    fn score(&self, member: &[u8]) -> Option<f64> {
+        match self {
+            Input::Set(set) => set.contains(member).then_some(1.0),
+            Input::Sorted(zset) => zset.score(member),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&Bytes, f64)> + '_> {
+        match self {
+            Input::Set(set) => Box::new(set.iter().map(|member| (member, 1.0))),
+            Input::Sorted(zset) => Box::new(zset.iter()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SetOp {
+    Union,
+    Inter,
+    Diff,
+}
+
+#[derive(Clone, Copy)]
+enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            // inf + -inf has no sensible answer; Redis settles on 0.
+            Aggregate::Sum => Some(a + b).filter(|sum| !sum.is_nan()).unwrap_or(0.0),
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+/// The `numkeys key [key ...] [WEIGHTS weight [weight ...]]
+/// [AGGREGATE <SUM | MIN | MAX>] [WITHSCORES]` arguments shared by ZUNION,
+/// ZINTER, ZDIFF and their STORE variants.
+struct Combine<'a> {
+    op: SetOp,
+    keys: &'a [Value],
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    with_scores: bool,
+}
+
+impl<'a> Combine<'a> {
+    fn parse(
+        params: &'a [Value],
+        op: SetOp,
+        allow_with_scores: bool,
+        cmd: &str,
+    ) -> Result<Self, CommandError> {
+        let Some((numkeys, rest)) = params.split_first() else {
+            return Err(CommandError::WrongArity(cmd.to_string()));
+        };
+        let numkeys = args::parse_i64(numkeys)?;
+        if numkeys <= 0 {
+            return Err(CommandError::Err(format!(
+                "at least 1 input key is needed for '{cmd}' command"
+            )));
+        }
+        if numkeys as usize > rest.len() {
+            return Err(CommandError::Syntax);
+        }
+        let (keys, mut options) = rest.split_at(numkeys as usize);
+
+        let mut combine = Combine {
+            op,
+            keys,
+            weights: vec![1.0; keys.len()],
+            aggregate: Aggregate::Sum,
+            with_scores: false,
+        };
+        while let Some((option, rest)) = options.split_first() {
+            options = rest;
+            if op != SetOp::Diff && args::is_option(option, "WEIGHTS") && rest.len() >= keys.len() {
+                for (weight, arg) in combine.weights.iter_mut().zip(rest) {
+                    *weight = args::to_f64(args::bulk(arg)?).ok_or_else(|| {
+                        CommandError::Err("weight value is not a float".to_string())
+                    })?;
+                }
+                options = &rest[keys.len()..];
+            } else if op != SetOp::Diff
+                && args::is_option(option, "AGGREGATE")
+                && let Some((aggregate, rest)) = rest.split_first()
+            {
+                combine.aggregate = if args::is_option(aggregate, "SUM") {
+                    Aggregate::Sum
+                } else if args::is_option(aggregate, "MIN") {
+                    Aggregate::Min
+                } else if args::is_option(aggregate, "MAX") {
+                    Aggregate::Max
+                } else {
+                    return Err(CommandError::Syntax);
+                };
+                options = rest;
+            } else if allow_with_scores && args::is_option(option, "WITHSCORES") {
+                combine.with_scores = true;
+            } else {
+                return Err(CommandError::Syntax);
+            }
+        }
+        Ok(combine)
+    }
+
+    /// The inputs at `keys`, `None` for missing ones. Every key must hold a
+    /// set or a sorted set.
+    fn inputs<'c>(&self, ctx: &'c mut Context) -> Result<Vec<Option<Input<'c>>>, CommandError> {
+        let keys = self
+            .keys
+            .iter()
+            .map(args::bulk)
+            .collect::<Result<Vec<_>, _>>()?;
+        for key in &keys {
+            match ctx.store.get(key).map(|stored| &stored.value) {
+                None | Some(Data::Set(_)) | Some(Data::SortedSet(_)) => {}
+                Some(_) => return Err(CommandError::WrongType),
+            }
+        }
+        // Expired keys are gone after the pass above, so plain lookups are safe.
+        let inputs = keys
+            .iter()
+            .map(
+                |key| match ctx.store.db.get(key.as_ref()).map(|stored| &stored.value) {
+                    Some(Data::Set(set)) => Some(Input::Set(set)),
+                    Some(Data::SortedSet(zset)) => Some(Input::Sorted(zset)),
+                    _ => None,
+                },
+            )
+            .collect();
+        Ok(inputs)
+    }
+
+    fn run(&self, ctx: &mut Context) -> Result<SortedSet, CommandError> {
+        let inputs = self.inputs(ctx)?;
+        let weighted = |score: f64, weight: f64| {
+            // 0 * inf counts as 0, like in Redis.
+            Some(score * weight)
+                .filter(|score| !score.is_nan())
+                .unwrap_or(0.0)
+        };
+
+        Ok(match self.op {
+            SetOp::Union => {
+                let mut scores: HashMap<Bytes, f64> = HashMap::new();
+                for (input, &weight) in inputs.iter().zip(&self.weights) {
+                    for (member, score) in input.iter().flat_map(|input| input.iter()) {
+                        let score = weighted(score, weight);
+                        scores
+                            .entry(member.clone())
+                            .and_modify(|total| *total = self.aggregate.apply(*total, score))
+                            .or_insert(score);
+                    }
+                }
+                scores.into_iter().collect()
+            }
+            SetOp::Inter => {
+                let Some(inputs) = inputs.into_iter().collect::<Option<Vec<_>>>() else {
+                    return Ok(SortedSet::new());
+                };
+                // Probing the smallest input against the others does the least work.
+                let smallest = (0..inputs.len())
+                    .min_by_key(|&index| inputs[index].len())
+                    .expect("at least one key");
+                inputs[smallest]
+                    .iter()
+                    .filter_map(|(member, _)| {
+                        let mut total = None;
+                        for (input, &weight) in inputs.iter().zip(&self.weights) {
+                            let score = weighted(input.score(member)?, weight);
+                            total = Some(match total {
+                                None => score,
+                                Some(total) => self.aggregate.apply(total, score),
+                            });
+                        }
+                        Some((member.clone(), total?))
+                    })
+                    .collect()
+            }
+            SetOp::Diff => {
+                let (first, others) = inputs.split_first().expect("at least one key");
+                first
+                    .iter()
+                    .flat_map(|input| input.iter())
+                    .filter(|(member, _)| {
+                        !others
+                            .iter()
+                            .flatten()
+                            .any(|other| other.score(member).is_some())
+                    })
+                    .map(|(member, score)| (member.clone(), score))
+                    .collect()
+            }
+        })
+    }
+}
+
+fn combine_command(
+    params: &[Value],
+    ctx: &mut Context,
+    op: SetOp,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let combine = Combine::parse(params, op, true, cmd)?;
+    let result = combine.run(ctx)?;
+    Ok(scored_reply(
+        result.iter(),
+        combine.with_scores,
+        ctx.client.protocol,
+    ))
+}
+
+fn store_command(
+    params: &[Value],
+    ctx: &mut Context,
+    op: SetOp,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let Some((destination, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let result = Combine::parse(rest, op, false, cmd)?.run(ctx)?;
+    let len = store_zset(ctx, args::bulk(destination)?, result);
+    Ok(Value::Integer(len as i64))
+}
+
+/// ZUNION numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>] [WITHSCORES]
+pub fn zunion(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Union, "zunion")
+}
+
+/// ZUNIONSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>]
+pub fn zunionstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Union, "zunionstore")
+}
+
+/// ZINTER numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>] [WITHSCORES]
+pub fn zinter(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Inter, "zinter")
+}
+
+/// ZINTERSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>]
+pub fn zinterstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Inter, "zinterstore")
+}
+
+/// ZDIFF numkeys key [key ...] [WITHSCORES]
+pub fn zdiff(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    combine_command(params, ctx, SetOp::Diff, "zdiff")
+}
+
+/// ZDIFFSTORE destination numkeys key [key ...]
+pub fn zdiffstore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    store_command(params, ctx, SetOp::Diff, "zdiffstore")
+}
+
+/// ZINTERCARD numkeys key [key ...] [LIMIT limit]
+pub fn zintercard(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((numkeys, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("zintercard".to_string()));
+    };
+    let numkeys = args::parse_i64(numkeys)?;
+    if numkeys <= 0 {
+        return Err(CommandError::Err(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    if numkeys as usize > rest.len() {
+        return Err(CommandError::Err(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    let (keys, options) = rest.split_at(numkeys as usize);
+
+    // LIMIT 0 means no limit.
+    let mut limit = 0;
+    for pair in options.chunks(2) {
+        match pair {
+            [option, value] if args::is_option(option, "LIMIT") => {
+                limit = args::parse_i64(value)?;
+                if limit < 0 {
+                    return Err(CommandError::Err("LIMIT can't be negative".to_string()));
+                }
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+    }
+
+    let combine = Combine {
+        op: SetOp::Inter,
+        keys,
+        weights: vec![1.0; keys.len()],
+        aggregate: Aggregate::Sum,
+        with_scores: false,
+    };
+    let Some(inputs) = combine.inputs(ctx)?.into_iter().collect::<Option<Vec<_>>>() else {
+        return Ok(Value::Integer(0));
+    };
+    // Count straight off the smallest input, stopping once LIMIT is reached.
+    let smallest = (0..inputs.len())
+        .min_by_key(|&index| inputs[index].len())
+        .expect("at least one key");
+    let len = inputs[smallest]
+        .iter()
+        .filter(|(member, _)| inputs.iter().all(|input| input.score(member).is_some()))
+        .take(match limit {
+            0 => usize::MAX,
+            limit => limit as usize,
+        })
+        .count();
+    Ok(Value::Integer(len as i64))
+}
+
+/// ZRANGESTORE dst src min max [BYSCORE | BYLEX] [REV] [LIMIT offset count]
+pub fn zrangestore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [destination, source, rest @ ..] = params else {
+        return Err(CommandError::WrongArity("zrangestore".to_string()));
+    };
+    let query = RangeQuery::parse(rest)?;
+    if query.with_scores {
+        return Err(CommandError::Syntax);
+    }
+
+    let selected = get_zset(ctx, args::bulk(source)?)?.map_or_else(SortedSet::new, |zset| {
+        query
+            .select(zset)
+            .into_iter()
+            .map(|(member, score)| (member.clone(), score))
+            .collect()
+    });
+    let len = store_zset(ctx, args::bulk(destination)?, selected);
+    Ok(Value::Integer(len as i64))
+}
+
+/// ZLEXCOUNT key min max
+pub fn zlexcount(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, min, max] = params else {
+        return Err(CommandError::WrongArity("zlexcount".to_string()));
+    };
+    let range = LexRange::parse(min, max)?;
+    let count = get_zset(ctx, args::bulk(key)?)?.map_or(0, |zset| range.ranks(zset).len());
+    Ok(Value::Integer(count as i64))
+}
+
+/// Removes the members whose ranks `ranks` picks out, deleting the key if
+/// that empties it.
+fn remove_ranks(
+    ctx: &mut Context,
+    key: &Value,
+    ranks: impl FnOnce(&SortedSet) -> Range<usize>,
+) -> Result<Value, CommandError> {
+    let key = args::bulk(key)?;
+    let Some(zset) = get_zset(ctx, key)? else {
+        return Ok(Value::Integer(0));
+    };
+    let Range { start, end } = ranks(zset);
+    let doomed = zset
+        .range(start, end)
+        .map(|(member, _)| member.clone())
+        .collect::<Vec<_>>();
+    for member in &doomed {
+        zset.remove(member);
+    }
+    remove_if_empty(ctx, key);
+    Ok(Value::Integer(doomed.len() as i64))
+}
+
+/// ZREMRANGEBYSCORE key min max
+pub fn zremrangebyscore(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, min, max] = params else {
+        return Err(CommandError::WrongArity("zremrangebyscore".to_string()));
+    };
+    let range = ScoreRange::parse(min, max)?;
+    remove_ranks(ctx, key, |zset| range.ranks(zset))
+}
+
+/// ZREMRANGEBYRANK key start stop
+pub fn zremrangebyrank(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, start, stop] = params else {
+        return Err(CommandError::WrongArity("zremrangebyrank".to_string()));
+    };
+    let (start, stop) = (args::parse_i64(start)?, args::parse_i64(stop)?);
+    remove_ranks(ctx, key, |zset| rank_range(zset.len(), start, stop))
+}
+
+/// ZREMRANGEBYLEX key min max
+pub fn zremrangebylex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, min, max] = params else {
+        return Err(CommandError::WrongArity("zremrangebylex".to_string()));
+    };
+    let range = LexRange::parse(min, max)?;
+    remove_ranks(ctx, key, |zset| range.ranks(zset))
+}
+
+/// The `numkeys key [key ...] <MIN | MAX> [COUNT count]` tail of ZMPOP and BZMPOP.
+struct MpopRequest<'a> {
+    keys: &'a [Value],
+    end: Extreme,
+    count: usize,
+}
+
+impl<'a> MpopRequest<'a> {
+    fn parse(params: &'a [Value], cmd: &str) -> Result<Self, CommandError> {
+        let Some((numkeys, rest)) = params.split_first() else {
+            return Err(CommandError::WrongArity(cmd.to_string()));
+        };
+        let numkeys = args::parse_i64(numkeys)?;
+        if numkeys <= 0 {
+            return Err(CommandError::Err(
+                "numkeys should be greater than 0".to_string(),
+            ));
+        }
+        let numkeys = numkeys as usize;
+        if rest.len() <= numkeys {
+            return Err(CommandError::Syntax);
+        }
+        let (keys, options) = rest.split_at(numkeys);
+        let end = if args::is_option(&options[0], "MIN") {
+            Extreme::Min
+        } else if args::is_option(&options[0], "MAX") {
+            Extreme::Max
+        } else {
+            return Err(CommandError::Syntax);
+        };
+        let count = match &options[1..] {
+            [] => 1,
+            [option, count] if args::is_option(option, "COUNT") => {
+                let count = args::parse_i64(count)?;
+                if count <= 0 {
+                    return Err(CommandError::Err(
+                        "count should be greater than 0".to_string(),
+                    ));
+                }
+                count as usize
+            }
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(MpopRequest { keys, end, count })
+    }
+
+    /// Pops from the first non-empty sorted set, or replies with a null array.
+    fn pop(&self, ctx: &mut Context) -> Result<Value, CommandError> {
+        for key in self.keys {
+            let key = args::bulk(key)?;
+            if let Some(popped) = pop_many(ctx, key, self.end, self.count)? {
+                let popped = popped
+                    .into_iter()
+                    .map(|(member, score)| {
+                        Value::Array(vec![Value::BulkString(member), Value::Double(score)])
+                    })
+                    .collect();
+                return Ok(Value::Array(vec![
+                    Value::BulkString(key.clone()),
+                    Value::Array(popped),
+                ]));
+            }
+        }
+        Ok(Value::NullArray)
+    }
+}
+
+/// ZMPOP numkeys key [key ...] <MIN | MAX> [COUNT count]
+pub fn zmpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    MpopRequest::parse(params, "zmpop")?.pop(ctx)
+}
+
+/// BZMPOP timeout numkeys key [key ...] <MIN | MAX> [COUNT count]
+pub fn bzmpop(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((timeout, rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("bzmpop".to_string()));
+    };
+    let timeout = args::parse_timeout(timeout)?;
+    let request = MpopRequest::parse(rest, "bzmpop")?;
+    match request.pop(ctx)? {
+        Value::NullArray => ctx.block(request.keys, timeout, Value::NullArray),
+        popped => Ok(popped),
+    }
+}
+
+/// Pops one member from the first non-empty sorted set as a
+/// `[key, member, score]` triple.
+fn bzpop(
+    params: &[Value],
+    ctx: &mut Context,
+    end: Extreme,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let Some((timeout, keys)) = params.split_last() else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let timeout = args::parse_timeout(timeout)?;
+    for key in keys {
+        let key = args::bulk(key)?;
+        if let Some(popped) = pop_many(ctx, key, end, 1)? {
+            let (member, score) = popped
+                .into_iter()
+                .next()
+                .expect("sorted sets are never empty");
+            return Ok(Value::Array(vec![
+                Value::BulkString(key.clone()),
+                Value::BulkString(member),
+                Value::Double(score),
+            ]));
+        }
+    }
+    ctx.block(keys, timeout, Value::NullArray)
+}
+
+/// BZPOPMIN key [key ...] timeout
+pub fn bzpopmin(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    bzpop(params, ctx, Extreme::Min, "bzpopmin")
+}
+
+/// BZPOPMAX key [key ...] timeout
+pub fn bzpopmax(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    bzpop(params, ctx, Extreme::Max, "bzpopmax")
+}
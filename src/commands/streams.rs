@@ -0,0 +1,1545 @@
+use super::registry::{CommandSpec, Flag};
+use super::{Context, args};
+use crate::db::{Data, StoredValue};
+use crate::error::CommandError;
+use crate::resp::{Protocol, Value};
+use crate::stream::{ConsumerGroup, Fields, NODE_MAX_ENTRIES, Stream, StreamId, Trim};
+use bytes::Bytes;
+use std::ops::Bound;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("xadd", "stream", -5, xadd)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Appends a new message to a stream. Creates the key if it doesn't exist."),
+    CommandSpec::new("xrange", "stream", -4, xrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns the messages from a stream within a range of IDs."),
+    CommandSpec::new("xrevrange", "stream", -4, xrevrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns the messages from a stream within a range of IDs in reverse order."),
+    CommandSpec::new("xlen", "stream", 2, xlen)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Return the number of messages in a stream."),
+    CommandSpec::new("xdel", "stream", -3, xdel)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of messages after removing them from a stream."),
+    CommandSpec::new("xread", "stream", -4, xread)
+        .flags(&[Flag::ReadOnly, Flag::Blocking])
+        .key_finder(xread_keys)
+        .summary("Returns messages from multiple streams with IDs greater than the ones requested. Blocks until a message is available otherwise."),
+    CommandSpec::new("xreadgroup", "stream", -7, xreadgroup)
+        .flags(&[Flag::Write, Flag::Blocking])
+        .key_finder(xreadgroup_keys)
+        .summary("Returns new or historical messages from a stream for a consumer in a group. Blocks until a message is available otherwise."),
+    CommandSpec::new("xack", "stream", -4, xack)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the number of messages that were successfully acknowledged by the consumer group member of a stream."),
+    CommandSpec::new("xpending", "stream", -3, xpending)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns the information and entries from a stream consumer group's pending entries list."),
+    CommandSpec::new("xclaim", "stream", -6, xclaim)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Changes, or acquires, ownership of a message in a consumer group, as if the message was delivered a consumer group member."),
+    CommandSpec::new("xautoclaim", "stream", -6, xautoclaim)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Changes, or acquires, ownership of messages in a consumer group, as if the messages were delivered to as consumer group member."),
+    CommandSpec::new("xtrim", "stream", -4, xtrim)
+        .flags(&[Flag::Write])
+        .keys(1, 1, 1)
+        .summary("Deletes messages from the beginning of a stream."),
+    CommandSpec::new("xsetid", "stream", -3, xsetid)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("An internal command for replicating stream values."),
+    CommandSpec::new("xinfo", "stream", -2, xinfo_stream)
+        .summary("A container for stream introspection commands.")
+        .subcommands(&[
+            CommandSpec::new("stream", "stream", -3, xinfo_stream)
+                .flags(&[Flag::ReadOnly])
+                .keys(2, 2, 1)
+                .summary("Returns information about a stream."),
+            CommandSpec::new("groups", "stream", 3, xinfo_groups)
+                .flags(&[Flag::ReadOnly])
+                .keys(2, 2, 1)
+                .summary("Returns a list of the consumer groups of a stream."),
+            CommandSpec::new("consumers", "stream", 4, xinfo_consumers)
+                .flags(&[Flag::ReadOnly])
+                .keys(2, 2, 1)
+                .summary("Returns a list of the consumers in a consumer group."),
+        ]),
+    CommandSpec::new("xgroup", "stream", -2, xgroup_create)
+        .summary("A container for consumer groups commands.")
+        .subcommands(&[
+            CommandSpec::new("create", "stream", -5, xgroup_create)
+                .flags(&[Flag::Write, Flag::DenyOom])
+                .keys(2, 2, 1)
+                .summary("Creates a consumer group."),
+            CommandSpec::new("setid", "stream", -5, xgroup_setid)
+                .flags(&[Flag::Write])
+                .keys(2, 2, 1)
+                .summary("Sets the last-delivered ID of a consumer group."),
+            CommandSpec::new("destroy", "stream", 4, xgroup_destroy)
+                .flags(&[Flag::Write])
+                .keys(2, 2, 1)
+                .summary("Destroys a consumer group."),
+            CommandSpec::new("createconsumer", "stream", 5, xgroup_createconsumer)
+                .flags(&[Flag::Write])
+                .keys(2, 2, 1)
+                .summary("Creates a consumer in a consumer group."),
+            CommandSpec::new("delconsumer", "stream", 5, xgroup_delconsumer)
+                .flags(&[Flag::Write])
+                .keys(2, 2, 1)
+                .summary("Deletes a consumer from a consumer group."),
+        ]),
+];
+
+/// The stream stored at `key`, `None` if it is missing, or WRONGTYPE.
+fn get_stream<'a>(
+    ctx: &'a mut Context,
+    key: &[u8],
+) -> Result<Option<&'a mut Stream>, CommandError> {
+    match ctx.store.get_mut(key).map(|stored| &mut stored.value) {
+        None => Ok(None),
+        Some(Data::Stream(stream)) => Ok(Some(stream)),
+        Some(_) => Err(CommandError::WrongType),
+    }
+}
+
+/// The current time in ms since the epoch, which IDs and delivery times use.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn invalid_id() -> CommandError {
+    CommandError::Err("Invalid stream ID specified as stream command argument".to_string())
+}
+
+/// An ID argument given in full, or as a bare `ms` with sequence 0.
+fn parse_id(arg: &Value) -> Result<StreamId, CommandError> {
+    StreamId::parse(args::bulk(arg)?, 0).ok_or_else(invalid_id)
+}
+
+fn id_reply(id: StreamId) -> Value {
+    Value::BulkString(Bytes::from(id.to_string()))
+}
+
+/// `[id, [field, value, ...]]`
+fn entry_reply(id: &StreamId, fields: &Fields) -> Value {
+    let fields = fields
+        .iter()
+        .flat_map(|(field, value)| {
+            [
+                Value::BulkString(field.clone()),
+                Value::BulkString(value.clone()),
+            ]
+        })
+        .collect();
+    Value::Array(vec![id_reply(*id), Value::Array(fields)])
+}
+
+/// What XADD was asked to use as the new entry's ID.
+enum NewId {
+    /// `*`
+    Auto,
+    /// `ms-*`
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+impl NewId {
+    fn parse(arg: &Value) -> Result<Self, CommandError> {
+        let arg = args::bulk(arg)?;
+        if arg.as_ref() == b"*" {
+            return Ok(NewId::Auto);
+        }
+        if let Some(ms) = arg.strip_suffix(b"-*") {
+            return match StreamId::parse(ms, 0) {
+                Some(id) if !ms.contains(&b'-') => Ok(NewId::AutoSeq(id.ms)),
+                _ => Err(invalid_id()),
+            };
+        }
+        StreamId::parse(arg, 0)
+            .map(NewId::Explicit)
+            .ok_or_else(invalid_id)
+    }
+
+    /// The ID the next entry of a stream whose top item is `last` gets.
+    fn resolve(&self, last: StreamId) -> Result<StreamId, CommandError> {
+        let too_small = || {
+            CommandError::Err(
+                "The ID specified in XADD is equal or smaller than the target stream top item"
+                    .to_string(),
+            )
+        };
+        match *self {
+            NewId::Auto => {
+                let now = now_ms();
+                if now > last.ms {
+                    return Ok(StreamId::new(now, 0));
+                }
+                // The clock went backwards or this millisecond is taken.
+                last.next().ok_or_else(|| {
+                    CommandError::Err(
+                        "The stream has exhausted the last possible ID, unable to add more items"
+                            .to_string(),
+                    )
+                })
+            }
+            NewId::AutoSeq(ms) if ms > last.ms => Ok(StreamId::new(ms, 0)),
+            NewId::AutoSeq(ms) if ms == last.ms => last
+                .seq
+                .checked_add(1)
+                .map(|seq| StreamId::new(ms, seq))
+                .ok_or_else(too_small),
+            NewId::AutoSeq(_) => Err(too_small()),
+            NewId::Explicit(id) if id == StreamId::MIN => Err(CommandError::Err(
+                "The ID specified in XADD must be greater than 0-0".to_string(),
+            )),
+            NewId::Explicit(id) if id > last => Ok(id),
+            NewId::Explicit(_) => Err(too_small()),
+        }
+    }
+}
+
+/// How many entries approximate trimming removes at most without LIMIT.
+const DEFAULT_TRIM_LIMIT: usize = 100 * NODE_MAX_ENTRIES;
+
+/// The MAXLEN or MINID trimming XADD and XTRIM take.
+#[derive(Default)]
+struct TrimOptions {
+    trim: Option<Trim>,
+    approximate: bool,
+    limit: Option<usize>,
+}
+
+impl TrimOptions {
+    /// Takes a MAXLEN, MINID or LIMIT option off the front of `options`,
+    /// returning what follows it, or `None` if there is no such option.
+    fn consume<'a>(&mut self, options: &'a [Value]) -> Result<Option<&'a [Value]>, CommandError> {
+        let [option, rest @ ..] = options else {
+            return Ok(None);
+        };
+        if args::is_option(option, "LIMIT") {
+            let [limit, rest @ ..] = rest else {
+                return Err(CommandError::Syntax);
+            };
+            let limit = args::parse_i64(limit)?;
+            if limit < 0 {
+                return Err(CommandError::Err(
+                    "The LIMIT argument must be >= 0.".to_string(),
+                ));
+            }
+            self.limit = Some(limit as usize);
+            return Ok(Some(rest));
+        }
+        let max_len = args::is_option(option, "MAXLEN");
+        if !max_len && !args::is_option(option, "MINID") {
+            return Ok(None);
+        }
+        if self.trim.is_some() {
+            return Err(CommandError::Err(
+                "syntax error, MAXLEN and MINID options at the same time are not compatible"
+                    .to_string(),
+            ));
+        }
+        let rest = match rest {
+            [operator, rest @ ..] if args::bulk(operator)?.as_ref() == b"~" => {
+                self.approximate = true;
+                rest
+            }
+            [operator, rest @ ..] if args::bulk(operator)?.as_ref() == b"=" => rest,
+            rest => rest,
+        };
+        let [threshold, rest @ ..] = rest else {
+            return Err(CommandError::Syntax);
+        };
+        self.trim = Some(if max_len {
+            let max_len = args::parse_i64(threshold)?;
+            if max_len < 0 {
+                return Err(CommandError::Err(
+                    "The MAXLEN argument must be >= 0.".to_string(),
+                ));
+            }
+            Trim::MaxLen(max_len as usize)
+        } else {
+            Trim::MinId(parse_id(threshold)?)
+        });
+        Ok(Some(rest))
+    }
+
+    /// Checks the options make sense together, once they are all in.
+    fn validate(&self) -> Result<(), CommandError> {
+        if self.limit.is_some() && !self.approximate {
+            return Err(CommandError::Err(
+                "syntax error, LIMIT cannot be used without the special ~ option".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, stream: &mut Stream) -> usize {
+        let Some(trim) = self.trim else {
+            return 0;
+        };
+        let limit = match (self.approximate, self.limit) {
+            (false, _) | (true, Some(0)) => None,
+            (true, None) => Some(DEFAULT_TRIM_LIMIT),
+            (true, limit) => limit,
+        };
+        stream.trim(trim, self.approximate, limit)
+    }
+}
+
+/// XADD key [NOMKSTREAM] [<MAXLEN | MINID> [= | ~] threshold [LIMIT count]]
+///   <* | id> field value [field value ...]
+pub fn xadd(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, mut rest)) = params.split_first() else {
+        return Err(CommandError::WrongArity("xadd".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let (mut make_stream, mut trim) = (true, TrimOptions::default());
+    loop {
+        if let [option, tail @ ..] = rest
+            && args::is_option(option, "NOMKSTREAM")
+        {
+            make_stream = false;
+            rest = tail;
+            continue;
+        }
+        match trim.consume(rest)? {
+            Some(tail) => rest = tail,
+            None => break,
+        }
+    }
+    trim.validate()?;
+    let Some((id, pairs)) = rest.split_first() else {
+        return Err(CommandError::Syntax);
+    };
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity("xadd".to_string()));
+    }
+    let id = NewId::parse(id)?;
+    let fields = pairs
+        .chunks(2)
+        .map(|pair| Ok((args::bulk(&pair[0])?.clone(), args::bulk(&pair[1])?.clone())))
+        .collect::<Result<Fields, CommandError>>()?;
+
+    let last = match get_stream(ctx, key)? {
+        Some(stream) => stream.last_id(),
+        None if make_stream => StreamId::MIN,
+        None => return Ok(Value::NullString),
+    };
+    let id = id.resolve(last)?;
+    if get_stream(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::Stream(Stream::new()),
+                expiry: None,
+            },
+        );
+    }
+    let stream = get_stream(ctx, key)?.expect("stream was just created");
+    stream.append(id, fields);
+    trim.apply(stream);
+    ctx.store.blocking.signal(key);
+    Ok(id_reply(id))
+}
+
+/// XTRIM key <MAXLEN | MINID> [= | ~] threshold [LIMIT count]
+pub fn xtrim(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, mut options)) = params.split_first() else {
+        return Err(CommandError::WrongArity("xtrim".to_string()));
+    };
+    let mut trim = TrimOptions::default();
+    while !options.is_empty() {
+        options = trim.consume(options)?.ok_or(CommandError::Syntax)?;
+    }
+    trim.validate()?;
+    if trim.trim.is_none() {
+        return Err(CommandError::Syntax);
+    }
+
+    let Some(stream) = get_stream(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Integer(0));
+    };
+    Ok(Value::Integer(trim.apply(stream) as i64))
+}
+
+/// One end of an XRANGE interval: `-`, `+`, or an ID, where a bare `ms` covers
+/// the whole millisecond and a leading `(` excludes the ID itself.
+fn parse_interval_end(arg: &Value, is_start: bool) -> Result<StreamId, CommandError> {
+    let arg = args::bulk(arg)?;
+    let (exclusive, arg) = match arg.strip_prefix(b"(") {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, &arg[..]),
+    };
+    let id = match arg {
+        b"-" => StreamId::MIN,
+        b"+" => StreamId::MAX,
+        arg => StreamId::parse(arg, if is_start { 0 } else { u64::MAX }).ok_or_else(invalid_id)?,
+    };
+    match (exclusive, is_start) {
+        (false, _) => Ok(id),
+        (true, true) => id
+            .next()
+            .ok_or_else(|| CommandError::Err("invalid start ID for the interval".to_string())),
+        (true, false) => id
+            .prev()
+            .ok_or_else(|| CommandError::Err("invalid end ID for the interval".to_string())),
+    }
+}
+
+fn range_command(
+    params: &[Value],
+    ctx: &mut Context,
+    reverse: bool,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let [key, first, second, options @ ..] = params else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    // XREVRANGE takes the interval from its upper end.
+    let (start, end) = if reverse {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    let (start, end) = (
+        parse_interval_end(start, true)?,
+        parse_interval_end(end, false)?,
+    );
+    let count = match options {
+        [] => None,
+        [option, count] if args::is_option(option, "COUNT") => {
+            Some(args::parse_i64(count)?.max(0) as usize)
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+
+    let Some(stream) = get_stream(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Array(vec![]));
+    };
+    let entries = stream.range(start..=end);
+    let entries: Box<dyn Iterator<Item = _>> = if reverse {
+        Box::new(entries.rev())
+    } else {
+        Box::new(entries)
+    };
+    let entries = entries
+        .take(count.unwrap_or(usize::MAX))
+        .map(|(id, fields)| entry_reply(id, fields))
+        .collect();
+    Ok(Value::Array(entries))
+}
+
+/// XRANGE key start end [COUNT count]
+pub fn xrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    range_command(params, ctx, false, "xrange")
+}
+
+/// XREVRANGE key end start [COUNT count]
+pub fn xrevrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    range_command(params, ctx, true, "xrevrange")
+}
+
+/// XLEN key
+pub fn xlen(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("xlen".to_string()));
+    };
+    let len = get_stream(ctx, args::bulk(key)?)?.map_or(0, |stream| stream.len());
+    Ok(Value::Integer(len as i64))
+}
+
+/// XDEL key id [id ...]
+pub fn xdel(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, ids)) = params.split_first() else {
+        return Err(CommandError::WrongArity("xdel".to_string()));
+    };
+    let ids = ids.iter().map(parse_id).collect::<Result<Vec<_>, _>>()?;
+
+    // Unlike other types, a stream emptied this way keeps its key and last ID.
+    let Some(stream) = get_stream(ctx, args::bulk(key)?)? else {
+        return Ok(Value::Integer(0));
+    };
+    let deleted = ids.into_iter().filter(|&id| stream.remove(id)).count();
+    Ok(Value::Integer(deleted as i64))
+}
+
+/// Key positions of `... STREAMS key [key ...] id [id ...]`, looking for
+/// STREAMS from `argv[from]` on.
+fn streams_keys(argv: &[Value], from: usize) -> Vec<usize> {
+    let Some(streams) = argv
+        .iter()
+        .skip(from)
+        .position(|arg| args::is_option(arg, "STREAMS"))
+        .map(|at| at + from)
+    else {
+        return vec![];
+    };
+    let rest = argv.len() - streams - 1;
+    if rest == 0 || !rest.is_multiple_of(2) {
+        return vec![];
+    }
+    (streams + 1..streams + 1 + rest / 2).collect()
+}
+
+fn xread_keys(argv: &[Value]) -> Vec<usize> {
+    streams_keys(argv, 1)
+}
+
+/// Skips `GROUP group consumer`, whose names may well be "streams".
+fn xreadgroup_keys(argv: &[Value]) -> Vec<usize> {
+    streams_keys(argv, 4)
+}
+
+/// The options XREAD and XREADGROUP share, and their lists of streams.
+struct ReadOptions<'a> {
+    /// `usize::MAX` when no positive COUNT was given.
+    count: usize,
+    /// BLOCK's timeout, `Some(None)` for BLOCK 0.
+    block: Option<Option<Duration>>,
+    noack: bool,
+    keys: &'a [Value],
+    ids: &'a [Value],
+}
+
+impl<'a> ReadOptions<'a> {
+    fn parse(mut options: &'a [Value], group: bool) -> Result<Self, CommandError> {
+        let (mut count, mut block, mut noack) = (usize::MAX, None, false);
+        let streams = loop {
+            match options {
+                [option, value, rest @ ..] if args::is_option(option, "COUNT") => {
+                    count = match args::parse_i64(value)? {
+                        count if count > 0 => count as usize,
+                        _ => usize::MAX,
+                    };
+                    options = rest;
+                }
+                [option, value, rest @ ..] if args::is_option(option, "BLOCK") => {
+                    let ms = args::parse_i64(value).map_err(|_| {
+                        CommandError::Err("timeout is not an integer or out of range".to_string())
+                    })?;
+                    if ms < 0 {
+                        return Err(CommandError::Err("timeout is negative".to_string()));
+                    }
+                    block = Some((ms > 0).then(|| Duration::from_millis(ms as u64)));
+                    options = rest;
+                }
+                [option, rest @ ..] if group && args::is_option(option, "NOACK") => {
+                    noack = true;
+                    options = rest;
+                }
+                [option, rest @ ..] if args::is_option(option, "STREAMS") => break rest,
+                _ => return Err(CommandError::Syntax),
+            }
+        };
+        if streams.is_empty() || !streams.len().is_multiple_of(2) {
+            let (name, marker) = if group {
+                ("xreadgroup", ">")
+            } else {
+                ("xread", "$")
+            };
+            return Err(CommandError::Err(format!(
+                "Unbalanced '{name}' list of streams: for each stream key an ID or '{marker}' must be specified."
+            )));
+        }
+        let (keys, ids) = streams.split_at(streams.len() / 2);
+        Ok(ReadOptions {
+            count,
+            block,
+            noack,
+            keys,
+            ids,
+        })
+    }
+}
+
+/// Each stream's key and entries: a list of pairs in RESP2, a map in RESP3.
+fn streams_reply(results: Vec<(Value, Value)>, protocol: Protocol) -> Value {
+    match protocol {
+        Protocol::Resp2 => Value::Array(
+            results
+                .into_iter()
+                .map(|(key, entries)| Value::Array(vec![key, entries]))
+                .collect(),
+        ),
+        Protocol::Resp3 => Value::Map(results),
+    }
+}
+
+/// Where XREAD starts reading a stream.
+#[derive(Clone, Copy)]
+enum ReadFrom {
+    /// Entries after this ID; `$` resolves to the stream's last ID.
+    After(StreamId),
+    /// `+`: just the stream's last entry.
+    LastEntry,
+}
+
+/// XREAD [COUNT count] [BLOCK milliseconds] STREAMS key [key ...] id [id ...]
+pub fn xread(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let read = ReadOptions::parse(params, false)?;
+
+    let mut starts = Vec::with_capacity(read.keys.len());
+    for (key, id) in read.keys.iter().zip(read.ids) {
+        let last =
+            get_stream(ctx, args::bulk(key)?)?.map_or(StreamId::MIN, |stream| stream.last_id());
+        starts.push(match args::bulk(id)?.as_ref() {
+            b"$" => ReadFrom::After(last),
+            b"+" => ReadFrom::LastEntry,
+            _ => ReadFrom::After(parse_id(id)?),
+        });
+    }
+
+    let mut results = vec![];
+    for (key, start) in read.keys.iter().zip(&starts) {
+        let key = args::bulk(key)?;
+        let Some(stream) = get_stream(ctx, key)? else {
+            continue;
+        };
+        let entries: Vec<Value> = match *start {
+            ReadFrom::After(id) => match id.next() {
+                Some(from) => stream
+                    .range(from..=StreamId::MAX)
+                    .take(read.count)
+                    .map(|(id, fields)| entry_reply(id, fields))
+                    .collect(),
+                None => vec![],
+            },
+            ReadFrom::LastEntry => stream
+                .last_entry()
+                .map(|(id, fields)| entry_reply(id, fields))
+                .into_iter()
+                .collect(),
+        };
+        if !entries.is_empty() {
+            results.push((Value::BulkString(key.clone()), Value::Array(entries)));
+        }
+    }
+
+    if !results.is_empty() {
+        return Ok(streams_reply(results, ctx.client.protocol));
+    }
+    let Some(timeout) = read.block else {
+        return Ok(Value::NullArray);
+    };
+
+    // Retry with `$` and `+` pinned to what they meant now, so only entries
+    // added from here on wake the client.
+    let mut retry = vec![Value::BulkString(Bytes::from_static(b"XREAD"))];
+    retry.extend_from_slice(&params[..params.len() - read.ids.len()]);
+    for (key, id) in read.keys.iter().zip(read.ids) {
+        let pinned = match args::bulk(id)?.as_ref() {
+            b"$" | b"+" => {
+                let last = get_stream(ctx, args::bulk(key)?)?
+                    .map_or(StreamId::MIN, |stream| stream.last_id());
+                id_reply(last)
+            }
+            _ => id.clone(),
+        };
+        retry.push(pinned);
+    }
+    ctx.block_with_retry(read.keys, timeout, Value::NullArray, Value::Array(retry))
+}
+
+fn no_such_group(key: &[u8], group: &[u8]) -> String {
+    format!(
+        "No such key '{}' or consumer group '{}'",
+        String::from_utf8_lossy(key),
+        String::from_utf8_lossy(group)
+    )
+}
+
+/// The stream at `key` if it has a group named `group`, or NOGROUP.
+fn stream_with_group<'a>(
+    ctx: &'a mut Context,
+    key: &[u8],
+    group: &[u8],
+) -> Result<&'a mut Stream, CommandError> {
+    match get_stream(ctx, key)? {
+        Some(stream) if stream.groups().contains_key(group) => Ok(stream),
+        _ => Err(CommandError::NoGroup(no_such_group(key, group))),
+    }
+}
+
+/// XREADGROUP GROUP group consumer [COUNT count] [BLOCK milliseconds] [NOACK]
+///   STREAMS key [key ...] id [id ...]
+pub fn xreadgroup(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [option, group, consumer, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xreadgroup".to_string()));
+    };
+    if !args::is_option(option, "GROUP") {
+        return Err(CommandError::Syntax);
+    }
+    let (group, consumer) = (args::bulk(group)?, args::bulk(consumer)?);
+    let read = ReadOptions::parse(options, true)?;
+
+    // `None` reads new entries, `Some(id)` the consumer's history after `id`.
+    let mut starts = Vec::with_capacity(read.keys.len());
+    for (key, id) in read.keys.iter().zip(read.ids) {
+        let key = args::bulk(key)?;
+        stream_with_group(ctx, key, group).map_err(|_| {
+            CommandError::NoGroup(format!(
+                "{} in XREADGROUP with GROUP option",
+                no_such_group(key, group)
+            ))
+        })?;
+        starts.push(match args::bulk(id)?.as_ref() {
+            b">" => None,
+            b"$" => {
+                return Err(CommandError::Err(
+                    "The $ ID is meaningless in the context of XREADGROUP: you want to read the history of this consumer by specifying a proper ID, or use the > ID to get new messages. The $ ID would just return an empty result set."
+                        .to_string(),
+                ));
+            }
+            _ => Some(parse_id(id)?),
+        });
+    }
+
+    let now = now_ms();
+    let mut results = vec![];
+    for (key, start) in read.keys.iter().zip(starts) {
+        let key = args::bulk(key)?;
+        let stream = get_stream(ctx, key)?.expect("checked above");
+        let entries = match start {
+            None => deliver_new(stream, group, consumer, &read, now),
+            Some(after) => deliver_history(stream, group, consumer, after, read.count, now),
+        };
+        // History reads report their stream even when there is nothing left.
+        if start.is_some() || !entries.is_empty() {
+            results.push((Value::BulkString(key.clone()), Value::Array(entries)));
+        }
+    }
+
+    if !results.is_empty() {
+        return Ok(streams_reply(results, ctx.client.protocol));
+    }
+    match read.block {
+        Some(timeout) => ctx.block(read.keys, timeout, Value::NullArray),
+        None => Ok(Value::NullArray),
+    }
+}
+
+/// Hands `consumer` the entries after the group's last delivered one, making
+/// them pending unless NOACK was given.
+fn deliver_new(
+    stream: &mut Stream,
+    group: &[u8],
+    consumer: &Bytes,
+    read: &ReadOptions,
+    now: u64,
+) -> Vec<Value> {
+    let (last, mut entries_read) = {
+        let group = &stream.groups()[group];
+        (group.last_delivered, group.entries_read)
+    };
+    let (ids, entries): (Vec<StreamId>, Vec<Value>) = match last.next() {
+        Some(from) => stream
+            .range(from..=StreamId::MAX)
+            .take(read.count)
+            .map(|(id, fields)| (*id, entry_reply(id, fields)))
+            .unzip(),
+        None => (vec![], vec![]),
+    };
+    for &id in &ids {
+        entries_read = stream.entries_read_after(entries_read, id);
+    }
+
+    let group = stream.group_mut(group).expect("checked by the caller");
+    group.consumer(consumer, now);
+    if let Some(&last) = ids.last() {
+        group.last_delivered = last;
+        group.entries_read = entries_read;
+        group.consumer(consumer, now).active_time = Some(now);
+    }
+    if !read.noack {
+        for id in ids {
+            group.deliver(id, consumer, now, 1);
+        }
+    }
+    entries
+}
+
+/// Delivers `consumer`'s pending entries after `after` again. Entries since
+/// deleted from the stream come back with nil fields.
+fn deliver_history(
+    stream: &mut Stream,
+    group: &[u8],
+    consumer: &Bytes,
+    after: StreamId,
+    count: usize,
+    now: u64,
+) -> Vec<Value> {
+    let group_state = stream.group_mut(group).expect("checked by the caller");
+    let ids: Vec<StreamId> = group_state
+        .consumer(consumer, now)
+        .pending()
+        .range((Bound::Excluded(after), Bound::Unbounded))
+        .take(count)
+        .copied()
+        .collect();
+
+    let mut delivered = vec![];
+    let entries = ids
+        .iter()
+        .map(|id| match stream.get(*id) {
+            Some(fields) => {
+                delivered.push(*id);
+                entry_reply(id, fields)
+            }
+            None => Value::Array(vec![id_reply(*id), Value::NullArray]),
+        })
+        .collect();
+
+    let group = stream.group_mut(group).expect("checked by the caller");
+    for id in delivered {
+        let delivery_count = group.pending()[&id].delivery_count;
+        group.deliver(id, consumer, now, delivery_count + 1);
+    }
+    if !ids.is_empty() {
+        group.consumer(consumer, now).active_time = Some(now);
+    }
+    entries
+}
+
+/// XACK key group id [id ...]
+pub fn xack(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, ids @ ..] = params else {
+        return Err(CommandError::WrongArity("xack".to_string()));
+    };
+    let ids = ids.iter().map(parse_id).collect::<Result<Vec<_>, _>>()?;
+
+    let Some(group) = get_stream(ctx, args::bulk(key)?)?
+        .and_then(|stream| stream.group_mut(args::bulk(group).ok()?))
+    else {
+        return Ok(Value::Integer(0));
+    };
+    let acked = ids.into_iter().filter(|&id| group.ack(id)).count();
+    Ok(Value::Integer(acked as i64))
+}
+
+/// The extended form of XPENDING.
+struct PendingQuery {
+    min_idle: u64,
+    start: StreamId,
+    end: StreamId,
+    count: usize,
+    consumer: Option<Bytes>,
+}
+
+impl PendingQuery {
+    fn parse(options: &[Value]) -> Result<Self, CommandError> {
+        let (min_idle, options) = match options {
+            [option, idle, rest @ ..] if args::is_option(option, "IDLE") => {
+                (args::parse_i64(idle)?.max(0) as u64, rest)
+            }
+            _ => (0, options),
+        };
+        let [start, end, count, consumer @ ..] = options else {
+            return Err(CommandError::Syntax);
+        };
+        let consumer = match consumer {
+            [] => None,
+            [consumer] => Some(args::bulk(consumer)?.clone()),
+            _ => return Err(CommandError::Syntax),
+        };
+        Ok(PendingQuery {
+            min_idle,
+            start: parse_interval_end(start, true)?,
+            end: parse_interval_end(end, false)?,
+            count: args::parse_i64(count)?.max(0) as usize,
+            consumer,
+        })
+    }
+}
+
+/// XPENDING key group [[IDLE min-idle-time] start end count [consumer]]
+pub fn xpending(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xpending".to_string()));
+    };
+    let (key, group) = (args::bulk(key)?, args::bulk(group)?);
+    let query = match options {
+        [] => None,
+        options => Some(PendingQuery::parse(options)?),
+    };
+    let now = now_ms();
+    let group = &stream_with_group(ctx, key, group)?.groups()[group];
+    let pending = group.pending();
+
+    let Some(query) = query else {
+        let (Some((first, _)), Some((last, _))) =
+            (pending.first_key_value(), pending.last_key_value())
+        else {
+            return Ok(Value::Array(vec![
+                Value::Integer(0),
+                Value::NullString,
+                Value::NullString,
+                Value::NullArray,
+            ]));
+        };
+        let consumers = group
+            .consumers()
+            .iter()
+            .filter(|(_, consumer)| !consumer.pending().is_empty())
+            .map(|(name, consumer)| {
+                Value::Array(vec![
+                    Value::BulkString(name.clone()),
+                    Value::BulkString(Bytes::from(consumer.pending().len().to_string())),
+                ])
+            })
+            .collect();
+        return Ok(Value::Array(vec![
+            Value::Integer(pending.len() as i64),
+            id_reply(*first),
+            id_reply(*last),
+            Value::Array(consumers),
+        ]));
+    };
+
+    if query.start > query.end {
+        return Ok(Value::Array(vec![]));
+    }
+    let entries = pending
+        .range(query.start..=query.end)
+        .filter(|(_, entry)| {
+            query
+                .consumer
+                .as_ref()
+                .is_none_or(|consumer| entry.consumer == consumer)
+        })
+        .filter(|(_, entry)| now.saturating_sub(entry.delivery_time) >= query.min_idle)
+        .take(query.count)
+        .map(|(id, entry)| {
+            Value::Array(vec![
+                id_reply(*id),
+                Value::BulkString(entry.consumer.clone()),
+                Value::Integer(now.saturating_sub(entry.delivery_time) as i64),
+                Value::Integer(entry.delivery_count as i64),
+            ])
+        })
+        .collect();
+    Ok(Value::Array(entries))
+}
+
+fn parse_min_idle(arg: &Value, cmd: &str) -> Result<u64, CommandError> {
+    let min_idle = args::parse_i64(arg)
+        .map_err(|_| CommandError::Err(format!("Invalid min-idle-time argument for {cmd}")))?;
+    Ok(min_idle.max(0) as u64)
+}
+
+/// XCLAIM key group consumer min-idle-time id [id ...] [IDLE ms]
+///   [TIME unix-time-milliseconds] [RETRYCOUNT count] [FORCE] [JUSTID]
+///   [LASTID lastid]
+pub fn xclaim(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, consumer, min_idle, rest @ ..] = params else {
+        return Err(CommandError::WrongArity("xclaim".to_string()));
+    };
+    let (key, group, consumer) = (args::bulk(key)?, args::bulk(group)?, args::bulk(consumer)?);
+    let min_idle = parse_min_idle(min_idle, "XCLAIM")?;
+    // The IDs run up to the first argument that isn't one.
+    let id_count = rest
+        .iter()
+        .take_while(|arg| args::bulk(arg).is_ok_and(|arg| StreamId::parse(arg, 0).is_some()))
+        .count();
+    let (ids, mut options) = rest.split_at(id_count);
+    let ids = ids.iter().map(parse_id).collect::<Result<Vec<_>, _>>()?;
+
+    let now = now_ms();
+    let option_value = |value: &Value, name: &str| {
+        args::parse_i64(value)
+            .map(|value| value.max(0) as u64)
+            .map_err(|_| CommandError::Err(format!("Invalid {name} option argument for XCLAIM")))
+    };
+    let (mut delivery_time, mut retry_count, mut last_id) = (now, None, None);
+    let (mut force, mut justid) = (false, false);
+    loop {
+        match options {
+            [] => break,
+            [option, value, rest @ ..] if args::is_option(option, "IDLE") => {
+                delivery_time = now.saturating_sub(option_value(value, "IDLE")?);
+                options = rest;
+            }
+            [option, value, rest @ ..] if args::is_option(option, "TIME") => {
+                delivery_time = option_value(value, "TIME")?;
+                options = rest;
+            }
+            [option, value, rest @ ..] if args::is_option(option, "RETRYCOUNT") => {
+                retry_count = Some(option_value(value, "RETRYCOUNT")?);
+                options = rest;
+            }
+            [option, value, rest @ ..] if args::is_option(option, "LASTID") => {
+                last_id = Some(parse_id(value)?);
+                options = rest;
+            }
+            [option, rest @ ..] if args::is_option(option, "FORCE") => {
+                force = true;
+                options = rest;
+            }
+            [option, rest @ ..] if args::is_option(option, "JUSTID") => {
+                justid = true;
+                options = rest;
+            }
+            [option, ..] => {
+                return Err(CommandError::Err(format!(
+                    "Unrecognized XCLAIM option '{}'",
+                    String::from_utf8_lossy(args::bulk(option)?)
+                )));
+            }
+        }
+    }
+    // A delivery can't be in the future.
+    let delivery_time = delivery_time.min(now);
+
+    let stream = stream_with_group(ctx, key, group)?;
+    let exists: Vec<bool> = ids.iter().map(|&id| stream.get(id).is_some()).collect();
+    let group = stream.group_mut(group).expect("checked above");
+    if let Some(last_id) = last_id
+        && last_id > group.last_delivered
+    {
+        group.last_delivered = last_id;
+    }
+    group.consumer(consumer, now);
+
+    let mut claimed = vec![];
+    for (&id, exists) in ids.iter().zip(exists) {
+        if !exists {
+            // Nobody can process an entry deleted from the stream.
+            group.ack(id);
+            continue;
+        }
+        let (delivered_at, delivery_count) = match group.pending().get(&id) {
+            Some(entry) => (entry.delivery_time, entry.delivery_count),
+            None if force => (now, 1),
+            None => continue,
+        };
+        if now.saturating_sub(delivered_at) < min_idle {
+            continue;
+        }
+        let delivery_count = match retry_count {
+            Some(retry_count) => retry_count,
+            None if justid => delivery_count,
+            None => delivery_count + 1,
+        };
+        group.deliver(id, consumer, delivery_time, delivery_count);
+        claimed.push(id);
+    }
+    if !claimed.is_empty() {
+        group.consumer(consumer, now).active_time = Some(now);
+    }
+
+    let replies = claimed
+        .iter()
+        .map(|id| match justid {
+            true => id_reply(*id),
+            false => entry_reply(id, stream.get(*id).expect("claimed entries exist")),
+        })
+        .collect();
+    Ok(Value::Array(replies))
+}
+
+/// XAUTOCLAIM key group consumer min-idle-time start [COUNT count] [JUSTID]
+pub fn xautoclaim(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, consumer, min_idle, start, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xautoclaim".to_string()));
+    };
+    let mut options = options;
+    let (key, group, consumer) = (args::bulk(key)?, args::bulk(group)?, args::bulk(consumer)?);
+    let min_idle = parse_min_idle(min_idle, "XAUTOCLAIM")?;
+    let start = parse_interval_end(start, true)?;
+    let (mut count, mut justid) = (100, false);
+    loop {
+        match options {
+            [] => break,
+            [option, value, rest @ ..] if args::is_option(option, "COUNT") => {
+                count = match args::parse_i64(value)? {
+                    count if count >= 1 && count <= i64::MAX / ATTEMPTS_PER_CLAIM as i64 => {
+                        count as usize
+                    }
+                    _ => return Err(CommandError::Err("COUNT must be > 0".to_string())),
+                };
+                options = rest;
+            }
+            [option, rest @ ..] if args::is_option(option, "JUSTID") => {
+                justid = true;
+                options = rest;
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+    }
+
+    let now = now_ms();
+    let stream = stream_with_group(ctx, key, group)?;
+    // One more than we may look at, to tell where the next call should start.
+    let attempts = count * ATTEMPTS_PER_CLAIM;
+    let candidates: Vec<(StreamId, bool)> = stream.groups()[group]
+        .pending()
+        .range(start..)
+        .take(attempts + 1)
+        .map(|(id, _)| (*id, stream.get(*id).is_some()))
+        .collect();
+    let group = stream.group_mut(group).expect("checked above");
+    group.consumer(consumer, now);
+
+    let (mut claimed, mut deleted, mut cursor) = (vec![], vec![], StreamId::MIN);
+    for (attempt, (id, exists)) in candidates.into_iter().enumerate() {
+        if attempt == attempts || claimed.len() == count {
+            cursor = id;
+            break;
+        }
+        if !exists {
+            group.ack(id);
+            deleted.push(id_reply(id));
+            continue;
+        }
+        let entry = &group.pending()[&id];
+        if now.saturating_sub(entry.delivery_time) < min_idle {
+            continue;
+        }
+        let delivery_count = entry.delivery_count + u64::from(!justid);
+        group.deliver(id, consumer, now, delivery_count);
+        claimed.push(id);
+    }
+    if !claimed.is_empty() {
+        group.consumer(consumer, now).active_time = Some(now);
+    }
+
+    let replies = claimed
+        .iter()
+        .map(|id| match justid {
+            true => id_reply(*id),
+            false => entry_reply(id, stream.get(*id).expect("claimed entries exist")),
+        })
+        .collect();
+    Ok(Value::Array(vec![
+        id_reply(cursor),
+        Value::Array(replies),
+        Value::Array(deleted),
+    ]))
+}
+
+/// How many pending entries XAUTOCLAIM may look at per entry it's asked for.
+const ATTEMPTS_PER_CLAIM: usize = 10;
+
+/// The stream an XGROUP subcommand works on, which must exist.
+fn xgroup_stream<'a>(ctx: &'a mut Context, key: &[u8]) -> Result<&'a mut Stream, CommandError> {
+    get_stream(ctx, key)?.ok_or_else(|| {
+        CommandError::Err(
+            "The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically."
+                .to_string(),
+        )
+    })
+}
+
+fn group_not_found(key: &[u8], group: &[u8]) -> CommandError {
+    CommandError::NoGroup(format!(
+        "No such consumer group '{}' for key name '{}'",
+        String::from_utf8_lossy(group),
+        String::from_utf8_lossy(key)
+    ))
+}
+
+/// An ID, or `None` for `$`, which stands for the stream's last ID.
+fn parse_group_id(arg: &Value) -> Result<Option<StreamId>, CommandError> {
+    match args::bulk(arg)?.as_ref() {
+        b"$" => Ok(None),
+        _ => parse_id(arg).map(Some),
+    }
+}
+
+/// Takes an `ENTRIESREAD entries-read` option off the front of `options`,
+/// where -1 means unknown.
+fn consume_entries_read<'a>(
+    options: &'a [Value],
+    entries_read: &mut Option<u64>,
+) -> Result<Option<&'a [Value]>, CommandError> {
+    let [option, value, rest @ ..] = options else {
+        return Ok(None);
+    };
+    if !args::is_option(option, "ENTRIESREAD") {
+        return Ok(None);
+    }
+    *entries_read = match args::parse_i64(value)? {
+        -1 => None,
+        read if read >= 0 => Some(read as u64),
+        _ => {
+            return Err(CommandError::Err(
+                "value for ENTRIESREAD must be positive or -1".to_string(),
+            ));
+        }
+    };
+    Ok(Some(rest))
+}
+
+/// XGROUP CREATE key group <id | $> [MKSTREAM] [ENTRIESREAD entries-read]
+pub fn xgroup_create(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, id, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xgroup|create".to_string()));
+    };
+    let (mut options, mut mkstream, mut entries_read) = (options, false, None);
+    while let [option, rest @ ..] = options {
+        if args::is_option(option, "MKSTREAM") {
+            mkstream = true;
+            options = rest;
+            continue;
+        }
+        options = consume_entries_read(options, &mut entries_read)?.ok_or(CommandError::Syntax)?;
+    }
+    let key = args::bulk(key)?;
+    if !mkstream {
+        xgroup_stream(ctx, key)?;
+    }
+    // Parse the ID before MKSTREAM creates anything, so a bad one leaves no stream.
+    let id = parse_group_id(id)?;
+    if get_stream(ctx, key)?.is_none() {
+        ctx.store.insert(
+            key.clone(),
+            StoredValue {
+                value: Data::Stream(Stream::new()),
+                expiry: None,
+            },
+        );
+    }
+    let stream = xgroup_stream(ctx, key)?;
+    let id = id.unwrap_or_else(|| stream.last_id());
+    if !stream.create_group(args::bulk(group)?.clone(), id, entries_read) {
+        return Err(CommandError::BusyGroup);
+    }
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+/// XGROUP SETID key group <id | $> [ENTRIESREAD entries-read]
+pub fn xgroup_setid(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, id, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xgroup|setid".to_string()));
+    };
+    let mut entries_read = None;
+    if !options.is_empty() && consume_entries_read(options, &mut entries_read)? != Some(&[]) {
+        return Err(CommandError::Syntax);
+    }
+    let (key, group) = (args::bulk(key)?, args::bulk(group)?);
+    let stream = xgroup_stream(ctx, key)?;
+    let id = parse_group_id(id)?.unwrap_or_else(|| stream.last_id());
+    let group = stream
+        .group_mut(group)
+        .ok_or_else(|| group_not_found(key, group))?;
+    group.last_delivered = id;
+    group.entries_read = entries_read;
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+/// XGROUP DESTROY key group
+pub fn xgroup_destroy(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group] = params else {
+        return Err(CommandError::WrongArity("xgroup|destroy".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let destroyed = xgroup_stream(ctx, key)?.destroy_group(args::bulk(group)?);
+    if destroyed {
+        // Clients blocked reading the group find out it's gone.
+        ctx.store.blocking.signal(key);
+    }
+    Ok(Value::Integer(destroyed as i64))
+}
+
+/// XGROUP CREATECONSUMER key group consumer
+pub fn xgroup_createconsumer(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, consumer] = params else {
+        return Err(CommandError::WrongArity(
+            "xgroup|createconsumer".to_string(),
+        ));
+    };
+    let (key, group) = (args::bulk(key)?, args::bulk(group)?);
+    let group = xgroup_stream(ctx, key)?
+        .group_mut(group)
+        .ok_or_else(|| group_not_found(key, group))?;
+    let created = group.create_consumer(args::bulk(consumer)?, now_ms());
+    Ok(Value::Integer(created as i64))
+}
+
+/// XGROUP DELCONSUMER key group consumer
+pub fn xgroup_delconsumer(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group, consumer] = params else {
+        return Err(CommandError::WrongArity("xgroup|delconsumer".to_string()));
+    };
+    let (key, group) = (args::bulk(key)?, args::bulk(group)?);
+    let group = xgroup_stream(ctx, key)?
+        .group_mut(group)
+        .ok_or_else(|| group_not_found(key, group))?;
+    let pending = group.delete_consumer(args::bulk(consumer)?).unwrap_or(0);
+    Ok(Value::Integer(pending as i64))
+}
+
+/// XSETID key last-id [ENTRIESADDED entries-added] [MAXDELETEDID max-deleted-id]
+pub fn xsetid(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, id, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xsetid".to_string()));
+    };
+    let id = parse_id(id)?;
+    let (mut options, mut entries_added, mut max_deleted_id) = (options, None, None);
+    loop {
+        match options {
+            [] => break,
+            [option, value, rest @ ..] if args::is_option(option, "ENTRIESADDED") => {
+                let added = args::parse_i64(value)?;
+                if added < 0 {
+                    return Err(CommandError::Err(
+                        "entries_added must be positive".to_string(),
+                    ));
+                }
+                entries_added = Some(added as u64);
+                options = rest;
+            }
+            [option, value, rest @ ..] if args::is_option(option, "MAXDELETEDID") => {
+                max_deleted_id = Some(parse_id(value)?);
+                options = rest;
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+    }
+    if max_deleted_id.is_some_and(|max_deleted_id| id < max_deleted_id) {
+        return Err(CommandError::Err(
+            "The ID specified in XSETID is smaller than the provided max_deleted_entry_id"
+                .to_string(),
+        ));
+    }
+
+    let stream = get_stream(ctx, args::bulk(key)?)?.ok_or(CommandError::NoSuchKey)?;
+    if entries_added.is_some_and(|added| added < stream.len() as u64) {
+        return Err(CommandError::Err(
+            "The entries_added specified in XSETID is smaller than the target stream length"
+                .to_string(),
+        ));
+    }
+    if stream.last_entry().is_some_and(|(last, _)| id < *last) {
+        return Err(CommandError::Err(
+            "The ID specified in XSETID is smaller than the target stream top item".to_string(),
+        ));
+    }
+    stream.set_metadata(
+        id,
+        entries_added.unwrap_or(stream.entries_added()),
+        max_deleted_id.unwrap_or(stream.max_deleted_id()),
+    );
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+fn bulk(s: &str) -> Value {
+    Value::BulkString(Bytes::from(s.to_string()))
+}
+
+fn optional_integer(value: Option<u64>) -> Value {
+    value.map_or(Value::NullString, |value| Value::Integer(value as i64))
+}
+
+/// XINFO STREAM key [FULL [COUNT count]]
+pub fn xinfo_stream(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, options @ ..] = params else {
+        return Err(CommandError::WrongArity("xinfo|stream".to_string()));
+    };
+    // FULL lists up to COUNT entries and pending entries; 0 lists them all.
+    let full = match options {
+        [] => None,
+        [option] if args::is_option(option, "FULL") => Some(10),
+        [option, count_option, count]
+            if args::is_option(option, "FULL") && args::is_option(count_option, "COUNT") =>
+        {
+            match args::parse_i64(count)? {
+                count if count > 0 => Some(count as usize),
+                _ => Some(usize::MAX),
+            }
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+    let stream = get_stream(ctx, args::bulk(key)?)?.ok_or(CommandError::NoSuchKey)?;
+
+    let mut info = vec![
+        (bulk("length"), Value::Integer(stream.len() as i64)),
+        (
+            bulk("radix-tree-keys"),
+            Value::Integer(stream.node_count() as i64),
+        ),
+        (
+            bulk("radix-tree-nodes"),
+            Value::Integer(stream.node_count() as i64),
+        ),
+        (bulk("last-generated-id"), id_reply(stream.last_id())),
+        (
+            bulk("max-deleted-entry-id"),
+            id_reply(stream.max_deleted_id()),
+        ),
+        (
+            bulk("entries-added"),
+            Value::Integer(stream.entries_added() as i64),
+        ),
+        (bulk("recorded-first-entry-id"), id_reply(stream.first_id())),
+    ];
+    let Some(count) = full else {
+        let entry = |entry: Option<(&StreamId, &Fields)>| {
+            entry.map_or(Value::NullString, |(id, fields)| entry_reply(id, fields))
+        };
+        info.extend([
+            (bulk("groups"), Value::Integer(stream.groups().len() as i64)),
+            (bulk("first-entry"), entry(stream.first_entry())),
+            (bulk("last-entry"), entry(stream.last_entry())),
+        ]);
+        return Ok(Value::Map(info));
+    };
+
+    let entries = stream
+        .range(StreamId::MIN..=StreamId::MAX)
+        .take(count)
+        .map(|(id, fields)| entry_reply(id, fields))
+        .collect();
+    let groups = stream
+        .groups()
+        .iter()
+        .map(|(name, group)| full_group_info(stream, name, group, count))
+        .collect();
+    info.extend([
+        (bulk("entries"), Value::Array(entries)),
+        (bulk("groups"), Value::Array(groups)),
+    ]);
+    Ok(Value::Map(info))
+}
+
+/// A group as XINFO STREAM FULL describes it, with up to `count` pending
+/// entries for it and for each consumer.
+fn full_group_info(stream: &Stream, name: &Bytes, group: &ConsumerGroup, count: usize) -> Value {
+    let pending = group
+        .pending()
+        .iter()
+        .take(count)
+        .map(|(id, entry)| {
+            Value::Array(vec![
+                id_reply(*id),
+                Value::BulkString(entry.consumer.clone()),
+                Value::Integer(entry.delivery_time as i64),
+                Value::Integer(entry.delivery_count as i64),
+            ])
+        })
+        .collect();
+    let consumers = group
+        .consumers()
+        .iter()
+        .map(|(consumer_name, consumer)| {
+            let pending = consumer
+                .pending()
+                .iter()
+                .take(count)
+                .map(|id| {
+                    let entry = &group.pending()[id];
+                    Value::Array(vec![
+                        id_reply(*id),
+                        Value::Integer(entry.delivery_time as i64),
+                        Value::Integer(entry.delivery_count as i64),
+                    ])
+                })
+                .collect();
+            Value::Map(vec![
+                (bulk("name"), Value::BulkString(consumer_name.clone())),
+                (bulk("seen-time"), Value::Integer(consumer.seen_time as i64)),
+                (
+                    bulk("active-time"),
+                    Value::Integer(consumer.active_time.map_or(-1, |time| time as i64)),
+                ),
+                (
+                    bulk("pel-count"),
+                    Value::Integer(consumer.pending().len() as i64),
+                ),
+                (bulk("pending"), Value::Array(pending)),
+            ])
+        })
+        .collect();
+    Value::Map(vec![
+        (bulk("name"), Value::BulkString(name.clone())),
+        (bulk("last-delivered-id"), id_reply(group.last_delivered)),
+        (bulk("entries-read"), optional_integer(group.entries_read)),
+        (bulk("lag"), optional_integer(stream.lag(group))),
+        (
+            bulk("pel-count"),
+            Value::Integer(group.pending().len() as i64),
+        ),
+        (bulk("pending"), Value::Array(pending)),
+        (bulk("consumers"), Value::Array(consumers)),
+    ])
+}
+
+/// XINFO GROUPS key
+pub fn xinfo_groups(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("xinfo|groups".to_string()));
+    };
+    let stream = get_stream(ctx, args::bulk(key)?)?.ok_or(CommandError::NoSuchKey)?;
+    let groups = stream
+        .groups()
+        .iter()
+        .map(|(name, group)| {
+            Value::Map(vec![
+                (bulk("name"), Value::BulkString(name.clone())),
+                (
+                    bulk("consumers"),
+                    Value::Integer(group.consumers().len() as i64),
+                ),
+                (
+                    bulk("pending"),
+                    Value::Integer(group.pending().len() as i64),
+                ),
+                (bulk("last-delivered-id"), id_reply(group.last_delivered)),
+                (bulk("entries-read"), optional_integer(group.entries_read)),
+                (bulk("lag"), optional_integer(stream.lag(group))),
+            ])
+        })
+        .collect();
+    Ok(Value::Array(groups))
+}
+
+/// XINFO CONSUMERS key group
+pub fn xinfo_consumers(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, group] = params else {
+        return Err(CommandError::WrongArity("xinfo|consumers".to_string()));
+    };
+    let (key, group) = (args::bulk(key)?, args::bulk(group)?);
+    let now = now_ms();
+    let stream = get_stream(ctx, key)?.ok_or(CommandError::NoSuchKey)?;
+    let group = stream
+        .groups()
+        .get(group)
+        .ok_or_else(|| group_not_found(key, group))?;
+    let consumers = group
+        .consumers()
+        .iter()
+        .map(|(name, consumer)| {
+            let inactive = consumer
+                .active_time
+                .map_or(-1, |time| now.saturating_sub(time) as i64);
+            Value::Map(vec![
+                (bulk("name"), Value::BulkString(name.clone())),
+                (
+                    bulk("pending"),
+                    Value::Integer(consumer.pending().len() as i64),
+                ),
+                (
+                    bulk("idle"),
+                    Value::Integer(now.saturating_sub(consumer.seen_time) as i64),
+                ),
+                (bulk("inactive"), Value::Integer(inactive)),
+            ])
+        })
+        .collect();
+    Ok(Value::Array(consumers))
+}
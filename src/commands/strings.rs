@@ -1,55 +1,679 @@
-use crate::db;
-use crate::db::DB;
+use super::registry::{CommandSpec, Flag};
+use super::{Context, args};
+use crate::db::{self, Data};
+use crate::error::CommandError;
 use crate::resp::Value;
-use std::ops::Add;
-use std::sync::MutexGuard;
-use std::time::{Duration, SystemTime};
-
-pub fn eval_set(params: &[Value], mut store: MutexGuard<DB>) -> Result<Value, String> {
-    println!("set params: {params:?}");
-
-    match params {
-        [Value::BulkString(name), Value::BulkString(value)] => {
-            store.db.insert(
-                String::from(name),
-                db::StoredValue {
-                    value: Value::BulkString(String::from(value)),
-                    expiry: None,
-                },
-            );
-        }
-        [
-            Value::BulkString(name),
-            Value::BulkString(value),
-            Value::BulkString(_cmd),
-            Value::BulkString(str_px),
-        ] => {
-            let px = str_px
-                .parse::<u64>()
-                .map_err(|err| format!("invalid px: {err}"))?;
-            store.db.insert(
-                String::from(name),
-                db::StoredValue {
-                    value: Value::BulkString(String::from(value)),
-                    expiry: Some(SystemTime::now().add(Duration::from_millis(px))),
-                },
-            );
-        }
-        _ => {
-            return Err("invalid number of arguments".to_string());
+use bytes::{Bytes, BytesMut};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) const COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("set", "string", -3, eval_set)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Sets the string value of a key, ignoring its type."),
+    CommandSpec::new("get", "string", 2, eval_get)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the string value of a key."),
+    CommandSpec::new("append", "string", 3, eval_append)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Appends a string to the value of a key. Creates the key if it doesn't exist."),
+    CommandSpec::new("strlen", "string", 2, eval_strlen)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the length of a string value."),
+    CommandSpec::new("getrange", "string", 4, eval_getrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns a substring of the string stored at a key."),
+    CommandSpec::new("substr", "string", 4, eval_getrange)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 1, 1)
+        .summary("Returns a substring from a string value."),
+    CommandSpec::new("setrange", "string", 4, eval_setrange)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Overwrites a part of a string value with another by an offset."),
+    CommandSpec::new("mset", "string", -3, eval_mset)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, -1, 2)
+        .summary("Atomically creates or modifies the string values of one or more keys."),
+    CommandSpec::new("msetnx", "string", -3, eval_msetnx)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, -1, 2)
+        .summary("Atomically modifies the string values of one or more keys only when all keys don't exist."),
+    CommandSpec::new("mget", "string", -2, eval_mget)
+        .flags(&[Flag::ReadOnly, Flag::Fast])
+        .keys(1, -1, 1)
+        .summary("Atomically returns the string values of one or more keys."),
+    CommandSpec::new("getset", "string", 3, eval_getset)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the previous string value of a key after setting it to a new value."),
+    CommandSpec::new("getdel", "string", 2, eval_getdel)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the string value of a key after deleting the key."),
+    CommandSpec::new("getex", "string", -2, eval_getex)
+        .flags(&[Flag::Write, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Returns the string value of a key after setting its expiration time."),
+    CommandSpec::new("setnx", "string", 3, eval_setnx)
+        .flags(&[Flag::Write, Flag::DenyOom, Flag::Fast])
+        .keys(1, 1, 1)
+        .summary("Set the string value of a key only when the key doesn't exist."),
+    CommandSpec::new("setex", "string", 4, eval_setex)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Sets the string value and expiration time of a key. Creates the key if it doesn't exist."),
+    CommandSpec::new("psetex", "string", 4, eval_psetex)
+        .flags(&[Flag::Write, Flag::DenyOom])
+        .keys(1, 1, 1)
+        .summary("Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist."),
+    CommandSpec::new("lcs", "string", -3, eval_lcs)
+        .flags(&[Flag::ReadOnly])
+        .keys(1, 2, 1)
+        .summary("Finds the longest common substring."),
+];
+
+/// Largest string SETRANGE and APPEND may grow a value to, like proto-max-bulk-len.
+const MAX_STRING_LENGTH: usize = 512 * 1024 * 1024;
+
+/// The string stored at `key`, `None` if it is missing, or WRONGTYPE.
+pub(super) fn get_string<'a>(
+    ctx: &'a mut Context,
+    key: &[u8],
+) -> Result<Option<&'a Bytes>, CommandError> {
+    match ctx.store.get(key).map(|stored| &stored.value) {
+        None => Ok(None),
+        Some(Data::String(value)) => Ok(Some(value)),
+        Some(_) => Err(CommandError::WrongType),
+    }
+}
+
+pub(super) fn set_string(ctx: &mut Context, key: &Bytes, value: Bytes, expiry: Option<SystemTime>) {
+    ctx.store.insert(
+        key.clone(),
+        db::StoredValue {
+            value: Data::String(value),
+            expiry,
+        },
+    );
+}
+
+fn bulk_or_null(value: Option<&Bytes>) -> Value {
+    match value {
+        Some(value) => Value::BulkString(value.clone()),
+        None => Value::NullString,
+    }
+}
+
+/// How long a value written by SET should live.
+enum Expiry {
+    Never,
+    At(SystemTime),
+    KeepTtl,
+}
+
+#[derive(PartialEq)]
+enum Condition {
+    Always,
+    IfMissing,
+    IfExists,
+}
+
+/// Turns an `EX`/`PX`/`EXAT`/`PXAT` option and its argument into an absolute
+/// deadline. Relative ones must be positive; all of them must fit in a `SystemTime`.
+pub(super) fn parse_expiry(
+    option: &Value,
+    arg: &Value,
+    cmd: &str,
+) -> Result<SystemTime, CommandError> {
+    let amount = args::parse_i64(arg)?;
+    let invalid = || CommandError::InvalidExpireTime(cmd.to_string());
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    let amount = amount as u64;
+
+    let deadline = if args::is_option(option, "EX") {
+        amount
+            .checked_mul(1000)
+            .and_then(|ms| SystemTime::now().checked_add(Duration::from_millis(ms)))
+    } else if args::is_option(option, "PX") {
+        SystemTime::now().checked_add(Duration::from_millis(amount))
+    } else if args::is_option(option, "EXAT") {
+        amount
+            .checked_mul(1000)
+            .and_then(|ms| UNIX_EPOCH.checked_add(Duration::from_millis(ms)))
+    } else {
+        UNIX_EPOCH.checked_add(Duration::from_millis(amount))
+    };
+    deadline.ok_or_else(invalid)
+}
+
+pub(super) fn is_expiry_option(arg: &Value) -> bool {
+    ["EX", "PX", "EXAT", "PXAT"]
+        .iter()
+        .any(|option| args::is_option(arg, option))
+}
+
+/// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+///   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
+pub fn eval_set(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, value, options @ ..] = params else {
+        return Err(CommandError::WrongArity("set".to_string()));
+    };
+    let key = args::bulk(key)?;
+    let value = args::bulk(value)?;
+
+    let mut condition = Condition::Always;
+    let mut get = false;
+    let mut expiry = None;
+
+    let mut i = 0;
+    while i < options.len() {
+        let option = &options[i];
+        if args::is_option(option, "NX") && condition != Condition::IfExists {
+            condition = Condition::IfMissing;
+        } else if args::is_option(option, "XX") && condition != Condition::IfMissing {
+            condition = Condition::IfExists;
+        } else if args::is_option(option, "GET") {
+            get = true;
+        } else if args::is_option(option, "KEEPTTL") && expiry.is_none() {
+            expiry = Some(Expiry::KeepTtl);
+        } else if is_expiry_option(option) && expiry.is_none() {
+            let Some(arg) = options.get(i + 1) else {
+                return Err(CommandError::Syntax);
+            };
+            expiry = Some(Expiry::At(parse_expiry(option, arg, "set")?));
+            i += 1;
+        } else {
+            return Err(CommandError::Syntax);
         }
+        i += 1;
+    }
+
+    let existing = ctx.store.get(key);
+    let old_value = match existing.map(|stored| &stored.value) {
+        None => Value::NullString,
+        Some(Data::String(old)) => Value::BulkString(old.clone()),
+        Some(_) if get => return Err(CommandError::WrongType),
+        Some(_) => Value::NullString,
+    };
+    let old_expiry = existing.and_then(|stored| stored.expiry);
+
+    let should_set = match condition {
+        Condition::Always => true,
+        Condition::IfMissing => existing.is_none(),
+        Condition::IfExists => existing.is_some(),
     };
+    if should_set {
+        let expiry = match expiry.unwrap_or(Expiry::Never) {
+            Expiry::Never => None,
+            Expiry::At(deadline) => Some(deadline),
+            Expiry::KeepTtl => old_expiry,
+        };
+        set_string(ctx, key, value.clone(), expiry);
+    }
+
+    match (get, should_set) {
+        (true, _) => Ok(old_value),
+        (false, true) => Ok(Value::SimpleString("OK".to_string())),
+        (false, false) => Ok(Value::NullString),
+    }
+}
+
+pub fn eval_get(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("get".to_string()));
+    };
+    Ok(bulk_or_null(get_string(ctx, args::bulk(key)?)?))
+}
+
+/// APPEND key value
+pub fn eval_append(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, suffix] = params else {
+        return Err(CommandError::WrongArity("append".to_string()));
+    };
+    let (key, suffix) = (args::bulk(key)?, args::bulk(suffix)?);
+
+    let Some(current) = get_string(ctx, key)? else {
+        set_string(ctx, key, suffix.clone(), None);
+        return Ok(Value::Integer(suffix.len() as i64));
+    };
+    if current.len() + suffix.len() > MAX_STRING_LENGTH {
+        return Err(CommandError::StringTooLong);
+    }
+    let mut appended = BytesMut::from(&current[..]);
+    appended.extend_from_slice(suffix);
+    let length = appended.len();
+
+    // Appending keeps whatever TTL the key already had.
+    if let Some(stored) = ctx.store.get_mut(key) {
+        stored.value = Data::String(appended.freeze());
+    }
+    Ok(Value::Integer(length as i64))
+}
+
+/// STRLEN key
+pub fn eval_strlen(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("strlen".to_string()));
+    };
+    let length = get_string(ctx, args::bulk(key)?)?.map_or(0, |value| value.len());
+    Ok(Value::Integer(length as i64))
+}
+
+/// GETRANGE key start end
+pub fn eval_getrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, start, end] = params else {
+        return Err(CommandError::WrongArity("getrange".to_string()));
+    };
+    let (start, end) = (args::parse_i64(start)?, args::parse_i64(end)?);
+    let Some(value) = get_string(ctx, args::bulk(key)?)? else {
+        return Ok(Value::BulkString(Bytes::new()));
+    };
+
+    let length = value.len() as i64;
+    if start < 0 && end < 0 && start > end {
+        return Ok(Value::BulkString(Bytes::new()));
+    }
+    let start = if start < 0 {
+        (length + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 { (length + end).max(0) } else { end }.min(length - 1);
+    if length == 0 || start > end {
+        return Ok(Value::BulkString(Bytes::new()));
+    }
+    Ok(Value::BulkString(
+        value.slice(start as usize..=end as usize),
+    ))
+}
+
+/// SETRANGE key offset value
+pub fn eval_setrange(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, offset, patch] = params else {
+        return Err(CommandError::WrongArity("setrange".to_string()));
+    };
+    let (key, patch) = (args::bulk(key)?, args::bulk(patch)?);
+    let offset = args::parse_i64(offset)?;
+    if offset < 0 {
+        return Err(CommandError::Err("offset is out of range".to_string()));
+    }
+    let offset = offset as usize;
+
+    let current = get_string(ctx, key)?.cloned();
+    if patch.is_empty() {
+        // Nothing to write, so a missing key is not created either.
+        return Ok(Value::Integer(current.map_or(0, |value| value.len()) as i64));
+    }
+    if offset + patch.len() > MAX_STRING_LENGTH {
+        return Err(CommandError::StringTooLong);
+    }
+
+    let mut updated = BytesMut::from(&current.clone().unwrap_or_default()[..]);
+    if updated.len() < offset + patch.len() {
+        updated.resize(offset + patch.len(), 0);
+    }
+    updated[offset..offset + patch.len()].copy_from_slice(patch);
+    let length = updated.len();
+
+    match ctx.store.get_mut(key) {
+        Some(stored) => stored.value = Data::String(updated.freeze()),
+        None => set_string(ctx, key, updated.freeze(), None),
+    }
+    Ok(Value::Integer(length as i64))
+}
+
+fn key_value_pairs<'a>(
+    params: &'a [Value],
+    cmd: &str,
+) -> Result<Vec<(&'a Bytes, &'a Bytes)>, CommandError> {
+    if params.is_empty() || !params.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    }
+    params
+        .chunks(2)
+        .map(|pair| Ok((args::bulk(&pair[0])?, args::bulk(&pair[1])?)))
+        .collect()
+}
 
+/// MSET key value [key value ...]
+pub fn eval_mset(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    for (key, value) in key_value_pairs(params, "mset")? {
+        set_string(ctx, key, value.clone(), None);
+    }
     Ok(Value::SimpleString("OK".to_string()))
 }
 
-pub fn eval_get(params: &[Value], store: MutexGuard<DB>) -> Result<Value, String> {
-    if let Some(Value::BulkString(val)) = params.first()
-        && let Some(stored) = store.db.get(val)
-        && let Some(value) = stored.get()
+/// MSETNX key value [key value ...]
+pub fn eval_msetnx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let pairs = key_value_pairs(params, "msetnx")?;
+    if pairs.iter().any(|(key, _)| ctx.store.get(key).is_some()) {
+        return Ok(Value::Integer(0));
+    }
+    for (key, value) in pairs {
+        set_string(ctx, key, value.clone(), None);
+    }
+    Ok(Value::Integer(1))
+}
+
+/// MGET key [key ...]
+///
+/// Keys holding other types read as missing rather than failing the whole reply.
+pub fn eval_mget(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let values = params
+        .iter()
+        .map(|key| {
+            let key = args::bulk(key)?;
+            Ok(bulk_or_null(get_string(ctx, key).ok().flatten()))
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?;
+    Ok(Value::Array(values))
+}
+
+/// GETSET key value
+pub fn eval_getset(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, value] = params else {
+        return Err(CommandError::WrongArity("getset".to_string()));
+    };
+    let (key, value) = (args::bulk(key)?, args::bulk(value)?);
+
+    let old = bulk_or_null(get_string(ctx, key)?);
+    set_string(ctx, key, value.clone(), None);
+    Ok(old)
+}
+
+/// GETDEL key
+pub fn eval_getdel(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key] = params else {
+        return Err(CommandError::WrongArity("getdel".to_string()));
+    };
+    let key = args::bulk(key)?;
+
+    let old = bulk_or_null(get_string(ctx, key)?);
+    ctx.store.remove(key);
+    Ok(old)
+}
+
+/// GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+///   PXAT unix-time-milliseconds | PERSIST]
+pub fn eval_getex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let Some((key, options)) = params.split_first() else {
+        return Err(CommandError::WrongArity("getex".to_string()));
+    };
+    let key = args::bulk(key)?;
+
+    // `None` leaves the TTL alone, `Some(None)` persists the key.
+    let new_expiry = match options {
+        [] => None,
+        [option] if args::is_option(option, "PERSIST") => Some(None),
+        [option, arg] if is_expiry_option(option) => {
+            Some(Some(parse_expiry(option, arg, "getex")?))
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+
+    let value = bulk_or_null(get_string(ctx, key)?);
+    if let Some(expiry) = new_expiry
+        && let Some(stored) = ctx.store.get_mut(key)
     {
-        Ok(value.clone())
-    } else {
-        Ok(Value::NullString)
+        stored.expiry = expiry;
+    }
+    Ok(value)
+}
+
+/// SETNX key value
+pub fn eval_setnx(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key, value] = params else {
+        return Err(CommandError::WrongArity("setnx".to_string()));
+    };
+    let (key, value) = (args::bulk(key)?, args::bulk(value)?);
+
+    if ctx.store.get(key).is_some() {
+        return Ok(Value::Integer(0));
+    }
+    set_string(ctx, key, value.clone(), None);
+    Ok(Value::Integer(1))
+}
+
+fn set_with_ttl(
+    params: &[Value],
+    ctx: &mut Context,
+    unit: &str,
+    cmd: &str,
+) -> Result<Value, CommandError> {
+    let [key, ttl, value] = params else {
+        return Err(CommandError::WrongArity(cmd.to_string()));
+    };
+    let unit = Value::BulkString(Bytes::copy_from_slice(unit.as_bytes()));
+    let expiry = parse_expiry(&unit, ttl, cmd)?;
+    set_string(
+        ctx,
+        args::bulk(key)?,
+        args::bulk(value)?.clone(),
+        Some(expiry),
+    );
+    Ok(Value::SimpleString("OK".to_string()))
+}
+
+/// SETEX key seconds value
+pub fn eval_setex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    set_with_ttl(params, ctx, "EX", "setex")
+}
+
+/// PSETEX key milliseconds value
+pub fn eval_psetex(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    set_with_ttl(params, ctx, "PX", "psetex")
+}
+
+/// LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
+pub fn eval_lcs(params: &[Value], ctx: &mut Context) -> Result<Value, CommandError> {
+    let [key_a, key_b, options @ ..] = params else {
+        return Err(CommandError::WrongArity("lcs".to_string()));
+    };
+
+    let (mut get_len, mut get_idx, mut with_match_len, mut min_match_len) =
+        (false, false, false, 0);
+    let mut i = 0;
+    while i < options.len() {
+        let option = &options[i];
+        if args::is_option(option, "LEN") {
+            get_len = true;
+        } else if args::is_option(option, "IDX") {
+            get_idx = true;
+        } else if args::is_option(option, "WITHMATCHLEN") {
+            with_match_len = true;
+        } else if args::is_option(option, "MINMATCHLEN") && i + 1 < options.len() {
+            min_match_len = args::parse_i64(&options[i + 1])?.max(0) as usize;
+            i += 1;
+        } else {
+            return Err(CommandError::Syntax);
+        }
+        i += 1;
+    }
+    if get_len && get_idx {
+        return Err(CommandError::Err(
+            "If you want both the length and indexes, please just use IDX.".to_string(),
+        ));
+    }
+
+    let not_strings =
+        || CommandError::Err("The specified keys must contain string values".to_string());
+    let a = get_string(ctx, args::bulk(key_a)?)
+        .map_err(|_| not_strings())?
+        .cloned()
+        .unwrap_or_default();
+    let b = get_string(ctx, args::bulk(key_b)?)
+        .map_err(|_| not_strings())?
+        .cloned()
+        .unwrap_or_default();
+
+    let lcs = Lcs::new(&a, &b)?;
+    if get_len {
+        return Ok(Value::Integer(lcs.len() as i64));
+    }
+    if !get_idx {
+        return Ok(Value::BulkString(lcs.string()));
+    }
+
+    let matches = lcs
+        .ranges()
+        .into_iter()
+        .filter(|range| range.len() >= min_match_len)
+        .map(|range| {
+            let mut entry = vec![
+                Value::Array(vec![
+                    Value::Integer(range.a_start as i64),
+                    Value::Integer(range.a_end as i64),
+                ]),
+                Value::Array(vec![
+                    Value::Integer(range.b_start as i64),
+                    Value::Integer(range.b_end as i64),
+                ]),
+            ];
+            if with_match_len {
+                entry.push(Value::Integer(range.len() as i64));
+            }
+            Value::Array(entry)
+        })
+        .collect();
+    let field = |name: &'static str| Value::BulkString(Bytes::from_static(name.as_bytes()));
+    Ok(Value::Map(vec![
+        (field("matches"), Value::Array(matches)),
+        (field("len"), Value::Integer(lcs.len() as i64)),
+    ]))
+}
+
+/// Dynamic-programming table for the longest common subsequence of two strings.
+struct Lcs<'a> {
+    a: &'a [u8],
+    b: &'a [u8],
+    table: Vec<u32>,
+}
+
+/// A run of the LCS that is contiguous in both strings, as inclusive offsets.
+struct MatchRange {
+    a_start: usize,
+    a_end: usize,
+    b_start: usize,
+    b_end: usize,
+}
+
+impl MatchRange {
+    fn len(&self) -> usize {
+        self.a_end - self.a_start + 1
+    }
+}
+
+impl<'a> Lcs<'a> {
+    /// Fills in the table, refusing like Redis when it would outgrow
+    /// proto-max-bulk-len, since it is built while the store is locked.
+    fn new(a: &'a [u8], b: &'a [u8]) -> Result<Self, CommandError> {
+        let cells = (a.len() + 1)
+            .checked_mul(b.len() + 1)
+            .filter(|cells| cells.saturating_mul(size_of::<u32>()) <= MAX_STRING_LENGTH)
+            .ok_or_else(|| {
+                CommandError::Err(
+                    "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                        .to_string(),
+                )
+            })?;
+        let mut lcs = Lcs {
+            a,
+            b,
+            table: vec![0; cells],
+        };
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let value = if a[i - 1] == b[j - 1] {
+                    lcs.at(i - 1, j - 1) + 1
+                } else {
+                    lcs.at(i - 1, j).max(lcs.at(i, j - 1))
+                };
+                let width = b.len() + 1;
+                lcs.table[i * width + j] = value;
+            }
+        }
+        Ok(lcs)
+    }
+
+    fn at(&self, i: usize, j: usize) -> u32 {
+        self.table[i * (self.b.len() + 1) + j]
+    }
+
+    fn len(&self) -> usize {
+        self.at(self.a.len(), self.b.len()) as usize
+    }
+
+    /// Walks the table back from the end, collecting the subsequence itself.
+    fn string(&self) -> Bytes {
+        let mut result = vec![0; self.len()];
+        let (mut i, mut j, mut idx) = (self.a.len(), self.b.len(), self.len());
+        while i > 0 && j > 0 {
+            if self.a[i - 1] == self.b[j - 1] {
+                result[idx - 1] = self.a[i - 1];
+                idx -= 1;
+                i -= 1;
+                j -= 1;
+            } else if self.at(i - 1, j) > self.at(i, j - 1) {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+        }
+        Bytes::from(result)
+    }
+
+    /// The contiguous runs of the subsequence, last run first, as Redis reports them.
+    fn ranges(&self) -> Vec<MatchRange> {
+        let mut ranges = Vec::new();
+        let mut current: Option<MatchRange> = None;
+        let (mut i, mut j) = (self.a.len(), self.b.len());
+
+        while i > 0 && j > 0 {
+            let mut emit = false;
+            if self.a[i - 1] == self.b[j - 1] {
+                match &mut current {
+                    None => {
+                        current = Some(MatchRange {
+                            a_start: i - 1,
+                            a_end: i - 1,
+                            b_start: j - 1,
+                            b_end: j - 1,
+                        })
+                    }
+                    Some(range) if range.a_start == i && range.b_start == j => {
+                        range.a_start -= 1;
+                        range.b_start -= 1;
+                    }
+                    Some(_) => emit = true,
+                }
+                if current
+                    .as_ref()
+                    .is_some_and(|range| range.a_start == 0 || range.b_start == 0)
+                {
+                    emit = true;
+                }
+                i -= 1;
+                j -= 1;
+            } else {
+                if self.at(i - 1, j) > self.at(i, j - 1) {
+                    i -= 1;
+                } else {
+                    j -= 1;
+                }
+                emit = current.is_some();
+            }
+
+            if emit && let Some(range) = current.take() {
+                ranges.push(range);
+            }
+        }
+        ranges
     }
 }
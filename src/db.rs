@@ -1,20 +1,98 @@
+use crate::blocking::Blocking;
 use crate::config::Config;
-use crate::resp::Value;
-use std::collections::HashMap;
+use crate::dict::Dict;
+use crate::hash::Hash;
+use crate::quicklist::QuickList;
+use crate::set::Set;
+use crate::sortedset::SortedSet;
+use crate::stream::Stream;
+use bytes::Bytes;
+use std::collections::HashSet;
 use std::fs;
 use std::iter::Peekable;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub type Redis = Arc<Mutex<DB>>;
 
 pub struct DB {
     pub config: Config,
-    pub db: HashMap<String, StoredValue>,
+    pub db: Dict<Bytes, StoredValue>,
+    pub blocking: Blocking,
+    /// Keys that may hold hashes with expiring fields, for the active expiry cycle.
+    pub volatile_hashes: HashSet<Bytes>,
+}
+
+/// What a key holds, in the representation its commands work on rather than
+/// the wire format.
+#[derive(Debug, Clone)]
+pub enum Data {
+    String(Bytes),
+    List(QuickList),
+    Hash(Hash),
+    Set(Set),
+    SortedSet(SortedSet),
+    Stream(Stream),
+}
+
+impl Data {
+    /// What TYPE reports for this kind of value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Data::String(_) => "string",
+            Data::List(_) => "list",
+            Data::Hash(_) => "hash",
+            Data::Set(_) => "set",
+            Data::SortedSet(_) => "zset",
+            Data::Stream(_) => "stream",
+        }
+    }
+
+    /// Roughly how much work dropping this value is: one unit per element.
+    fn free_effort(&self) -> usize {
+        match self {
+            Data::String(_) => 1,
+            Data::List(list) => list.len(),
+            Data::Hash(hash) => hash.len(),
+            Data::Set(set) => set.len(),
+            Data::SortedSet(zset) => zset.len(),
+            Data::Stream(stream) => stream.len(),
+        }
+    }
+}
+
+/// Every name `Data::type_name` returns.
+pub const TYPE_NAMES: &[&str] = &["string", "list", "hash", "set", "zset", "stream"];
+
+/// Values taking more effort than this to drop are dropped on another thread
+/// when UNLINK removes them, like Redis's lazyfree.
+const LAZYFREE_THRESHOLD: usize = 64;
+
+/// Drops `stored` in the background if it is big enough to be worth it.
+pub fn free_lazily(stored: StoredValue) {
+    if stored.value.free_effort() > LAZYFREE_THRESHOLD {
+        drop_in_background(Box::new(stored));
+    }
+}
+
+/// Hands `garbage` to the one thread that frees values for UNLINK and
+/// FLUSHDB ASYNC, like Redis's lazyfree thread, so freeing never costs a
+/// thread per call.
+fn drop_in_background(garbage: Box<dyn Send>) {
+    static LAZYFREE: OnceLock<mpsc::Sender<Box<dyn Send>>> = OnceLock::new();
+    let sender = LAZYFREE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Box<dyn Send>>();
+        thread::spawn(move || receiver.into_iter().for_each(drop));
+        sender
+    });
+    // Should the thread be gone, the value comes back in the error and is
+    // dropped here instead.
+    let _ = sender.send(garbage);
 }
 
 pub struct StoredValue {
-    pub value: Value,
+    pub value: Data,
     pub expiry: Option<SystemTime>,
 }
 
@@ -26,7 +104,7 @@ impl StoredValue {
         }
     }
 
-    pub fn get(&self) -> Option<&Value> {
+    pub fn get(&self) -> Option<&Data> {
         if self.is_expired() {
             None
         } else {
@@ -39,17 +117,73 @@ impl DB {
     pub fn new(args: Vec<String>) -> Self {
         DB {
             config: Config::new(args),
-            db: HashMap::new(),
+            db: Dict::new(),
+            blocking: Blocking::default(),
+            volatile_hashes: HashSet::new(),
+        }
+    }
+
+    /// Drops `key` if it has logically expired, so callers only ever see live entries.
+    fn expire_if_needed(&mut self, key: &[u8]) {
+        if self.db.get(key).is_some_and(StoredValue::is_expired) {
+            self.db.remove(key);
         }
     }
 
-    // pub fn get(&mut self, key: &str) -> Option<&mut Value> {
-    //     if let Some(stored) = self.db.get_mut(key) {
-    //         stored.get()
-    //     } else {
-    //         None
-    //     }
-    // }
+    pub fn get(&mut self, key: &[u8]) -> Option<&StoredValue> {
+        self.expire_if_needed(key);
+        self.db.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut StoredValue> {
+        self.expire_if_needed(key);
+        self.db.get_mut(key)
+    }
+
+    pub fn insert(&mut self, key: Bytes, value: StoredValue) -> Option<StoredValue> {
+        self.db.insert(key, value).filter(|old| !old.is_expired())
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<StoredValue> {
+        self.db.remove(key).filter(|old| !old.is_expired())
+    }
+
+    /// Empties the keyspace for FLUSHDB and FLUSHALL, dropping the old
+    /// contents on another thread if `lazy`. Blocked clients are retried,
+    /// since whatever they waited on is gone.
+    pub fn flush(&mut self, lazy: bool) {
+        let old = std::mem::take(&mut self.db);
+        self.volatile_hashes.clear();
+        self.blocking.signal_all();
+        if lazy {
+            drop_in_background(Box::new(old));
+        }
+    }
+
+    /// Background counterpart of the lazy checks: drops hash fields whose TTL
+    /// has passed, and hashes left empty by that, even if nobody reads them.
+    pub fn active_expire_cycle(&mut self) {
+        let now = SystemTime::now();
+        let keys = self.volatile_hashes.iter().cloned().collect::<Vec<_>>();
+        for key in keys {
+            let Some(StoredValue {
+                value: Data::Hash(hash),
+                ..
+            }) = self.get_mut(&key)
+            else {
+                self.volatile_hashes.remove(&key);
+                continue;
+            };
+            hash.purge_expired(now);
+            let (empty, volatile) = (hash.is_empty(), hash.has_expiring_fields());
+            if empty {
+                self.db.remove(&key);
+            }
+            if empty || !volatile {
+                self.volatile_hashes.remove(&key);
+            }
+        }
+    }
 
     fn load_rdb(&self) -> Vec<u8> {
         let path = self.config.rbd();
@@ -105,12 +239,12 @@ impl DB {
             let key = Self::get_string(&mut byte_cursor);
             let value = Self::get_string(&mut byte_cursor);
 
-            println!("inserting {key}:{value} {expiry:?} type={value_type:#04X?}");
+            println!("inserting {key:?}:{value:?} {expiry:?} type={value_type:#04X?}");
 
             self.db.insert(
                 key,
                 StoredValue {
-                    value: Value::SimpleString(value),
+                    value: Data::String(value),
                     expiry,
                 },
             );
@@ -162,10 +296,9 @@ impl DB {
         }
     }
 
-    fn get_string<I: Iterator<Item = u8>>(iter: &mut I) -> String {
+    fn get_string<I: Iterator<Item = u8>>(iter: &mut I) -> Bytes {
         let size = Self::get_length_encoding(iter);
-        let bytes: Vec<u8> = (0..size).map(|_| iter.next().unwrap()).collect();
-        String::from_utf8(bytes).unwrap()
+        (0..size).map(|_| iter.next().unwrap()).collect()
     }
 
     fn extract_u16<I: Iterator<Item = u8>>(iter: &mut I) -> u16 {
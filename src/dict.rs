@@ -0,0 +1,408 @@
+//! A chained hash table that resizes by rehashing a bucket at a time, like
+//! Redis's dict, so SCAN cursors stay meaningful across resizes.
+
+use rand::Rng;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// Buckets a table starts with, and the fewest it shrinks to.
+const MIN_SIZE: usize = 4;
+/// Shrink once fewer than one in this many buckets would hold an entry.
+const MIN_FILL: usize = 10;
+/// Empty buckets one rehash step looks at before leaving the rest for later.
+const MAX_EMPTY_VISITS: usize = 10;
+
+#[derive(Clone)]
+struct Entry<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+type Table<K, V> = Vec<Vec<Entry<K, V>>>;
+
+fn table<K, V>(size: usize) -> Table<K, V> {
+    (0..size).map(|_| Vec::new()).collect()
+}
+
+#[derive(Clone)]
+pub struct Dict<K, V> {
+    /// Entries live in `tables[0]`, except while resizing, when they move
+    /// bucket by bucket into `tables[1]`, which then takes its place.
+    tables: [Table<K, V>; 2],
+    /// The next bucket of `tables[0]` to move, while resizing.
+    rehash_index: Option<usize>,
+    len: usize,
+    hasher: RandomState,
+}
+
+impl<K, V> Default for Dict<K, V> {
+    fn default() -> Self {
+        Dict {
+            tables: [Vec::new(), Vec::new()],
+            rehash_index: None,
+            len: 0,
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Dict<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Dict<K, V> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.tables = [Vec::new(), Vec::new()];
+        self.rehash_index = None;
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.tables
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Visits the entries under `cursor` and returns the cursor to pass next,
+    /// 0 once everything has been covered. Start from 0.
+    ///
+    /// The cursor counts buckets with its bits reversed, so when the table
+    /// doubles or halves, the buckets already visited map onto buckets the
+    /// new cursor won't go back to. An entry present for the whole scan is
+    /// visited at least once; some may be visited twice.
+    pub fn scan(&self, cursor: u64, mut visit: impl FnMut(&K, &V)) -> u64 {
+        if self.len == 0 {
+            return 0;
+        }
+        let mut visit_bucket = |bucket: &Vec<Entry<K, V>>| {
+            for entry in bucket {
+                visit(&entry.key, &entry.value);
+            }
+        };
+        let next = |cursor: u64, mask: u64| {
+            (cursor | !mask)
+                .reverse_bits()
+                .wrapping_add(1)
+                .reverse_bits()
+        };
+
+        let mut cursor = cursor;
+        if self.rehash_index.is_none() {
+            let mask = self.tables[0].len() as u64 - 1;
+            visit_bucket(&self.tables[0][(cursor & mask) as usize]);
+            return next(cursor, mask);
+        }
+
+        // Visit the bucket in the smaller table, then every bucket of the
+        // larger one it expands to.
+        let (small, large) = if self.tables[0].len() <= self.tables[1].len() {
+            (&self.tables[0], &self.tables[1])
+        } else {
+            (&self.tables[1], &self.tables[0])
+        };
+        let (small_mask, large_mask) = (small.len() as u64 - 1, large.len() as u64 - 1);
+        visit_bucket(&small[(cursor & small_mask) as usize]);
+        loop {
+            visit_bucket(&large[(cursor & large_mask) as usize]);
+            cursor = next(cursor, large_mask);
+            if cursor & (small_mask ^ large_mask) == 0 {
+                return cursor;
+            }
+        }
+    }
+
+    /// A random entry, `None` if there are none. Picks a random non-empty
+    /// bucket and then an entry in it, so entries sharing a bucket are a
+    /// little less likely to come up, as in Redis.
+    pub fn random(&self, rng: &mut impl Rng) -> Option<(&K, &V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let [first, second] = &self.tables;
+        loop {
+            let at = rng.gen_range(0..first.len() + second.len());
+            let bucket = match at.checked_sub(first.len()) {
+                None => &first[at],
+                Some(at) => &second[at],
+            };
+            if !bucket.is_empty() {
+                let entry = &bucket[rng.gen_range(0..bucket.len())];
+                return Some((&entry.key, &entry.value));
+            }
+        }
+    }
+
+    /// Moves one bucket of a resize along, if one is under way.
+    fn rehash_step(&mut self) {
+        let Some(mut index) = self.rehash_index else {
+            return;
+        };
+        let [old, new] = &mut self.tables;
+        let mut empty_visits = 0;
+        while index < old.len() && old[index].is_empty() {
+            index += 1;
+            empty_visits += 1;
+            if empty_visits == MAX_EMPTY_VISITS {
+                self.rehash_index = Some(index);
+                return;
+            }
+        }
+        if index < old.len() {
+            let mask = new.len() - 1;
+            for entry in mem::take(&mut old[index]) {
+                new[entry.hash as usize & mask].push(entry);
+            }
+            index += 1;
+        }
+        if index < old.len() {
+            self.rehash_index = Some(index);
+        } else {
+            self.tables[0] = mem::take(&mut self.tables[1]);
+            self.rehash_index = None;
+        }
+    }
+
+    /// Starts moving the entries into a table of `size` buckets.
+    fn resize(&mut self, size: usize) {
+        if self.rehash_index.is_some() || size == self.tables[0].len() {
+            return;
+        }
+        self.tables[1] = table(size);
+        self.rehash_index = Some(0);
+    }
+}
+
+impl<K: Hash + Eq, V> Dict<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Where `key` is: its table, bucket and position in the bucket.
+    fn locate<Q>(&self, key: &Q) -> Option<(usize, usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher.hash_one(key);
+        let tables = if self.rehash_index.is_some() { 2 } else { 1 };
+        (0..tables).find_map(|t| {
+            let table = &self.tables[t];
+            if table.is_empty() {
+                return None;
+            }
+            let bucket = hash as usize & (table.len() - 1);
+            let at = table[bucket]
+                .iter()
+                .position(|entry| entry.hash == hash && entry.key.borrow() == key)?;
+            Some((t, bucket, at))
+        })
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (t, bucket, at) = self.locate(key)?;
+        Some(&self.tables[t][bucket][at].value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.rehash_step();
+        let (t, bucket, at) = self.locate(key)?;
+        Some(&mut self.tables[t][bucket][at].value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.locate(key).is_some()
+    }
+
+    /// Inserts or replaces `key`, returning the value it had.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.rehash_step();
+        if let Some((t, bucket, at)) = self.locate(&key) {
+            return Some(mem::replace(&mut self.tables[t][bucket][at].value, value));
+        }
+
+        if self.tables[0].is_empty() {
+            self.tables[0] = table(MIN_SIZE);
+        } else if self.len >= self.tables[0].len() {
+            self.resize((self.len + 1).next_power_of_two());
+        }
+        // New entries go straight to the table being filled.
+        let table = &mut self.tables[self.rehash_index.map_or(0, |_| 1)];
+        let hash = self.hasher.hash_one(&key);
+        let bucket = hash as usize & (table.len() - 1);
+        table[bucket].push(Entry { hash, key, value });
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.rehash_step();
+        let (t, bucket, at) = self.locate(key)?;
+        let entry = self.tables[t][bucket].swap_remove(at);
+        self.len -= 1;
+
+        let size = self.tables[0].len();
+        if size > MIN_SIZE && self.len * MIN_FILL < size {
+            self.resize(self.len.max(MIN_SIZE).next_power_of_two());
+        }
+        Some(entry.value)
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for Dict<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut dict = Dict::new();
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Scans `dict` to the end, calling `between` after every call.
+    fn scan_all(dict: &mut Dict<u32, ()>, mut between: impl FnMut(&mut Dict<u32, ()>)) -> Vec<u32> {
+        let (mut seen, mut cursor) = (vec![], 0);
+        loop {
+            cursor = dict.scan(cursor, |key, _| seen.push(*key));
+            if cursor == 0 {
+                return seen;
+            }
+            between(dict);
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut dict = Dict::new();
+        for n in 0..1000 {
+            assert_eq!(dict.insert(n, n * 2), None);
+        }
+        assert_eq!(dict.insert(7, 0), Some(14));
+        assert_eq!(dict.len(), 1000);
+        assert_eq!(dict.get(&7), Some(&0));
+        *dict.get_mut(&8).unwrap() += 1;
+        assert_eq!(dict.get(&8), Some(&17));
+        for n in 0..990 {
+            assert!(dict.remove(&n).is_some());
+        }
+        assert_eq!(dict.remove(&0), None);
+        assert_eq!(dict.len(), 10);
+        assert_eq!(dict.iter().count(), 10);
+        assert!(dict.contains_key(&995));
+    }
+
+    #[test]
+    fn test_tables_grow_and_shrink() {
+        let finish_resize = |dict: &mut Dict<u32, ()>| {
+            while dict.rehash_index.is_some() {
+                dict.rehash_step();
+            }
+        };
+        let mut dict = Dict::new();
+        for n in 0..1000 {
+            dict.insert(n, ());
+        }
+        finish_resize(&mut dict);
+        assert_eq!(dict.tables[0].len(), 1024);
+        for n in 0..995 {
+            dict.remove(&n);
+        }
+        finish_resize(&mut dict);
+        assert!(dict.tables[0].len() < 1024);
+        assert_eq!(dict.keys().copied().collect::<HashSet<_>>().len(), 5);
+    }
+
+    #[test]
+    fn test_random_entries() {
+        let mut rng = rand::thread_rng();
+        let mut dict: Dict<u32, ()> = Dict::new();
+        assert!(dict.random(&mut rng).is_none());
+        for n in 0..20 {
+            dict.insert(n, ());
+        }
+        let seen: HashSet<u32> = (0..1000)
+            .map(|_| *dict.random(&mut rng).expect("not empty").0)
+            .collect();
+        assert_eq!(seen.len(), 20);
+    }
+
+    #[test]
+    fn test_scan_visits_everything_once_when_nothing_changes() {
+        let mut dict: Dict<u32, ()> = (0..500).map(|n| (n, ())).collect();
+        let mut seen = scan_all(&mut dict, |_| {});
+        seen.sort();
+        assert_eq!(seen, (0..500).collect::<Vec<_>>());
+        assert_eq!(Dict::<u32, ()>::new().scan(0, |_, _| {}), 0);
+    }
+
+    #[test]
+    fn test_scan_survives_resizes() {
+        let mut dict: Dict<u32, ()> = (0..100).map(|n| (n, ())).collect();
+        let mut next = 100;
+        let seen: HashSet<u32> = scan_all(&mut dict, |dict| {
+            for _ in 0..2 {
+                dict.insert(next, ());
+                next += 1;
+            }
+        })
+        .into_iter()
+        .collect();
+        assert!((0..100).all(|n| seen.contains(&n)));
+        assert!(dict.len() > 200);
+
+        let mut dict: Dict<u32, ()> = (0..1000).map(|n| (n, ())).collect();
+        let mut doomed = 100..1000;
+        let seen: HashSet<u32> = scan_all(&mut dict, |dict| {
+            for n in doomed.by_ref().take(50) {
+                dict.remove(&n);
+            }
+        })
+        .into_iter()
+        .collect();
+        assert!((0..100).all(|n| seen.contains(&n)));
+    }
+}
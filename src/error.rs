@@ -0,0 +1,52 @@
+use crate::resp::{ProtocolError, Value};
+use thiserror::Error;
+
+/// Everything a command can fail with. The `Display` text is the exact error
+/// line sent to the client, prefix included.
+#[derive(Debug, Error, PartialEq)]
+pub enum CommandError {
+    #[error("ERR {0}")]
+    Err(String),
+    #[error("ERR syntax error")]
+    Syntax,
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("ERR unknown command '{0}', with args beginning with: {1}")]
+    UnknownCommand(String, String),
+    #[error("ERR unknown subcommand '{0}'. Try {1} HELP.")]
+    UnknownSubcommand(String, String),
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    OutOfRange,
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("NOGROUP {0}")]
+    NoGroup(String),
+    #[error("BUSYGROUP Consumer Group name already exists")]
+    BusyGroup,
+    #[error("NOAUTH Authentication required.")]
+    NoAuth,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("NOPROTO unsupported protocol version")]
+    NoProto,
+    #[error("READONLY You can't write against a read only replica.")]
+    ReadOnly,
+    #[error("ERR {0}")]
+    Protocol(#[from] ProtocolError),
+}
+
+impl From<CommandError> for Value {
+    fn from(err: CommandError) -> Self {
+        Value::Error(err.to_string())
+    }
+}
@@ -0,0 +1,125 @@
+//! Redis-style glob patterns, as KEYS, SCAN MATCH and friends take them.
+
+/// Whether `string` matches `pattern`, where `*` matches any run of bytes, `?`
+/// any single byte, `[abc]` and `[a-z]` a byte from a set, `[^...]` a byte
+/// outside it, and `\` makes the next byte literal.
+pub fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // Just past the last `*`, and the first byte it hasn't swallowed yet.
+    // Every other element matches one byte, so on a mismatch it's enough to
+    // let that star take one more.
+    let mut star = None;
+    while s < string.len() {
+        if pattern.get(p) == Some(&b'*') {
+            p += 1;
+            star = Some((p, s));
+            continue;
+        }
+        if let Some(next) = match_one(pattern, p, string[s]) {
+            p = next;
+            s += 1;
+            continue;
+        }
+        let Some((after_star, swallowed)) = star else {
+            return false;
+        };
+        p = after_star;
+        s = swallowed + 1;
+        star = Some((after_star, s));
+    }
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+/// Matches `byte` against the element at `pattern[p]`, returning where the
+/// next element starts.
+fn match_one(pattern: &[u8], p: usize, byte: u8) -> Option<usize> {
+    match *pattern.get(p)? {
+        b'?' => Some(p + 1),
+        b'[' => match_class(pattern, p + 1, byte),
+        b'\\' if p + 1 < pattern.len() => (pattern[p + 1] == byte).then_some(p + 2),
+        literal => (literal == byte).then_some(p + 1),
+    }
+}
+
+/// Matches `byte` against the class whose body starts at `pattern[p]`. Like
+/// Redis, an unterminated class runs to the end of the pattern and reversed
+/// ranges count as if written the right way round.
+fn match_class(pattern: &[u8], mut p: usize, byte: u8) -> Option<usize> {
+    let negated = pattern.get(p) == Some(&b'^');
+    if negated {
+        p += 1;
+    }
+    let mut matched = false;
+    loop {
+        match pattern.get(p) {
+            None => break,
+            Some(b']') => {
+                p += 1;
+                break;
+            }
+            Some(b'\\') if p + 1 < pattern.len() => {
+                matched |= pattern[p + 1] == byte;
+                p += 2;
+            }
+            Some(&start) if pattern.get(p + 1) == Some(&b'-') && p + 2 < pattern.len() => {
+                let end = pattern[p + 2];
+                matched |= (start.min(end)..=start.max(end)).contains(&byte);
+                p += 3;
+            }
+            Some(&member) => {
+                matched |= member == byte;
+                p += 1;
+            }
+        }
+    }
+    (matched != negated).then_some(p)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn glob(pattern: &str, string: &str) -> bool {
+        matches(pattern.as_bytes(), string.as_bytes())
+    }
+
+    #[test]
+    fn test_stars_and_question_marks() {
+        assert!(glob("*", ""));
+        assert!(glob("*", "anything"));
+        assert!(glob("h*llo", "hllo"));
+        assert!(glob("h*llo", "heeeello"));
+        assert!(glob("h?llo", "hallo"));
+        assert!(!glob("h?llo", "hllo"));
+        assert!(glob("*a*b*", "xxaxxbxx"));
+        assert!(!glob("*a*b", "xxbxxa"));
+        assert!(glob("user:*:name", "user:42:name"));
+        assert!(!glob("user:*:name", "user:42:email"));
+        assert!(!glob("", "a"));
+        // Backtracking doesn't blow up on many stars.
+        assert!(!glob("a*a*a*a*a*a*a*a*a*b", &"a".repeat(64)));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(glob("h[ae]llo", "hello"));
+        assert!(!glob("h[ae]llo", "hillo"));
+        assert!(glob("h[^e]llo", "hallo"));
+        assert!(!glob("h[^e]llo", "hello"));
+        assert!(glob("h[a-b]llo", "hbllo"));
+        assert!(glob("h[b-a]llo", "hallo"));
+        assert!(!glob("h[a-b]llo", "hcllo"));
+        assert!(glob("[\\]]", "]"));
+        assert!(glob("a[bc", "ab"));
+        assert!(!glob("a[]", "a"));
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert!(glob("\\*", "*"));
+        assert!(!glob("\\*", "a"));
+        assert!(glob("a\\?", "a?"));
+        assert!(!glob("a\\?", "ab"));
+        assert!(glob("a\\", "a\\"));
+    }
+}
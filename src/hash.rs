@@ -0,0 +1,169 @@
+use crate::dict::Dict;
+use bytes::Bytes;
+use rand::Rng;
+use std::collections::BTreeSet;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone)]
+struct Field {
+    value: Bytes,
+    expiry: Option<SystemTime>,
+}
+
+/// A hash whose fields may each carry their own expiry, as with HEXPIRE.
+/// Expiring fields are also indexed by deadline so purging them doesn't have
+/// to look at the rest.
+#[derive(Debug, Clone, Default)]
+pub struct Hash {
+    fields: Dict<Bytes, Field>,
+    expiries: BTreeSet<(SystemTime, Bytes)>,
+}
+
+impl Hash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn contains_key(&self, field: &[u8]) -> bool {
+        self.fields.contains_key(field)
+    }
+
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        self.fields.get(field).map(|field| &field.value)
+    }
+
+    /// Sets `field` to `value`, dropping any TTL it had, like HSET does.
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> Option<Bytes> {
+        self.set_expiry(&field, None);
+        self.fields
+            .insert(
+                field,
+                Field {
+                    value,
+                    expiry: None,
+                },
+            )
+            .map(|old| old.value)
+    }
+
+    /// Changes the value of `field` in place, keeping its TTL, like HINCRBY does.
+    pub fn update(&mut self, field: &Bytes, value: Bytes) {
+        match self.fields.get_mut(field) {
+            Some(existing) => existing.value = value,
+            None => {
+                self.insert(field.clone(), value);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, field: &[u8]) -> Option<Bytes> {
+        let removed = self.fields.remove(field)?;
+        if let Some(expiry) = removed.expiry {
+            self.expiries
+                .remove(&(expiry, Bytes::copy_from_slice(field)));
+        }
+        Some(removed.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        self.fields.iter().map(|(name, field)| (name, &field.value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Bytes> {
+        self.fields.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Bytes> {
+        self.fields.values().map(|field| &field.value)
+    }
+
+    /// A random field and its value, `None` if the hash is empty.
+    pub fn random(&self, rng: &mut impl Rng) -> Option<(&Bytes, &Bytes)> {
+        self.fields
+            .random(rng)
+            .map(|(name, field)| (name, &field.value))
+    }
+
+    /// Visits the fields under `cursor`, as `Dict::scan` does.
+    pub fn scan(&self, cursor: u64, mut visit: impl FnMut(&Bytes, &Bytes)) -> u64 {
+        self.fields
+            .scan(cursor, |name, field| visit(name, &field.value))
+    }
+
+    /// `None` if the field is missing, `Some(None)` if it never expires.
+    pub fn expiry(&self, field: &[u8]) -> Option<Option<SystemTime>> {
+        self.fields.get(field).map(|field| field.expiry)
+    }
+
+    /// Sets or clears the expiry of an existing field; false if it is missing.
+    pub fn set_expiry(&mut self, field: &[u8], expiry: Option<SystemTime>) -> bool {
+        let Some(existing) = self.fields.get_mut(field) else {
+            return false;
+        };
+        let name = Bytes::copy_from_slice(field);
+        if let Some(old) = existing.expiry {
+            self.expiries.remove(&(old, name.clone()));
+        }
+        if let Some(new) = expiry {
+            self.expiries.insert((new, name));
+        }
+        existing.expiry = expiry;
+        true
+    }
+
+    pub fn has_expiring_fields(&self) -> bool {
+        !self.expiries.is_empty()
+    }
+
+    /// Drops every field whose deadline is at or before `now`, returning how many.
+    pub fn purge_expired(&mut self, now: SystemTime) -> usize {
+        let mut purged = 0;
+        while let Some((deadline, _)) = self.expiries.first()
+            && *deadline <= now
+        {
+            let (_, field) = self.expiries.pop_first().expect("checked above");
+            self.fields.remove(&field);
+            purged += 1;
+        }
+        purged
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_field_expiry() {
+        let now = SystemTime::now();
+        let mut hash = Hash::new();
+        hash.insert(Bytes::from("a"), Bytes::from("1"));
+        hash.insert(Bytes::from("b"), Bytes::from("2"));
+        hash.insert(Bytes::from("c"), Bytes::from("3"));
+
+        assert!(hash.set_expiry(b"a", Some(now)));
+        assert!(hash.set_expiry(b"b", Some(now + Duration::from_secs(60))));
+        assert!(!hash.set_expiry(b"missing", Some(now)));
+        assert_eq!(hash.expiry(b"c"), Some(None));
+
+        assert_eq!(hash.purge_expired(now), 1);
+        assert!(!hash.contains_key(b"a"));
+        assert_eq!(hash.len(), 2);
+
+        // Updating in place keeps the TTL, overwriting clears it.
+        hash.update(&Bytes::from("b"), Bytes::from("20"));
+        assert_eq!(hash.expiry(b"b"), Some(Some(now + Duration::from_secs(60))));
+        hash.insert(Bytes::from("b"), Bytes::from("200"));
+        assert_eq!(hash.expiry(b"b"), Some(None));
+        assert!(!hash.has_expiring_fields());
+    }
+}
@@ -1,16 +1,34 @@
 #![allow(dead_code)]
+mod blocking;
+mod client;
 mod commands;
 mod config;
 mod db;
+mod dict;
+mod error;
+mod glob;
+mod hash;
+mod quicklist;
 mod resp;
+mod set;
+mod sortedset;
+mod stream;
 
+use crate::blocking::Ticket;
+use crate::client::Client;
+use crate::commands::Context;
 use crate::db::{DB, Redis};
-use crate::resp::Value;
+use crate::error::CommandError;
+use crate::resp::{Decoder, Value};
+use bytes::{Bytes, BytesMut};
 use std::error::Error;
-use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
+
+/// How often expired hash fields are swept without waiting for a read.
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
 
 pub async fn run_server(port: u16, args: Vec<String>) -> Result<(), Box<dyn Error>> {
     let listener = TcpListener::bind(format!("127.0.0.1:{port}")).await?;
@@ -18,35 +36,71 @@ pub async fn run_server(port: u16, args: Vec<String>) -> Result<(), Box<dyn Erro
     let db = DB::new(args).parse_rdb();
     let redis: Redis = Arc::new(Mutex::new(db));
 
+    let cron = redis.clone();
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+        loop {
+            ticks.tick().await;
+            cron.lock().unwrap().active_expire_cycle();
+        }
+    });
+
     loop {
         let (mut socket, _) = listener.accept().await?;
         let redis = redis.clone();
 
         tokio::spawn(async move {
-            let mut read_buffer = [0; 512];
+            let mut decoder = Decoder::new();
+            let mut client = Client::new();
 
             loop {
-                let read_count = match socket.read(&mut read_buffer).await {
+                match socket.read_buf(decoder.buffer_mut()).await {
                     Ok(0) => return,
-                    Ok(n) => n,
+                    Ok(_) => {}
                     Err(e) => {
                         eprintln!("failed to read from socket; err = {e:?}");
                         return;
                     }
                 };
-                let Ok(command) = str::from_utf8(&read_buffer[..read_count]) else {
-                    eprintln!("not utf8 buffer");
-                    return;
-                };
 
-                let response = match process(command, &redis).await {
-                    Ok(response) => response,
-                    Err(err) => {
-                        eprintln!("failed to process command {err}");
-                        return;
+                // Answer every complete command in the buffer, in order, with one write.
+                let mut response = BytesMut::new();
+                loop {
+                    match decoder.next_frame() {
+                        Ok(Some(command)) => match process(&command, &mut client, &redis) {
+                            Outcome::Reply(reply) => response.extend_from_slice(&reply),
+                            Outcome::Blocked(ticket) => {
+                                // Flush what came before, the wait may be long.
+                                if !response.is_empty() {
+                                    if let Err(e) = socket.write_all(&response).await {
+                                        eprintln!("failed to write to socket; err = {e}");
+                                        return;
+                                    }
+                                    response.clear();
+                                }
+                                let closed = closed(&mut socket, decoder.buffer_mut());
+                                match ticket.wait(&redis, closed).await {
+                                    Some(reply) => response.extend_from_slice(&reply),
+                                    None => return,
+                                }
+                            }
+                        },
+                        Ok(None) => break,
+                        Err(err) => {
+                            // The stream can't be resynchronised, so drop whatever is
+                            // buffered and keep the connection for the next command.
+                            eprintln!("failed to parse command {err}");
+                            let reply = Value::from(CommandError::from(err));
+                            response.extend_from_slice(&reply.serialize(client.protocol));
+                            decoder.buffer_mut().clear();
+                            break;
+                        }
                     }
-                };
-                if let Err(e) = socket.write_all(&response.into_bytes()).await {
+                }
+                if response.is_empty() {
+                    continue;
+                }
+                if let Err(e) = socket.write_all(&response).await {
                     eprintln!("failed to write to socket; err = {e}");
                     return;
                 };
@@ -55,18 +109,37 @@ pub async fn run_server(port: u16, args: Vec<String>) -> Result<(), Box<dyn Erro
     }
 }
 
+/// Resolves once the peer hangs up. Commands pipelined behind a blocked one are
+/// buffered meanwhile, to run once it is answered.
+async fn closed(socket: &mut TcpStream, buffer: &mut BytesMut) {
+    loop {
+        match socket.read_buf(buffer).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+    }
+}
+
+enum Outcome {
+    Reply(Bytes),
+    /// The command is parked until a write readies one of its keys.
+    Blocked(Ticket),
+}
+
 /*
  * *1\r\n$4\r\nPING\r\n
  */
-async fn process(buff: &str, store: &Redis) -> Result<String, String> {
-    let mut lines = buff.lines();
-    let Some(parsed_value) = Value::parse(&mut lines) else {
-        return Err("No value parsed".to_string());
+fn process(command: &Value, client: &mut Client, store: &Redis) -> Outcome {
+    let mut store = store.lock().unwrap();
+    let mut ctx = Context {
+        store: &mut store,
+        client,
     };
-
-    let store = store.lock().unwrap();
-    let Ok(response) = commands::eval_command(&parsed_value, store) else {
-        return Ok("-ERR".to_string());
+    let response = commands::eval_command(command, &mut ctx).unwrap_or_else(Value::from);
+    let outcome = match client.blocked.take() {
+        Some(request) => Outcome::Blocked(store.blocking.register(client, command, request)),
+        None => Outcome::Reply(response.serialize(client.protocol)),
     };
-    Ok(response.serialize())
+    blocking::serve_ready(&mut store);
+    outcome
 }
@@ -0,0 +1,359 @@
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Soft cap on a node's encoded size; one oversized element still gets a node.
+const NODE_BYTES: usize = 8 * 1024;
+/// Each element is framed by its length on both sides, so a node can be read
+/// from either end.
+const FRAME_BYTES: usize = 8;
+
+/// A deque of byte strings stored as a chain of packed nodes, like Redis's
+/// quicklist. Pushes and pops at either end only touch the end node, and
+/// elements cost 8 bytes of framing instead of a separate allocation each.
+#[derive(Default, Clone)]
+pub struct QuickList {
+    nodes: VecDeque<Node>,
+    len: usize,
+}
+
+#[derive(Default, Clone)]
+struct Node {
+    buf: Vec<u8>,
+    count: usize,
+}
+
+fn frame(element: &[u8]) -> Vec<u8> {
+    let len = (element.len() as u32).to_le_bytes();
+    let mut framed = Vec::with_capacity(element.len() + FRAME_BYTES);
+    framed.extend_from_slice(&len);
+    framed.extend_from_slice(element);
+    framed.extend_from_slice(&len);
+    framed
+}
+
+fn read_len(buf: &[u8], at: usize) -> usize {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap()) as usize
+}
+
+impl Node {
+    fn from_elements<'a>(elements: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let mut node = Node::default();
+        for element in elements {
+            node.push_back(element);
+        }
+        node
+    }
+
+    /// Packs `elements` into as many nodes as the size cap calls for.
+    fn pack<'a>(elements: impl IntoIterator<Item = &'a [u8]>) -> Vec<Self> {
+        let mut nodes = vec![Node::default()];
+        for element in elements {
+            let last = nodes.last_mut().expect("starts with a node");
+            if last.has_room_for(element) {
+                last.push_back(element);
+            } else {
+                nodes.push(Node::from_elements([element]));
+            }
+        }
+        nodes
+    }
+
+    fn has_room_for(&self, element: &[u8]) -> bool {
+        self.count == 0 || self.buf.len() + element.len() + FRAME_BYTES <= NODE_BYTES
+    }
+
+    fn push_back(&mut self, element: &[u8]) {
+        self.buf.extend_from_slice(&frame(element));
+        self.count += 1;
+    }
+
+    fn push_front(&mut self, element: &[u8]) {
+        self.buf.splice(0..0, frame(element));
+        self.count += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<Bytes> {
+        if self.count == 0 {
+            return None;
+        }
+        let len = read_len(&self.buf, 0);
+        let element = Bytes::copy_from_slice(&self.buf[4..4 + len]);
+        self.buf.drain(..len + FRAME_BYTES);
+        self.count -= 1;
+        Some(element)
+    }
+
+    fn pop_back(&mut self) -> Option<Bytes> {
+        if self.count == 0 {
+            return None;
+        }
+        let end = self.buf.len();
+        let len = read_len(&self.buf, end - 4);
+        let element = Bytes::copy_from_slice(&self.buf[end - 4 - len..end - 4]);
+        self.buf.truncate(end - len - FRAME_BYTES);
+        self.count -= 1;
+        Some(element)
+    }
+
+    fn iter(&self) -> NodeIter<'_> {
+        NodeIter {
+            buf: &self.buf,
+            front: 0,
+            back: self.buf.len(),
+        }
+    }
+}
+
+struct NodeIter<'a> {
+    buf: &'a [u8],
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        let len = read_len(self.buf, self.front);
+        let element = &self.buf[self.front + 4..self.front + 4 + len];
+        self.front += len + FRAME_BYTES;
+        Some(element)
+    }
+}
+
+impl DoubleEndedIterator for NodeIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        let len = read_len(self.buf, self.back - 4);
+        let element = &self.buf[self.back - 4 - len..self.back - 4];
+        self.back -= len + FRAME_BYTES;
+        Some(element)
+    }
+}
+
+impl QuickList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_back(&mut self, element: &[u8]) {
+        match self.nodes.back_mut() {
+            Some(node) if node.has_room_for(element) => node.push_back(element),
+            _ => self.nodes.push_back(Node::from_elements([element])),
+        }
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, element: &[u8]) {
+        match self.nodes.front_mut() {
+            Some(node) if node.has_room_for(element) => node.push_front(element),
+            _ => self.nodes.push_front(Node::from_elements([element])),
+        }
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<Bytes> {
+        let node = self.nodes.front_mut()?;
+        let element = node.pop_front();
+        if node.count == 0 {
+            self.nodes.pop_front();
+        }
+        self.len -= 1;
+        element
+    }
+
+    pub fn pop_back(&mut self) -> Option<Bytes> {
+        let node = self.nodes.back_mut()?;
+        let element = node.pop_back();
+        if node.count == 0 {
+            self.nodes.pop_back();
+        }
+        self.len -= 1;
+        element
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &[u8]> {
+        self.nodes.iter().flat_map(Node::iter)
+    }
+
+    /// The node holding `index` and the element's offset inside it.
+    fn locate(&self, mut index: usize) -> Option<(usize, usize)> {
+        if index >= self.len {
+            return None;
+        }
+        for (position, node) in self.nodes.iter().enumerate() {
+            if index < node.count {
+                return Some((position, index));
+            }
+            index -= node.count;
+        }
+        None
+    }
+
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let (node, offset) = self.locate(index)?;
+        self.nodes[node].iter().nth(offset)
+    }
+
+    /// Rewrites the node holding `index` with `edit` applied to its elements,
+    /// splitting it if it has outgrown the size cap.
+    fn edit_node(&mut self, index: usize, edit: impl FnOnce(&mut Vec<Vec<u8>>, usize)) -> bool {
+        let Some((position, offset)) = self.locate(index) else {
+            return false;
+        };
+        let mut elements = self.nodes[position]
+            .iter()
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        edit(&mut elements, offset);
+        let replaced = self.nodes.remove(position).expect("located above");
+        self.len = self.len - replaced.count + elements.len();
+        for (at, node) in Node::pack(elements.iter().map(Vec::as_slice))
+            .into_iter()
+            .enumerate()
+        {
+            self.nodes.insert(position + at, node);
+        }
+        true
+    }
+
+    /// Replaces the element at `index`; false if it is out of range.
+    pub fn set(&mut self, index: usize, element: &[u8]) -> bool {
+        self.edit_node(index, |elements, offset| {
+            elements[offset] = element.to_vec()
+        })
+    }
+
+    /// Inserts `element` so that it ends up at `index`, which may be `len()`.
+    pub fn insert(&mut self, index: usize, element: &[u8]) {
+        if index == self.len {
+            self.push_back(element);
+        } else {
+            self.edit_node(index, |elements, offset| {
+                elements.insert(offset, element.to_vec())
+            });
+        }
+    }
+
+    /// Keeps only the elements `keep` returns true for, in order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&[u8]) -> bool) {
+        *self = self.iter().filter(|element| keep(element)).collect();
+    }
+}
+
+impl<'a> FromIterator<&'a [u8]> for QuickList {
+    fn from_iter<I: IntoIterator<Item = &'a [u8]>>(iter: I) -> Self {
+        let mut list = QuickList::new();
+        for element in iter {
+            list.push_back(element);
+        }
+        list
+    }
+}
+
+impl fmt::Debug for QuickList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(String::from_utf8_lossy))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn collect(list: &QuickList) -> Vec<&[u8]> {
+        list.iter().collect()
+    }
+
+    #[test]
+    fn test_push_and_pop_at_both_ends() {
+        let mut list = QuickList::new();
+        list.push_back(b"b");
+        list.push_front(b"a");
+        list.push_back(b"c");
+        assert_eq!(collect(&list), [b"a", b"b", b"c"]);
+        assert_eq!(list.len(), 3);
+
+        assert_eq!(list.pop_back().as_deref(), Some(&b"c"[..]));
+        assert_eq!(list.pop_front().as_deref(), Some(&b"a"[..]));
+        assert_eq!(list.pop_front().as_deref(), Some(&b"b"[..]));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_spans_many_nodes() {
+        let elements = (0..5000).map(|i| i.to_string()).collect::<Vec<_>>();
+        let mut list = elements
+            .iter()
+            .map(|element| element.as_bytes())
+            .collect::<QuickList>();
+        assert!(list.nodes.len() > 1);
+        assert_eq!(list.len(), 5000);
+
+        assert_eq!(list.get(4321), Some(&b"4321"[..]));
+        assert_eq!(list.iter().next_back(), Some(&b"4999"[..]));
+        assert_eq!(list.iter().rev().nth(1), Some(&b"4998"[..]));
+
+        assert!(list.set(2500, b"x"));
+        list.insert(2500, b"y");
+        assert_eq!(list.get(2500), Some(&b"y"[..]));
+        assert_eq!(list.get(2501), Some(&b"x"[..]));
+        assert_eq!(list.len(), 5001);
+
+        list.retain(|element| element.len() == 1);
+        assert_eq!(collect(&list).len(), 12);
+        assert_eq!(list.len(), 12);
+    }
+
+    #[test]
+    fn test_inserts_split_full_nodes() {
+        let mut list = [&b"first"[..], b"last"].into_iter().collect::<QuickList>();
+        for i in 0..5000 {
+            list.insert(1, i.to_string().as_bytes());
+        }
+        list.set(2500, b"x");
+        assert_eq!(list.len(), 5002);
+        assert!(list.nodes.len() > 1);
+        assert!(list.nodes.iter().all(|node| node.buf.len() <= NODE_BYTES));
+        assert_eq!(list.get(0), Some(&b"first"[..]));
+        assert_eq!(list.get(1), Some(&b"4999"[..]));
+        assert_eq!(list.get(2500), Some(&b"x"[..]));
+        assert_eq!(list.get(5001), Some(&b"last"[..]));
+    }
+
+    #[test]
+    fn test_large_and_empty_elements() {
+        let big = vec![7u8; NODE_BYTES * 2];
+        let mut list = QuickList::new();
+        list.push_back(b"a");
+        list.push_back(&big);
+        list.push_front(b"");
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.nodes.len(), 2);
+        assert_eq!(list.get(0), Some(&b""[..]));
+        assert!(list.get(2) == Some(&big[..]));
+        assert_eq!(
+            list.pop_back().map(|element| element.len()),
+            Some(big.len())
+        );
+        assert_eq!(list.pop_back().as_deref(), Some(&b"a"[..]));
+        assert_eq!(list.pop_back().as_deref(), Some(&b""[..]));
+    }
+}
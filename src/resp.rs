@@ -1,91 +1,848 @@
-#[derive(Debug, Clone)]
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     NullString,
+    NullArray,
     SimpleString(String),
-    BulkString(String),
+    Error(String),
+    BulkString(Bytes),
     Integer(i64),
     Array(Vec<Value>),
+    // RESP3-only types; each falls back to its closest RESP2 shape.
+    Boolean(bool),
+    Double(f64),
+    BigNumber(String),
+    VerbatimString([u8; 3], Bytes),
+    Map(Vec<(Value, Value)>),
+    Set(Vec<Value>),
+    Push(Vec<Value>),
+}
+
+/// Wire protocol version a connection negotiated with HELLO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ProtocolError {
+    #[error("Protocol error: invalid multibulk length")]
+    InvalidMultibulkLength,
+    #[error("Protocol error: invalid bulk length")]
+    InvalidBulkLength,
+    #[error("Protocol error: invalid integer")]
+    InvalidInteger,
+    #[error("Protocol error: invalid double")]
+    InvalidDouble,
+    #[error("Protocol error: invalid boolean")]
+    InvalidBoolean,
+    #[error("Protocol error: invalid verbatim string")]
+    InvalidVerbatimString,
+    #[error("Protocol error: invalid utf8 in simple string")]
+    InvalidUtf8,
+    #[error("Protocol error: expected '\\r\\n' after bulk payload")]
+    MissingBulkTerminator,
+    #[error("Protocol error: expected '$', got '{}'", *.0 as char)]
+    ExpectedBulk(u8),
+    #[error("Protocol error: unexpected type byte '{}'", *.0 as char)]
+    UnexpectedType(u8),
+    #[error("Protocol error: too big request line")]
+    LineTooLong,
+    #[error("Protocol error: too big inline request")]
+    InlineTooLong,
+    #[error("Protocol error: unbalanced quotes in request")]
+    UnbalancedQuotes,
+}
+
+/// Outcome of decoding one frame from the front of a buffer: `Ok(None)` means
+/// the frame is incomplete, otherwise the value and the number of bytes it spans.
+type Parsed = Result<Option<(Value, usize)>, ProtocolError>;
+/// Like [`Parsed`], for the pieces of a frame where `None` stands for a `-1` length.
+type ParsedPart<T> = Result<Option<(Option<T>, usize)>, ProtocolError>;
+
+/// Formats a double the way Redis replies with one.
+pub fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        "nan".to_string()
+    } else if d.is_infinite() {
+        if d > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        // The shortest digits that read back as `d`, laid out like C's %.17g:
+        // plain below 1e17 and down to 1e-4, otherwise with a signed exponent
+        // of at least two digits.
+        let scientific = format!("{d:e}");
+        let (mantissa, exponent) = scientific
+            .split_once('e')
+            .expect("Rust always writes an exponent");
+        let exponent: i32 = exponent.parse().expect("the exponent is an integer");
+        if (-4..17).contains(&exponent) {
+            d.to_string()
+        } else {
+            let sign = if exponent < 0 { '-' } else { '+' };
+            format!("{mantissa}e{sign}{:02}", exponent.abs())
+        }
+    }
 }
 
 impl Value {
-    pub const NULL_STRING: &'static str = "$-1\r\n";
+    pub const NULL_STRING: &'static [u8] = b"$-1\r\n";
+    pub const NULL_ARRAY: &'static [u8] = b"*-1\r\n";
+    pub const NULL: &'static [u8] = b"_\r\n";
 
-    pub fn serialize(&self) -> String {
+    /// Longest header line accepted before a CRLF shows up.
+    const MAX_LINE_LENGTH: usize = 64 * 1024;
+    /// Largest bulk string payload a client may declare.
+    const MAX_BULK_LENGTH: usize = 512 * 1024 * 1024;
+    /// Largest array length a client may declare.
+    const MAX_MULTIBULK_LENGTH: usize = 1024 * 1024;
+
+    pub fn serialize(&self, protocol: Protocol) -> Bytes {
+        let mut buf = BytesMut::new();
+        self.serialize_into(&mut buf, protocol);
+        buf.freeze()
+    }
+
+    fn serialize_into(&self, buf: &mut BytesMut, protocol: Protocol) {
+        let resp3 = protocol == Protocol::Resp3;
         match self {
-            Value::NullString => Value::NULL_STRING.to_string(),
-            Value::SimpleString(s) => format!("+{}\r\n", s),
-            Value::BulkString(s) => format!("${}\r\n{}\r\n", s.chars().count(), s),
-            Value::Integer(i) => format!(":{}\r\n", i),
-            Value::Array(v) => {
-                let serialize_values = v
-                    .iter()
-                    .map(|value: &Value| value.serialize())
-                    .collect::<String>();
-
-                format!("*{}\r\n{}", v.len(), serialize_values)
+            Value::NullString | Value::NullArray if resp3 => buf.put_slice(Value::NULL),
+            Value::NullString => buf.put_slice(Value::NULL_STRING),
+            Value::NullArray => buf.put_slice(Value::NULL_ARRAY),
+            Value::SimpleString(s) => {
+                buf.put_u8(b'+');
+                buf.put_slice(s.as_bytes());
+                buf.put_slice(b"\r\n");
+            }
+            Value::Error(e) => {
+                // Error lines cannot carry a line break.
+                buf.put_u8(b'-');
+                buf.put_slice(e.replace(['\r', '\n'], " ").as_bytes());
+                buf.put_slice(b"\r\n");
+            }
+            Value::BulkString(s) => Self::serialize_blob(buf, b'$', s),
+            Value::Integer(i) => buf.put_slice(format!(":{}\r\n", i).as_bytes()),
+            Value::Array(v) => Self::serialize_aggregate(buf, b'*', v, protocol),
+            Value::Boolean(b) if resp3 => buf.put_slice(if *b { b"#t\r\n" } else { b"#f\r\n" }),
+            Value::Boolean(b) => buf.put_slice(if *b { b":1\r\n" } else { b":0\r\n" }),
+            Value::Double(d) if resp3 => {
+                buf.put_slice(format!(",{}\r\n", format_double(*d)).as_bytes())
             }
+            Value::Double(d) => Self::serialize_blob(buf, b'$', format_double(*d).as_bytes()),
+            Value::BigNumber(n) if resp3 => buf.put_slice(format!("({}\r\n", n).as_bytes()),
+            Value::BigNumber(n) => Self::serialize_blob(buf, b'$', n.as_bytes()),
+            Value::VerbatimString(format, text) if resp3 => {
+                buf.put_slice(format!("={}\r\n", text.len() + 4).as_bytes());
+                buf.put_slice(format);
+                buf.put_u8(b':');
+                buf.put_slice(text);
+                buf.put_slice(b"\r\n");
+            }
+            Value::VerbatimString(_, text) => Self::serialize_blob(buf, b'$', text),
+            Value::Map(pairs) => {
+                if resp3 {
+                    buf.put_slice(format!("%{}\r\n", pairs.len()).as_bytes());
+                } else {
+                    buf.put_slice(format!("*{}\r\n", pairs.len() * 2).as_bytes());
+                }
+                for (key, value) in pairs {
+                    key.serialize_into(buf, protocol);
+                    value.serialize_into(buf, protocol);
+                }
+            }
+            Value::Set(v) => {
+                Self::serialize_aggregate(buf, if resp3 { b'~' } else { b'*' }, v, protocol)
+            }
+            Value::Push(v) => {
+                Self::serialize_aggregate(buf, if resp3 { b'>' } else { b'*' }, v, protocol)
+            }
+        }
+    }
+
+    fn serialize_blob(buf: &mut BytesMut, kind: u8, payload: &[u8]) {
+        buf.put_u8(kind);
+        buf.put_slice(format!("{}\r\n", payload.len()).as_bytes());
+        buf.put_slice(payload);
+        buf.put_slice(b"\r\n");
+    }
+
+    fn serialize_aggregate(buf: &mut BytesMut, kind: u8, values: &[Value], protocol: Protocol) {
+        buf.put_u8(kind);
+        buf.put_slice(format!("{}\r\n", values.len()).as_bytes());
+        for value in values {
+            value.serialize_into(buf, protocol);
         }
     }
 
     /*
      * *1\r\n$4\r\nPING\r\n
      */
-    pub fn parse<'a, I>(iter: &mut I) -> Option<Value>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        let line = iter.next()?;
-
-        match line.chars().next() {
-            Some('+') => Self::parse_simple_string(iter, line),
-            Some('*') => Self::parse_array(iter, line),
-            Some('$') => Self::parse_bulk_string(iter, line),
-            Some(':') => Self::parse_integer(iter, line),
-            _ => None,
+    pub fn parse(buf: &[u8]) -> Parsed {
+        Self::parse_at(buf, 0)
+    }
+
+    fn parse_at(buf: &[u8], start: usize) -> Parsed {
+        let Some((line, next)) = Self::read_line(buf, start)? else {
+            return Ok(None);
+        };
+        let Some((&kind, header)) = line.split_first() else {
+            return Err(ProtocolError::UnexpectedType(b'\r'));
+        };
+
+        match kind {
+            b'+' => Self::parse_simple_string(header, next),
+            b'-' => Self::parse_error(header, next),
+            b'!' => Self::parse_blob_error(buf, header, next),
+            b'*' => Self::parse_array(buf, header, next),
+            b'$' => Self::parse_bulk_string(buf, header, next),
+            b':' => Self::parse_integer(header, next),
+            b'_' => Ok(Some((Value::NullString, next))),
+            b'#' => Self::parse_boolean(header, next),
+            b',' => Self::parse_double(header, next),
+            b'(' => Self::parse_big_number(header, next),
+            b'=' => Self::parse_verbatim_string(buf, header, next),
+            b'%' => Self::parse_map(buf, header, next),
+            b'~' => Self::parse_set(buf, header, next),
+            b'>' => Self::parse_push(buf, header, next),
+            other => Err(ProtocolError::UnexpectedType(other)),
         }
     }
 
-    fn parse_simple_string<'a, I>(_iter: &mut I, line: &str) -> Option<Value>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        Some(Value::SimpleString(line[1..].to_string()))
+    /// Returns the line starting at `start` without its CRLF, and the offset just past it.
+    fn read_line(buf: &[u8], start: usize) -> Result<Option<(&[u8], usize)>, ProtocolError> {
+        let rest = &buf[start..];
+        match rest.windows(2).position(|w| w == b"\r\n") {
+            Some(end) => Ok(Some((&rest[..end], start + end + 2))),
+            None if rest.len() > Self::MAX_LINE_LENGTH => Err(ProtocolError::LineTooLong),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_number(header: &[u8]) -> Option<i64> {
+        std::str::from_utf8(header).ok()?.parse::<i64>().ok()
+    }
+
+    fn parse_simple_string(header: &[u8], next: usize) -> Parsed {
+        let s = std::str::from_utf8(header).map_err(|_| ProtocolError::InvalidUtf8)?;
+        Ok(Some((Value::SimpleString(s.to_string()), next)))
     }
 
-    fn parse_bulk_string<'a, I>(iter: &mut I, line: &str) -> Option<Value>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        let Ok(_bytes) = line[1..].parse::<u64>() else {
-            return None;
+    fn parse_error(header: &[u8], next: usize) -> Parsed {
+        let e = String::from_utf8_lossy(header).to_string();
+        Ok(Some((Value::Error(e), next)))
+    }
+
+    fn parse_blob_error(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(Self::parse_blob(buf, header, next)?.map(|(word, after)| {
+            let e = String::from_utf8_lossy(&word.unwrap_or_default()).to_string();
+            (Value::Error(e), after)
+        }))
+    }
+
+    /// Reads a length-prefixed payload; `Ok(Some((None, _)))` is a `-1` null length.
+    fn parse_blob(buf: &[u8], header: &[u8], next: usize) -> ParsedPart<Bytes> {
+        let length = match Self::parse_number(header) {
+            Some(-1) => return Ok(Some((None, next))),
+            Some(n) if (0..=Self::MAX_BULK_LENGTH as i64).contains(&n) => n as usize,
+            _ => return Err(ProtocolError::InvalidBulkLength),
         };
-        let word = iter.next()?.to_string();
-        Some(Value::BulkString(word))
+
+        let end = next + length;
+        if buf.len() < end + 2 {
+            return Ok(None);
+        }
+        if &buf[end..end + 2] != b"\r\n" {
+            return Err(ProtocolError::MissingBulkTerminator);
+        }
+        let word = Bytes::copy_from_slice(&buf[next..end]);
+        Ok(Some((Some(word), end + 2)))
+    }
+
+    fn parse_bulk_string(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(
+            Self::parse_blob(buf, header, next)?.map(|(word, after)| match word {
+                Some(word) => (Value::BulkString(word), after),
+                None => (Value::NullString, after),
+            }),
+        )
+    }
+
+    fn parse_verbatim_string(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        let Some((word, after)) = Self::parse_blob(buf, header, next)? else {
+            return Ok(None);
+        };
+        match word {
+            Some(word) if word.len() >= 4 && word[3] == b':' => {
+                let format = [word[0], word[1], word[2]];
+                Ok(Some((
+                    Value::VerbatimString(format, word.slice(4..)),
+                    after,
+                )))
+            }
+            _ => Err(ProtocolError::InvalidVerbatimString),
+        }
+    }
+
+    fn parse_integer(header: &[u8], next: usize) -> Parsed {
+        let Some(integer) = Self::parse_number(header) else {
+            return Err(ProtocolError::InvalidInteger);
+        };
+        Ok(Some((Value::Integer(integer), next)))
+    }
+
+    fn parse_boolean(header: &[u8], next: usize) -> Parsed {
+        match header {
+            b"t" => Ok(Some((Value::Boolean(true), next))),
+            b"f" => Ok(Some((Value::Boolean(false), next))),
+            _ => Err(ProtocolError::InvalidBoolean),
+        }
     }
 
-    fn parse_integer<'a, I>(_iter: I, line: &str) -> Option<Value>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        let Ok(integer) = line[1..].parse::<i64>() else {
-            return None;
+    fn parse_double(header: &[u8], next: usize) -> Parsed {
+        let double = match header {
+            b"inf" => f64::INFINITY,
+            b"-inf" => f64::NEG_INFINITY,
+            _ => std::str::from_utf8(header)
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok())
+                .ok_or(ProtocolError::InvalidDouble)?,
         };
-        Some(Value::Integer(integer))
+        Ok(Some((Value::Double(double), next)))
     }
 
-    fn parse_array<'a, I>(iter: &mut I, line: &str) -> Option<Value>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        let Ok(array_length) = line[1..].parse::<usize>() else {
-            return None;
+    fn parse_big_number(header: &[u8], next: usize) -> Parsed {
+        let digits = header.strip_prefix(b"-").unwrap_or(header);
+        if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+            return Err(ProtocolError::InvalidInteger);
+        }
+        let s = String::from_utf8(header.to_vec()).map_err(|_| ProtocolError::InvalidUtf8)?;
+        Ok(Some((Value::BigNumber(s), next)))
+    }
+
+    /// Reads `header` elements; `Ok(Some((None, _)))` is a `-1` null length.
+    fn parse_elements(
+        buf: &[u8],
+        header: &[u8],
+        next: usize,
+        per_entry: usize,
+    ) -> ParsedPart<Vec<Value>> {
+        let array_length = match Self::parse_number(header) {
+            Some(-1) => return Ok(Some((None, next))),
+            Some(n) if (0..=Self::MAX_MULTIBULK_LENGTH as i64).contains(&n) => n as usize,
+            _ => return Err(ProtocolError::InvalidMultibulkLength),
         };
-        let mut arr: Vec<Value> = Vec::with_capacity(array_length);
+        let count = array_length * per_entry;
+        // Cap the up-front allocation; a client may declare far more than it sends.
+        let mut arr: Vec<Value> = Vec::with_capacity(count.min(1024));
 
-        for _i in 1..=array_length {
-            let next_value = Self::parse(iter)?;
+        let mut cursor = next;
+        for _i in 1..=count {
+            let Some((next_value, after)) = Self::parse_at(buf, cursor)? else {
+                return Ok(None);
+            };
             arr.push(next_value);
+            cursor = after;
+        }
+        Ok(Some((Some(arr), cursor)))
+    }
+
+    fn parse_array(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(
+            Self::parse_elements(buf, header, next, 1)?.map(|(arr, after)| match arr {
+                Some(arr) => (Value::Array(arr), after),
+                None => (Value::NullArray, after),
+            }),
+        )
+    }
+
+    fn parse_set(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(Self::parse_elements(buf, header, next, 1)?
+            .map(|(arr, after)| (Value::Set(arr.unwrap_or_default()), after)))
+    }
+
+    fn parse_push(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(Self::parse_elements(buf, header, next, 1)?
+            .map(|(arr, after)| (Value::Push(arr.unwrap_or_default()), after)))
+    }
+
+    fn parse_map(buf: &[u8], header: &[u8], next: usize) -> Parsed {
+        Ok(
+            Self::parse_elements(buf, header, next, 2)?.map(|(arr, after)| {
+                let mut flat = arr.unwrap_or_default().into_iter();
+                let mut pairs = Vec::with_capacity(flat.len() / 2);
+                while let (Some(key), Some(value)) = (flat.next(), flat.next()) {
+                    pairs.push((key, value));
+                }
+                (Value::Map(pairs), after)
+            }),
+        )
+    }
+}
+
+/// Per-connection buffer that accumulates raw socket reads and hands out
+/// complete frames one at a time, leaving any partial frame for the next read.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer: BytesMut,
+    /// The request being read, kept across reads so its finished arguments
+    /// aren't parsed again.
+    partial: Option<PartialRequest>,
+}
+
+/// A multibulk request read part way.
+#[derive(Debug)]
+struct PartialRequest {
+    args: Vec<Value>,
+    remaining: usize,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The buffer socket reads should append to.
+    pub fn buffer_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Pops the next complete frame, or `Ok(None)` if more data is needed.
+    /// Anything not starting with `*` is read as an inline command, and a
+    /// multibulk request may only hold bulk strings, as in Redis.
+    pub fn next_frame(&mut self) -> Result<Option<Value>, ProtocolError> {
+        let frame = self.read_frame();
+        if frame.is_err() {
+            self.partial = None;
+        }
+        frame
+    }
+
+    fn read_frame(&mut self) -> Result<Option<Value>, ProtocolError> {
+        loop {
+            if self.partial.is_none() {
+                match self.buffer.first() {
+                    None => return Ok(None),
+                    Some(b'*') => match self.read_multibulk_length()? {
+                        Some(0) => continue,
+                        Some(remaining) => {
+                            self.partial = Some(PartialRequest {
+                                // Cap the up-front allocation; a client may declare far more than it sends.
+                                args: Vec::with_capacity(remaining.min(1024)),
+                                remaining,
+                            });
+                        }
+                        None => return Ok(None),
+                    },
+                    Some(_) => match parse_inline(&self.buffer)? {
+                        // Blank inline lines are skipped rather than answered.
+                        Some((Value::Array(args), consumed)) if args.is_empty() => {
+                            self.buffer.advance(consumed);
+                            continue;
+                        }
+                        Some((value, consumed)) => {
+                            self.buffer.advance(consumed);
+                            return Ok(Some(value));
+                        }
+                        None => return Ok(None),
+                    },
+                }
+            }
+
+            let partial = self.partial.as_mut().expect("started above");
+            while partial.remaining > 0 {
+                let Some(arg) = Self::read_bulk(&mut self.buffer)? else {
+                    return Ok(None);
+                };
+                partial.args.push(Value::BulkString(arg));
+                partial.remaining -= 1;
+            }
+            let request = self.partial.take().expect("checked above");
+            return Ok(Some(Value::Array(request.args)));
+        }
+    }
+
+    /// Consumes a `*<count>` header. Empty and null requests count as 0, for
+    /// the caller to skip.
+    fn read_multibulk_length(&mut self) -> Result<Option<usize>, ProtocolError> {
+        let Some((line, next)) = Value::read_line(&self.buffer, 0)? else {
+            return Ok(None);
+        };
+        let count = match Value::parse_number(&line[1..]) {
+            Some(n) if n <= 0 => 0,
+            Some(n) if n <= Value::MAX_MULTIBULK_LENGTH as i64 => n as usize,
+            _ => return Err(ProtocolError::InvalidMultibulkLength),
+        };
+        self.buffer.advance(next);
+        Ok(Some(count))
+    }
+
+    /// Consumes one `$<length>` argument once all of it has arrived.
+    fn read_bulk(buffer: &mut BytesMut) -> Result<Option<Bytes>, ProtocolError> {
+        match buffer.first() {
+            None => return Ok(None),
+            Some(b'$') => {}
+            Some(&other) => return Err(ProtocolError::ExpectedBulk(other)),
+        }
+        let Some((line, next)) = Value::read_line(buffer, 0)? else {
+            return Ok(None);
+        };
+        match Value::parse_blob(buffer, &line[1..], next)? {
+            Some((Some(arg), after)) => {
+                buffer.advance(after);
+                Ok(Some(arg))
+            }
+            Some((None, _)) => Err(ProtocolError::InvalidBulkLength),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Longest inline command accepted before a newline shows up.
+const MAX_INLINE_LENGTH: usize = 64 * 1024;
+
+/*
+ * PING\r\n
+ * SET "hello world" 'it\'s'\n
+ */
+fn parse_inline(buf: &[u8]) -> Parsed {
+    let Some(end) = buf.iter().position(|&b| b == b'\n') else {
+        if buf.len() > MAX_INLINE_LENGTH {
+            return Err(ProtocolError::InlineTooLong);
+        }
+        return Ok(None);
+    };
+    let line = buf[..end].strip_suffix(b"\r").unwrap_or(&buf[..end]);
+    let args = split_args(line)?
+        .into_iter()
+        .map(Value::BulkString)
+        .collect();
+    Ok(Some((Value::Array(args), end + 1)))
+}
+
+/// Splits an inline command line into arguments, following Redis's quoting rules:
+/// double quotes understand `\n`, `\r`, `\t`, `\b`, `\a`, `\xHH` and escaped
+/// characters, single quotes only `\'`, and a closing quote must end the argument.
+fn split_args(line: &[u8]) -> Result<Vec<Bytes>, ProtocolError> {
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            return Ok(args);
+        }
+
+        let mut current = Vec::new();
+        let quote = match line[i] {
+            q @ (b'"' | b'\'') => {
+                i += 1;
+                Some(q)
+            }
+            _ => None,
+        };
+
+        loop {
+            match (quote, line.get(i)) {
+                (None, None) => break,
+                (None, Some(b)) if b.is_ascii_whitespace() => break,
+                (None, Some(&b)) => current.push(b),
+                (Some(_), None) => return Err(ProtocolError::UnbalancedQuotes),
+                (Some(b'"'), Some(b'\\')) if i + 3 < line.len() && line[i + 1] == b'x' => {
+                    match std::str::from_utf8(&line[i + 2..i + 4])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    {
+                        Some(byte) => {
+                            current.push(byte);
+                            i += 3;
+                        }
+                        None => current.push(b'\\'),
+                    }
+                }
+                (Some(b'"'), Some(b'\\')) if i + 1 < line.len() => {
+                    i += 1;
+                    current.push(match line[i] {
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'b' => 0x08,
+                        b'a' => 0x07,
+                        other => other,
+                    });
+                }
+                (Some(b'\''), Some(b'\\')) if line.get(i + 1) == Some(&b'\'') => {
+                    i += 1;
+                    current.push(b'\'');
+                }
+                (Some(q), Some(&b)) if b == q => {
+                    // The closing quote must be followed by a space or nothing.
+                    if line
+                        .get(i + 1)
+                        .is_some_and(|next| !next.is_ascii_whitespace())
+                    {
+                        return Err(ProtocolError::UnbalancedQuotes);
+                    }
+                    i += 1;
+                    break;
+                }
+                (Some(_), Some(&b)) => current.push(b),
+            }
+            i += 1;
         }
-        Some(Value::Array(arr))
+        args.push(Bytes::from(current));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bulk_strings(value: Option<Value>) -> Vec<Bytes> {
+        let Some(Value::Array(items)) = value else {
+            panic!("expected array, got {value:?}");
+        };
+        items
+            .into_iter()
+            .map(|item| match item {
+                Value::BulkString(s) => s,
+                other => panic!("expected bulk string, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_incomplete_frame_waits_for_more_data() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*2\r\n$4\r\nECHO\r\n$5\r\nhel");
+        assert_eq!(decoder.next_frame(), Ok(None));
+
+        decoder.extend_from_slice(b"lo\r\n");
+        assert_eq!(
+            bulk_strings(decoder.next_frame().unwrap()),
+            ["ECHO", "hello"]
+        );
+        assert_eq!(decoder.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn test_bulk_string_honours_declared_length() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*2\r\n$4\r\nECHO\r\n$7\r\na\r\nb\r\nc\r\n");
+        assert_eq!(
+            bulk_strings(decoder.next_frame().unwrap()),
+            ["ECHO", "a\r\nb\r\nc"]
+        );
+    }
+
+    #[test]
+    fn test_pipelined_frames() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nPI");
+
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+        assert_eq!(decoder.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn test_request_arguments_are_consumed_as_they_arrive() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$5\r\nva");
+        assert_eq!(decoder.next_frame(), Ok(None));
+        assert_eq!(&decoder.buffer_mut()[..], b"$5\r\nva");
+
+        decoder.extend_from_slice(b"lue\r\n*0\r\n*-1\r\n*1\r\n$4\r\nPING\r\n");
+        assert_eq!(
+            bulk_strings(decoder.next_frame().unwrap()),
+            ["SET", "k", "value"]
+        );
+        // Empty and null requests are skipped.
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+        assert_eq!(decoder.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn test_request_arguments_must_be_bulk_strings() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(&b"*1\r\n".repeat(100_000));
+        assert_eq!(decoder.next_frame(), Err(ProtocolError::ExpectedBulk(b'*')));
+
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*2\r\n$4\r\nECHO\r\n:1\r\n");
+        assert_eq!(decoder.next_frame(), Err(ProtocolError::ExpectedBulk(b':')));
+
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"*1\r\n$-1\r\n");
+        assert_eq!(decoder.next_frame(), Err(ProtocolError::InvalidBulkLength));
+
+        // The failed request doesn't linger once the connection clears its buffer.
+        decoder.buffer_mut().clear();
+        decoder.extend_from_slice(b"*1\r\n$4\r\nPING\r\n");
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert_eq!(
+            Value::parse(b"*x\r\n"),
+            Err(ProtocolError::InvalidMultibulkLength)
+        );
+        assert_eq!(
+            Value::parse(b"$-5\r\n"),
+            Err(ProtocolError::InvalidBulkLength)
+        );
+        assert_eq!(
+            Value::parse(b"$3\r\nabcd\r\n"),
+            Err(ProtocolError::MissingBulkTerminator)
+        );
+    }
+
+    #[test]
+    fn test_binary_bulk_string_round_trip() {
+        let payload = Bytes::from_static(b"\x00\xff\r\n\xc3");
+        let encoded = Value::BulkString(payload.clone()).serialize(Protocol::Resp2);
+        assert_eq!(&encoded[..], b"$5\r\n\x00\xff\r\n\xc3\r\n");
+
+        let (decoded, consumed) = Value::parse(&encoded).unwrap().unwrap();
+        assert_eq!(decoded, Value::BulkString(payload));
+        assert_eq!(consumed, encoded.len());
+    }
+
+    #[test]
+    fn test_bulk_length_counts_bytes() {
+        let encoded = Value::BulkString(Bytes::from("héllo")).serialize(Protocol::Resp2);
+        assert_eq!(&encoded[..], "$6\r\nhéllo\r\n".as_bytes());
+    }
+
+    #[test]
+    fn test_resp3_types_downgrade_to_resp2() {
+        let map = Value::Map(vec![(
+            Value::BulkString(Bytes::from("dir")),
+            Value::BulkString(Bytes::from("/tmp")),
+        )]);
+        assert_eq!(
+            &map.serialize(Protocol::Resp3)[..],
+            b"%1\r\n$3\r\ndir\r\n$4\r\n/tmp\r\n"
+        );
+        assert_eq!(
+            &map.serialize(Protocol::Resp2)[..],
+            b"*2\r\n$3\r\ndir\r\n$4\r\n/tmp\r\n"
+        );
+
+        assert_eq!(&Value::NullArray.serialize(Protocol::Resp3)[..], b"_\r\n");
+        assert_eq!(&Value::NullArray.serialize(Protocol::Resp2)[..], b"*-1\r\n");
+        assert_eq!(
+            &Value::Boolean(true).serialize(Protocol::Resp3)[..],
+            b"#t\r\n"
+        );
+        assert_eq!(
+            &Value::Boolean(true).serialize(Protocol::Resp2)[..],
+            b":1\r\n"
+        );
+        assert_eq!(
+            &Value::Double(1.5).serialize(Protocol::Resp3)[..],
+            b",1.5\r\n"
+        );
+        assert_eq!(
+            &Value::Double(1.5).serialize(Protocol::Resp2)[..],
+            b"$3\r\n1.5\r\n"
+        );
+        assert_eq!(
+            &Value::Double(f64::NEG_INFINITY).serialize(Protocol::Resp3)[..],
+            b",-inf\r\n"
+        );
+    }
+
+    #[test]
+    fn test_format_double() {
+        assert_eq!(format_double(1.5), "1.5");
+        assert_eq!(format_double(-0.0001), "-0.0001");
+        assert_eq!(format_double(1e16), "10000000000000000");
+        assert_eq!(format_double(1e17), "1e+17");
+        assert_eq!(format_double(1e21), "1e+21");
+        assert_eq!(format_double(-1.25e-7), "-1.25e-07");
+        assert_eq!(format_double(1e-300), "1e-300");
+        assert_eq!(format_double(0.1 + 0.2), "0.30000000000000004");
+    }
+
+    #[test]
+    fn test_resp3_round_trip() {
+        let value = Value::Push(vec![
+            Value::Set(vec![Value::Integer(1)]),
+            Value::BigNumber("-1234567890123456789012".to_string()),
+            Value::Error("ERR oops".to_string()),
+            Value::VerbatimString(*b"txt", Bytes::from("some text")),
+            Value::Boolean(false),
+            Value::Double(-0.25),
+            Value::Map(vec![(
+                Value::SimpleString("k".to_string()),
+                Value::NullString,
+            )]),
+        ]);
+        let encoded = value.serialize(Protocol::Resp3);
+        let (decoded, consumed) = Value::parse(&encoded).unwrap().unwrap();
+
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, encoded.len());
+    }
+
+    #[test]
+    fn test_error_serialization() {
+        let error = Value::Error("ERR bad\r\nthing".to_string());
+        assert_eq!(
+            &error.serialize(Protocol::Resp2)[..],
+            b"-ERR bad  thing\r\n"
+        );
+        assert_eq!(
+            &error.serialize(Protocol::Resp3)[..],
+            b"-ERR bad  thing\r\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_commands() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"PING\r\n\r\nECHO   hello\nSET \"a b\\x41\\n\" 'it\\'s'\r\n");
+
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+        assert_eq!(
+            bulk_strings(decoder.next_frame().unwrap()),
+            ["ECHO", "hello"]
+        );
+        assert_eq!(
+            bulk_strings(decoder.next_frame().unwrap()),
+            ["SET", "a bA\n", "it's"]
+        );
+        assert_eq!(decoder.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn test_inline_waits_for_newline() {
+        let mut decoder = Decoder::new();
+        decoder.extend_from_slice(b"PI");
+        assert_eq!(decoder.next_frame(), Ok(None));
+        decoder.extend_from_slice(b"NG\r\n");
+        assert_eq!(bulk_strings(decoder.next_frame().unwrap()), ["PING"]);
+    }
+
+    #[test]
+    fn test_inline_unbalanced_quotes() {
+        assert_eq!(
+            parse_inline(b"ECHO \"oops\r\n"),
+            Err(ProtocolError::UnbalancedQuotes)
+        );
+        assert_eq!(
+            parse_inline(b"ECHO \"a\"b\r\n"),
+            Err(ProtocolError::UnbalancedQuotes)
+        );
     }
 }
@@ -0,0 +1,85 @@
+use crate::dict::Dict;
+use bytes::Bytes;
+use rand::Rng;
+use rand::seq::IteratorRandom;
+use std::collections::HashSet;
+
+/// The members of a set, kept in a `Dict` so SSCAN can walk them a few at a
+/// time.
+#[derive(Debug, Clone, Default)]
+pub struct Set {
+    members: Dict<Bytes, ()>,
+}
+
+impl Set {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn contains(&self, member: &[u8]) -> bool {
+        self.members.contains_key(member)
+    }
+
+    /// Adds `member`, returning whether it is new.
+    pub fn insert(&mut self, member: Bytes) -> bool {
+        self.members.insert(member, ()).is_none()
+    }
+
+    /// Removes `member`, returning whether it was there.
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        self.members.remove(member).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bytes> {
+        self.members.keys()
+    }
+
+    /// A random member, `None` if the set is empty.
+    pub fn random(&self, rng: &mut impl Rng) -> Option<&Bytes> {
+        self.members.random(rng).map(|(member, _)| member)
+    }
+
+    /// `count` distinct random members, or all of them if there are fewer.
+    /// Counts well below the size draw members one at a time instead of
+    /// walking the whole set, as Redis does.
+    pub fn random_distinct(&self, rng: &mut impl Rng, count: usize) -> Vec<&Bytes> {
+        if count.saturating_mul(3) > self.len() {
+            return self.iter().choose_multiple(rng, count.min(self.len()));
+        }
+        let mut picked = HashSet::with_capacity(count);
+        while picked.len() < count {
+            picked.insert(self.random(rng).expect("count is below the size"));
+        }
+        picked.into_iter().collect()
+    }
+
+    /// Visits the members under `cursor`, as `Dict::scan` does.
+    pub fn scan(&self, cursor: u64, mut visit: impl FnMut(&Bytes)) -> u64 {
+        self.members.scan(cursor, |member, _| visit(member))
+    }
+}
+
+impl FromIterator<Bytes> for Set {
+    fn from_iter<I: IntoIterator<Item = Bytes>>(iter: I) -> Self {
+        Set {
+            members: iter.into_iter().map(|member| (member, ())).collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Set {
+    type Item = &'a Bytes;
+    type IntoIter = Box<dyn Iterator<Item = &'a Bytes> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
@@ -0,0 +1,482 @@
+use crate::dict::Dict;
+use bytes::Bytes;
+use rand::Rng;
+use std::fmt;
+
+/// Enough levels for 2^64 elements at a 1/4 promotion rate.
+const MAX_LEVEL: usize = 32;
+const PROMOTION: f64 = 0.25;
+/// Slot 0 of the arena is the header, which holds no element.
+const HEAD: usize = 0;
+
+/// A set of members ordered by (score, member), like Redis's zset: a map for
+/// score lookups next to a skiplist whose links record how many elements they
+/// skip, so ranks are found in O(log n) alongside the usual searches.
+#[derive(Clone)]
+pub struct SortedSet {
+    scores: Dict<Bytes, f64>,
+    list: SkipList,
+}
+
+#[derive(Clone)]
+struct SkipList {
+    /// Nodes live in an arena and link to each other by index.
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    tail: Option<usize>,
+    level: usize,
+}
+
+#[derive(Clone)]
+struct Node {
+    member: Bytes,
+    score: f64,
+    backward: Option<usize>,
+    levels: Vec<Link>,
+}
+
+#[derive(Clone, Copy)]
+struct Link {
+    forward: Option<usize>,
+    /// How many elements this link moves past, counting the one it lands on.
+    span: usize,
+}
+
+impl Node {
+    fn precedes(&self, score: f64, member: &[u8]) -> bool {
+        self.score < score || (self.score == score && self.member.as_ref() < member)
+    }
+}
+
+impl SkipList {
+    fn new() -> Self {
+        let head = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            backward: None,
+            levels: vec![
+                Link {
+                    forward: None,
+                    span: 0,
+                };
+                MAX_LEVEL
+            ],
+        };
+        SkipList {
+            nodes: vec![head],
+            free: vec![],
+            tail: None,
+            level: 1,
+        }
+    }
+
+    fn random_level() -> usize {
+        let mut rng = rand::thread_rng();
+        let mut level = 1;
+        while level < MAX_LEVEL && rng.gen_bool(PROMOTION) {
+            level += 1;
+        }
+        level
+    }
+
+    fn link(&self, node: usize, level: usize) -> Link {
+        self.nodes[node].levels[level]
+    }
+
+    fn link_mut(&mut self, node: usize, level: usize) -> &mut Link {
+        &mut self.nodes[node].levels[level]
+    }
+
+    /// The last node before (score, member) on every level, and the rank of each.
+    fn find_predecessors(
+        &self,
+        score: f64,
+        member: &[u8],
+    ) -> ([usize; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            rank[level] = if level + 1 == self.level {
+                0
+            } else {
+                rank[level + 1]
+            };
+            while let Some(next) = self.link(node, level).forward
+                && self.nodes[next].precedes(score, member)
+            {
+                rank[level] += self.link(node, level).span;
+                node = next;
+            }
+            update[level] = node;
+        }
+        (update, rank)
+    }
+
+    fn insert(&mut self, score: f64, member: Bytes) {
+        let (mut update, mut rank) = self.find_predecessors(score, &member);
+        let level = Self::random_level();
+        if level > self.level {
+            for new_level in self.level..level {
+                rank[new_level] = 0;
+                update[new_level] = HEAD;
+                self.link_mut(HEAD, new_level).span = self.len();
+            }
+            self.level = level;
+        }
+
+        let node = Node {
+            member,
+            score,
+            backward: (update[0] != HEAD).then_some(update[0]),
+            levels: vec![
+                Link {
+                    forward: None,
+                    span: 0,
+                };
+                level
+            ],
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        for (level, (&prev, &prev_rank)) in update.iter().zip(&rank).enumerate().take(level) {
+            let before = self.link(prev, level);
+            let skipped = rank[0] - prev_rank;
+            *self.link_mut(id, level) = Link {
+                forward: before.forward,
+                span: before.span - skipped,
+            };
+            *self.link_mut(prev, level) = Link {
+                forward: Some(id),
+                span: skipped + 1,
+            };
+        }
+        for (level, &prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.link_mut(prev, level).span += 1;
+        }
+        match self.link(id, 0).forward {
+            Some(next) => self.nodes[next].backward = Some(id),
+            None => self.tail = Some(id),
+        }
+    }
+
+    fn remove(&mut self, score: f64, member: &[u8]) -> bool {
+        let (update, _) = self.find_predecessors(score, member);
+        let Some(id) = self.link(update[0], 0).forward else {
+            return false;
+        };
+        if self.nodes[id].score != score || self.nodes[id].member != member {
+            return false;
+        }
+
+        for (level, &prev) in update.iter().enumerate().take(self.level) {
+            if self.link(prev, level).forward == Some(id) {
+                let removed = self.link(id, level);
+                *self.link_mut(prev, level) = Link {
+                    forward: removed.forward,
+                    span: self.link(prev, level).span + removed.span - 1,
+                };
+            } else {
+                self.link_mut(prev, level).span -= 1;
+            }
+        }
+        let backward = self.nodes[id].backward;
+        match self.link(id, 0).forward {
+            Some(next) => self.nodes[next].backward = backward,
+            None => self.tail = backward,
+        }
+        while self.level > 1 && self.link(HEAD, self.level - 1).forward.is_none() {
+            self.level -= 1;
+        }
+        self.nodes[id].member = Bytes::new();
+        self.nodes[id].levels = vec![];
+        self.free.push(id);
+        true
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len() - 1 - self.free.len()
+    }
+
+    /// How many leading elements satisfy `before`, which must hold for a prefix
+    /// of the set and fail for the rest.
+    fn partition_point(&self, before: impl Fn(f64, &[u8]) -> bool) -> usize {
+        let mut rank = 0;
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.link(node, level).forward
+                && before(self.nodes[next].score, &self.nodes[next].member)
+            {
+                rank += self.link(node, level).span;
+                node = next;
+            }
+        }
+        rank
+    }
+
+    /// The node at 0-based `rank`.
+    fn node_at(&self, rank: usize) -> Option<usize> {
+        let target = rank + 1;
+        let mut traversed = 0;
+        let mut node = HEAD;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.link(node, level).forward
+                && traversed + self.link(node, level).span <= target
+            {
+                traversed += self.link(node, level).span;
+                node = next;
+            }
+            if traversed == target {
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
+impl Default for SortedSet {
+    fn default() -> Self {
+        SortedSet {
+            scores: Dict::new(),
+            list: SkipList::new(),
+        }
+    }
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Adds `member` or moves it to `score`, returning its previous score.
+    /// `score` must not be NaN.
+    pub fn insert(&mut self, member: Bytes, score: f64) -> Option<f64> {
+        let old = self.scores.insert(member.clone(), score);
+        match old {
+            Some(old) if old == score => {}
+            Some(old) => {
+                self.list.remove(old, &member);
+                self.list.insert(score, member);
+            }
+            None => self.list.insert(score, member),
+        }
+        old
+    }
+
+    pub fn remove(&mut self, member: &[u8]) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.list.remove(score, member);
+        Some(score)
+    }
+
+    /// Visits the members under `cursor`, in no particular order, as
+    /// `Dict::scan` does.
+    pub fn scan(&self, cursor: u64, mut visit: impl FnMut(&Bytes, f64)) -> u64 {
+        self.scores
+            .scan(cursor, |member, score| visit(member, *score))
+    }
+
+    /// The 0-based position of `member` in ascending order.
+    pub fn rank(&self, member: &[u8]) -> Option<usize> {
+        let score = self.score(member)?;
+        Some(self.list.partition_point(|other_score, other| {
+            other_score < score || (other_score == score && other < member)
+        }))
+    }
+
+    /// How many leading elements satisfy `before`, given as (score, member).
+    /// It must hold for a prefix of the set and fail for the rest, which makes
+    /// this the rank where a score or lex range starts or ends.
+    pub fn partition_point(&self, before: impl Fn(f64, &[u8]) -> bool) -> usize {
+        self.list.partition_point(before)
+    }
+
+    pub fn get_by_rank(&self, rank: usize) -> Option<(&Bytes, f64)> {
+        let node = &self.list.nodes[self.list.node_at(rank)?];
+        Some((&node.member, node.score))
+    }
+
+    /// The elements with ranks in `start..end`, ascending.
+    pub fn range(&self, start: usize, end: usize) -> Iter<'_> {
+        let end = end.min(self.len());
+        Iter {
+            list: &self.list,
+            next: if start < end {
+                self.list.node_at(start)
+            } else {
+                None
+            },
+            remaining: end.saturating_sub(start),
+            reverse: false,
+        }
+    }
+
+    /// The elements with ranks in `start..end`, descending.
+    pub fn range_rev(&self, start: usize, end: usize) -> Iter<'_> {
+        let end = end.min(self.len());
+        Iter {
+            list: &self.list,
+            next: if start < end {
+                self.list.node_at(end - 1)
+            } else {
+                None
+            },
+            remaining: end.saturating_sub(start),
+            reverse: true,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.range(0, self.len())
+    }
+
+    pub fn pop_first(&mut self) -> Option<(Bytes, f64)> {
+        let (member, score) = self.get_by_rank(0)?;
+        let member = member.clone();
+        self.remove(&member);
+        Some((member, score))
+    }
+
+    pub fn pop_last(&mut self) -> Option<(Bytes, f64)> {
+        let tail = self.list.tail?;
+        let member = self.list.nodes[tail].member.clone();
+        let score = self.remove(&member)?;
+        Some((member, score))
+    }
+}
+
+/// Walks the skiplist's bottom level, which links every element in order.
+pub struct Iter<'a> {
+    list: &'a SkipList,
+    next: Option<usize>,
+    remaining: usize,
+    reverse: bool,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Bytes, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = &self.list.nodes[self.next?];
+        self.next = if self.reverse {
+            node.backward
+        } else {
+            node.levels[0].forward
+        };
+        self.remaining -= 1;
+        Some((&node.member, node.score))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl FromIterator<(Bytes, f64)> for SortedSet {
+    fn from_iter<I: IntoIterator<Item = (Bytes, f64)>>(iter: I) -> Self {
+        let mut set = SortedSet::new();
+        for (member, score) in iter {
+            set.insert(member, score);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for SortedSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter()
+                    .map(|(member, score)| (String::from_utf8_lossy(member), score)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn members(set: &SortedSet) -> Vec<&[u8]> {
+        set.iter().map(|(member, _)| member.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_orders_by_score_then_member() {
+        let mut set = SortedSet::new();
+        set.insert(Bytes::from("c"), 1.0);
+        set.insert(Bytes::from("b"), 1.0);
+        set.insert(Bytes::from("a"), 2.0);
+        set.insert(Bytes::from("z"), f64::NEG_INFINITY);
+        assert_eq!(members(&set), [&b"z"[..], b"b", b"c", b"a"]);
+        assert_eq!(set.rank(b"c"), Some(2));
+        assert_eq!(set.rank(b"missing"), None);
+
+        assert_eq!(set.insert(Bytes::from("z"), 3.0), Some(f64::NEG_INFINITY));
+        assert_eq!(members(&set), [&b"b"[..], b"c", b"a", b"z"]);
+        assert_eq!(set.remove(b"c"), Some(1.0));
+        assert_eq!(set.len(), 3);
+        assert_eq!(
+            set.range_rev(0, 3)
+                .map(|(member, _)| member.as_ref())
+                .collect::<Vec<_>>(),
+            [&b"z"[..], b"a", b"b"]
+        );
+        assert_eq!(set.pop_last(), Some((Bytes::from("z"), 3.0)));
+        assert_eq!(set.pop_first(), Some((Bytes::from("b"), 1.0)));
+        assert_eq!(members(&set), [&b"a"[..]]);
+    }
+
+    #[test]
+    fn test_ranks_stay_consistent() {
+        let mut set = SortedSet::new();
+        for i in 0..2000 {
+            set.insert(Bytes::from(format!("m{i}")), ((i * 7919) % 2000) as f64);
+        }
+        for i in (0..2000).step_by(3) {
+            set.remove(format!("m{i}").as_bytes());
+        }
+        let expected = set
+            .iter()
+            .map(|(member, _)| member.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), set.len());
+        for (rank, member) in expected.iter().enumerate() {
+            assert_eq!(set.rank(member), Some(rank));
+            assert_eq!(set.get_by_rank(rank).map(|(found, _)| found), Some(member));
+        }
+        assert_eq!(set.get_by_rank(set.len()), None);
+
+        let below_100 = set.partition_point(|score, _| score < 100.0);
+        assert_eq!(
+            set.get_by_rank(below_100).map(|(_, score)| score >= 100.0),
+            Some(true)
+        );
+        assert_eq!(set.range(10, 20).count(), 10);
+        assert_eq!(set.range(set.len() - 2, set.len() + 5).count(), 2);
+    }
+}
@@ -0,0 +1,615 @@
+use bytes::Bytes;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// A stream entry ID: milliseconds and a sequence number within them, ordered
+/// by both and written `ms-seq`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// Parses `ms-seq`, or a bare `ms` with `missing_seq` as its sequence.
+    pub fn parse(bytes: &[u8], missing_seq: u64) -> Option<StreamId> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let number = |part: &str| {
+            part.bytes()
+                .all(|byte| byte.is_ascii_digit())
+                .then(|| part.parse::<u64>().ok())
+                .flatten()
+        };
+        match text.split_once('-') {
+            Some((ms, seq)) => Some(StreamId::new(number(ms)?, number(seq)?)),
+            None => Some(StreamId::new(number(text)?, missing_seq)),
+        }
+    }
+
+    /// The smallest ID after this one, if there is one.
+    pub fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_add(1)?, 0)),
+        }
+    }
+
+    /// The largest ID before this one, if there is one.
+    pub fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => Some(StreamId::new(self.ms.checked_sub(1)?, u64::MAX)),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// An entry's field-value pairs, in the order they were added.
+pub type Fields = Vec<(Bytes, Bytes)>;
+
+/// How many entries a node takes before appends start a new one.
+pub const NODE_MAX_ENTRIES: usize = 100;
+
+/// How a stream is trimmed from its oldest end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trim {
+    /// Down to this many entries.
+    MaxLen(usize),
+    /// Every entry below this ID.
+    MinId(StreamId),
+}
+
+/// A run of consecutive entries. Nodes are what approximate trimming removes,
+/// so it never has to split one.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    entries: Vec<(StreamId, Fields)>,
+    /// Entries ever appended, deleted ones included, so a node emptied by
+    /// XDEL doesn't fill up again.
+    appended: usize,
+}
+
+impl Node {
+    fn position(&self, id: StreamId) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by_key(&id, |(entry_id, _)| *entry_id)
+    }
+}
+
+/// An append-only log of entries keyed by strictly increasing IDs, held in
+/// nodes of up to `NODE_MAX_ENTRIES`.
+#[derive(Debug, Clone, Default)]
+pub struct Stream {
+    /// Nodes keyed by the ID they started with, which outlives that entry.
+    nodes: BTreeMap<StreamId, Node>,
+    len: usize,
+    /// The highest ID ever added, which deleting entries doesn't lower.
+    last_id: StreamId,
+    /// Entries ever added, counting deleted and trimmed ones.
+    entries_added: u64,
+    /// The highest ID removed by XDEL, or 0-0.
+    max_deleted_id: StreamId,
+    groups: BTreeMap<Bytes, ConsumerGroup>,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn entries_added(&self) -> u64 {
+        self.entries_added
+    }
+
+    pub fn max_deleted_id(&self) -> StreamId {
+        self.max_deleted_id
+    }
+
+    /// The ID of the oldest entry, or 0-0 when there is none.
+    pub fn first_id(&self) -> StreamId {
+        self.first_entry().map_or(StreamId::MIN, |(id, _)| *id)
+    }
+
+    /// Adds an entry; `id` must be greater than `last_id()`.
+    pub fn append(&mut self, id: StreamId, fields: Fields) {
+        debug_assert!(id > self.last_id);
+        match self.nodes.last_entry() {
+            Some(mut node) if node.get().appended < NODE_MAX_ENTRIES => {
+                let node = node.get_mut();
+                node.entries.push((id, fields));
+                node.appended += 1;
+            }
+            _ => {
+                let node = Node {
+                    entries: vec![(id, fields)],
+                    appended: 1,
+                };
+                self.nodes.insert(id, node);
+            }
+        }
+        self.len += 1;
+        self.last_id = id;
+        self.entries_added += 1;
+    }
+
+    /// The node that would hold `id`, with its key.
+    fn node_of(&self, id: StreamId) -> Option<(&StreamId, &Node)> {
+        self.nodes.range(..=id).next_back()
+    }
+
+    pub fn get(&self, id: StreamId) -> Option<&Fields> {
+        let (_, node) = self.node_of(id)?;
+        let at = node.position(id).ok()?;
+        Some(&node.entries[at].1)
+    }
+
+    /// Deletes one entry, as XDEL does.
+    pub fn remove(&mut self, id: StreamId) -> bool {
+        let Some((&key, node)) = self.node_of(id) else {
+            return false;
+        };
+        let Ok(at) = node.position(id) else {
+            return false;
+        };
+        let node = self.nodes.get_mut(&key).expect("found above");
+        node.entries.remove(at);
+        if node.entries.is_empty() {
+            self.nodes.remove(&key);
+        }
+        self.len -= 1;
+        self.max_deleted_id = self.max_deleted_id.max(id);
+        true
+    }
+
+    /// Entries with IDs in `range`, ascending; reverse it for XREVRANGE.
+    pub fn range(
+        &self,
+        range: RangeInclusive<StreamId>,
+    ) -> impl DoubleEndedIterator<Item = (&StreamId, &Fields)> {
+        let (start, end) = (*range.start(), *range.end());
+        let first_node = self.node_of(start).map_or(start, |(key, _)| *key);
+        // BTreeMap panics on inverted ranges, which clients can ask for.
+        let nodes = (start <= end).then(|| self.nodes.range(first_node..=end));
+        nodes
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, node)| node.entries.iter())
+            .filter(move |(id, _)| range.contains(id))
+            .map(|(id, fields)| (id, fields))
+    }
+
+    pub fn first_entry(&self) -> Option<(&StreamId, &Fields)> {
+        let (_, node) = self.nodes.first_key_value()?;
+        node.entries.first().map(|(id, fields)| (id, fields))
+    }
+
+    pub fn last_entry(&self) -> Option<(&StreamId, &Fields)> {
+        let (_, node) = self.nodes.last_key_value()?;
+        node.entries.last().map(|(id, fields)| (id, fields))
+    }
+
+    /// Removes the oldest entries as `trim` asks, returning how many went.
+    /// Approximate trimming only removes whole nodes, and stops before
+    /// removing more than `limit` entries.
+    pub fn trim(&mut self, trim: Trim, approximate: bool, limit: Option<usize>) -> usize {
+        let mut removed = 0;
+        while let Some(mut first) = self.nodes.first_entry() {
+            let node = first.get_mut();
+            let excess = match trim {
+                Trim::MaxLen(max_len) => self.len.saturating_sub(max_len),
+                Trim::MinId(min_id) => node.entries.partition_point(|(id, _)| *id < min_id),
+            };
+            if excess == 0 {
+                break;
+            }
+            if excess >= node.entries.len() {
+                let count = node.entries.len();
+                if limit.is_some_and(|limit| removed + count > limit) {
+                    break;
+                }
+                first.remove();
+                self.len -= count;
+                removed += count;
+                continue;
+            }
+            if !approximate {
+                node.entries.drain(..excess);
+                self.len -= excess;
+                removed += excess;
+            }
+            break;
+        }
+        removed
+    }
+
+    /// Overrides what XSETID controls; the caller has checked the values
+    /// fit the entries.
+    pub fn set_metadata(
+        &mut self,
+        last_id: StreamId,
+        entries_added: u64,
+        max_deleted_id: StreamId,
+    ) {
+        self.last_id = last_id;
+        self.entries_added = entries_added;
+        self.max_deleted_id = max_deleted_id;
+    }
+
+    /// Whether XDEL made a gap in the IDs from `start` on, which makes
+    /// counting reads by position unreliable.
+    fn has_tombstones_from(&self, start: StreamId) -> bool {
+        self.len > 0 && self.max_deleted_id != StreamId::MIN && start <= self.max_deleted_id
+    }
+
+    /// How many entries were added up to and including `id`, when it can be
+    /// told without walking the stream.
+    pub fn entries_up_to(&self, id: StreamId) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+        if self.len == 0 && id <= self.last_id {
+            return Some(self.entries_added);
+        }
+        match id.cmp(&self.last_id) {
+            Ordering::Equal => return Some(self.entries_added),
+            Ordering::Greater => return None,
+            Ordering::Less => {}
+        }
+        let first = self.first_id();
+        if self.max_deleted_id == StreamId::MIN || self.max_deleted_id < first {
+            match id.cmp(&first) {
+                Ordering::Less => return Some(self.entries_added - self.len as u64),
+                Ordering::Equal => return Some(self.entries_added - self.len as u64 + 1),
+                Ordering::Greater => {}
+            }
+        }
+        None
+    }
+
+    /// The group's read counter after it reads `id`, the entry following its
+    /// last delivered one.
+    pub fn entries_read_after(&self, entries_read: Option<u64>, id: StreamId) -> Option<u64> {
+        match entries_read {
+            Some(read) if !self.has_tombstones_from(id) => Some(read + 1),
+            _ => self.entries_up_to(id),
+        }
+    }
+
+    /// How many entries `group` has yet to read, if that can be told.
+    pub fn lag(&self, group: &ConsumerGroup) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+        let read = match group.entries_read {
+            Some(read) if !self.has_tombstones_from(group.last_delivered) => read,
+            _ => self.entries_up_to(group.last_delivered)?,
+        };
+        Some(self.entries_added.saturating_sub(read))
+    }
+
+    /// Consumer groups by name.
+    pub fn groups(&self) -> &BTreeMap<Bytes, ConsumerGroup> {
+        &self.groups
+    }
+
+    pub fn group_mut(&mut self, name: &[u8]) -> Option<&mut ConsumerGroup> {
+        self.groups.get_mut(name)
+    }
+
+    /// Adds a group that has seen everything up to `last_delivered`, unless
+    /// one with that name exists.
+    pub fn create_group(
+        &mut self,
+        name: Bytes,
+        last_delivered: StreamId,
+        entries_read: Option<u64>,
+    ) -> bool {
+        if self.groups.contains_key(&name) {
+            return false;
+        }
+        let group = ConsumerGroup::new(last_delivered, entries_read);
+        self.groups.insert(name, group);
+        true
+    }
+
+    pub fn destroy_group(&mut self, name: &[u8]) -> bool {
+        self.groups.remove(name).is_some()
+    }
+}
+
+/// An entry delivered to a consumer that hasn't acknowledged it yet.
+#[derive(Debug, Clone)]
+pub struct PendingEntry {
+    pub consumer: Bytes,
+    /// When it was last delivered, in ms since the epoch.
+    pub delivery_time: u64,
+    pub delivery_count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Consumer {
+    /// When the consumer last took part in a command, in ms since the epoch.
+    pub seen_time: u64,
+    /// When it last read or claimed an entry, if it ever did.
+    pub active_time: Option<u64>,
+    /// IDs of its entries in the group's pending entries list.
+    pending: BTreeSet<StreamId>,
+}
+
+impl Consumer {
+    fn new(now: u64) -> Self {
+        Consumer {
+            seen_time: now,
+            active_time: None,
+            pending: BTreeSet::new(),
+        }
+    }
+
+    pub fn pending(&self) -> &BTreeSet<StreamId> {
+        &self.pending
+    }
+}
+
+/// Consumers sharing a stream, each entry going to one of them and staying
+/// pending until acknowledged.
+#[derive(Debug, Clone)]
+pub struct ConsumerGroup {
+    /// The last entry handed out for `>` reads.
+    pub last_delivered: StreamId,
+    /// How many entries the group has read, when that is known; see
+    /// `Stream::lag`.
+    pub entries_read: Option<u64>,
+    /// The group's pending entries list; consumers index into it.
+    pending: BTreeMap<StreamId, PendingEntry>,
+    consumers: BTreeMap<Bytes, Consumer>,
+}
+
+impl ConsumerGroup {
+    pub fn new(last_delivered: StreamId, entries_read: Option<u64>) -> Self {
+        ConsumerGroup {
+            last_delivered,
+            entries_read,
+            pending: BTreeMap::new(),
+            consumers: BTreeMap::new(),
+        }
+    }
+
+    pub fn pending(&self) -> &BTreeMap<StreamId, PendingEntry> {
+        &self.pending
+    }
+
+    /// Consumers by name.
+    pub fn consumers(&self) -> &BTreeMap<Bytes, Consumer> {
+        &self.consumers
+    }
+
+    /// Looks up a consumer, creating it if needed, and marks it seen at `now`.
+    pub fn consumer(&mut self, name: &Bytes, now: u64) -> &mut Consumer {
+        let consumer = self
+            .consumers
+            .entry(name.clone())
+            .or_insert_with(|| Consumer::new(now));
+        consumer.seen_time = now;
+        consumer
+    }
+
+    pub fn create_consumer(&mut self, name: &Bytes, now: u64) -> bool {
+        if self.consumers.contains_key(name) {
+            return false;
+        }
+        self.consumer(name, now);
+        true
+    }
+
+    /// Removes a consumer along with its pending entries, returning how many
+    /// it had.
+    pub fn delete_consumer(&mut self, name: &[u8]) -> Option<usize> {
+        let consumer = self.consumers.remove(name)?;
+        for id in &consumer.pending {
+            self.pending.remove(id);
+        }
+        Some(consumer.pending.len())
+    }
+
+    /// Records `id` as pending for `consumer`, taking it over from whichever
+    /// consumer had it before.
+    pub fn deliver(
+        &mut self,
+        id: StreamId,
+        consumer: &Bytes,
+        delivery_time: u64,
+        delivery_count: u64,
+    ) {
+        let entry = PendingEntry {
+            consumer: consumer.clone(),
+            delivery_time,
+            delivery_count,
+        };
+        if let Some(previous) = self.pending.insert(id, entry)
+            && previous.consumer != *consumer
+            && let Some(owner) = self.consumers.get_mut(&previous.consumer)
+        {
+            owner.pending.remove(&id);
+        }
+        self.consumers
+            .entry(consumer.clone())
+            .or_insert_with(|| Consumer::new(delivery_time))
+            .pending
+            .insert(id);
+    }
+
+    /// Acknowledges `id`, dropping it from the pending entries list.
+    pub fn ack(&mut self, id: StreamId) -> bool {
+        let Some(entry) = self.pending.remove(&id) else {
+            return false;
+        };
+        if let Some(owner) = self.consumers.get_mut(&entry.consumer) {
+            owner.pending.remove(&id);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!(StreamId::parse(b"5-3", 0), Some(StreamId::new(5, 3)));
+        assert_eq!(
+            StreamId::parse(b"5", u64::MAX),
+            Some(StreamId::new(5, u64::MAX))
+        );
+        assert_eq!(StreamId::parse(b"5-", 0), None);
+        assert_eq!(StreamId::parse(b"-5", 0), None);
+        assert_eq!(StreamId::parse(b"+5-1", 0), None);
+        assert_eq!(StreamId::parse(b"18446744073709551616-0", 0), None);
+        assert_eq!(StreamId::new(1, 2).to_string(), "1-2");
+    }
+
+    #[test]
+    fn test_next_and_prev() {
+        assert_eq!(StreamId::new(1, u64::MAX).next(), Some(StreamId::new(2, 0)));
+        assert_eq!(StreamId::MAX.next(), None);
+        assert_eq!(StreamId::new(2, 0).prev(), Some(StreamId::new(1, u64::MAX)));
+        assert_eq!(StreamId::MIN.prev(), None);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut stream = Stream::new();
+        for ms in 1..=5 {
+            stream.append(StreamId::new(ms, 0), vec![]);
+        }
+        stream.remove(StreamId::new(3, 0));
+        let ids = |range| stream.range(range).map(|(id, _)| id.ms).collect::<Vec<_>>();
+        assert_eq!(ids(StreamId::new(2, 0)..=StreamId::new(4, 0)), [2, 4]);
+        assert_eq!(ids(StreamId::new(4, 0)..=StreamId::new(2, 0)), [0; 0]);
+        assert_eq!(stream.len(), 4);
+        assert_eq!(stream.last_id(), StreamId::new(5, 0));
+    }
+
+    #[test]
+    fn test_nodes_and_trimming() {
+        let mut stream = Stream::new();
+        for ms in 1..=250 {
+            stream.append(StreamId::new(ms, 0), vec![]);
+        }
+        assert_eq!(stream.node_count(), 3);
+        assert_eq!(stream.get(StreamId::new(150, 0)), Some(&vec![]));
+        assert_eq!(
+            stream
+                .range(StreamId::new(99, 0)..=StreamId::new(102, 0))
+                .count(),
+            4
+        );
+
+        // Approximately, only the first node fits within the excess of 130.
+        assert_eq!(stream.trim(Trim::MaxLen(120), true, None), 100);
+        assert_eq!(stream.first_id(), StreamId::new(101, 0));
+        assert_eq!(stream.trim(Trim::MaxLen(120), true, Some(50)), 0);
+        assert_eq!(stream.trim(Trim::MaxLen(120), false, None), 30);
+        assert_eq!(stream.first_id(), StreamId::new(131, 0));
+        assert_eq!(
+            stream.trim(Trim::MinId(StreamId::new(201, 0)), true, None),
+            70
+        );
+        assert_eq!(
+            stream.trim(Trim::MinId(StreamId::new(210, 0)), false, None),
+            9
+        );
+        assert_eq!(stream.len(), 41);
+        assert_eq!(stream.entries_added(), 250);
+    }
+
+    #[test]
+    fn test_emptied_nodes_are_dropped() {
+        let mut stream = Stream::new();
+        for ms in 1..=101 {
+            stream.append(StreamId::new(ms, 0), vec![]);
+        }
+        for ms in 1..=100 {
+            assert!(stream.remove(StreamId::new(ms, 0)));
+        }
+        assert!(!stream.remove(StreamId::new(1, 0)));
+        assert_eq!(stream.node_count(), 1);
+        assert_eq!(stream.first_id(), StreamId::new(101, 0));
+        assert_eq!(stream.max_deleted_id(), StreamId::new(100, 0));
+    }
+
+    #[test]
+    fn test_lag() {
+        let mut stream = Stream::new();
+        for ms in 1..=5 {
+            stream.append(StreamId::new(ms, 0), vec![]);
+        }
+        let group = ConsumerGroup::new(StreamId::MIN, None);
+        assert_eq!(stream.lag(&group), Some(5));
+        let read = stream.entries_read_after(None, StreamId::new(1, 0));
+        assert_eq!(read, Some(1));
+        assert_eq!(
+            stream.entries_read_after(read, StreamId::new(2, 0)),
+            Some(2)
+        );
+
+        // A deletion past the group makes its position unknowable.
+        stream.remove(StreamId::new(4, 0));
+        let group = ConsumerGroup::new(StreamId::new(2, 0), Some(2));
+        assert_eq!(stream.lag(&group), None);
+        let group = ConsumerGroup::new(StreamId::new(5, 0), None);
+        assert_eq!(stream.lag(&group), Some(0));
+    }
+
+    #[test]
+    fn test_pending_entries_follow_their_consumer() {
+        let (alice, bob) = (Bytes::from("alice"), Bytes::from("bob"));
+        let mut group = ConsumerGroup::new(StreamId::MIN, None);
+        group.consumer(&alice, 10);
+        group.deliver(StreamId::new(1, 0), &alice, 10, 1);
+        group.deliver(StreamId::new(2, 0), &alice, 10, 1);
+
+        // Claiming moves an entry between consumers.
+        group.deliver(StreamId::new(1, 0), &bob, 20, 2);
+        assert_eq!(group.consumers()[&alice].pending().len(), 1);
+        assert_eq!(group.consumers()[&bob].pending().len(), 1);
+        assert_eq!(group.pending()[&StreamId::new(1, 0)].consumer, bob);
+
+        assert!(group.ack(StreamId::new(1, 0)));
+        assert!(!group.ack(StreamId::new(1, 0)));
+        assert!(group.consumers()[&bob].pending().is_empty());
+
+        assert_eq!(group.delete_consumer(b"alice"), Some(1));
+        assert!(group.pending().is_empty());
+        assert_eq!(group.delete_consumer(b"alice"), None);
+    }
+}
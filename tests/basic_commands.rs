@@ -93,3 +93,69 @@ async fn test_multiple_clients() {
         .expect("Failed to GET");
     assert_eq!(parse_bulk_string(&response), Some("data".to_string()));
 }
+
+#[tokio::test]
+async fn test_commands_are_case_insensitive() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["set", "foo", "bar"])
+        .await
+        .expect("Failed to send set");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let response = client
+        .send_array(&["GeT", "foo"])
+        .await
+        .expect("Failed to send GeT");
+    assert_eq!(parse_bulk_string(&response), Some("bar".to_string()));
+
+    let response = client
+        .send_array(&["config", "get", "DBFILENAME"])
+        .await
+        .expect("Failed to send config get");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["DBFILENAME".to_string(), "rdbfile.rdb".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn test_keys_patterns() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for key in ["user:1", "user:2", "user:10", "usr:3", "user*"] {
+        client
+            .send_array(&["SET", key, "x"])
+            .await
+            .expect("Failed to SET");
+    }
+    let keys = |response: &str| {
+        let mut keys = parse_array(response).expect("Expected an array");
+        keys.sort();
+        keys
+    };
+
+    let response = client
+        .send_array(&["KEYS", "user:?"])
+        .await
+        .expect("Failed to KEYS");
+    assert_eq!(keys(&response), ["user:1", "user:2"]);
+    let response = client
+        .send_array(&["KEYS", "us[a-e]r:*"])
+        .await
+        .expect("Failed to KEYS");
+    assert_eq!(keys(&response), ["user:1", "user:10", "user:2"]);
+    let response = client
+        .send_array(&["KEYS", "u[^s]*"])
+        .await
+        .expect("Failed to KEYS");
+    assert_eq!(keys(&response), Vec::<String>::new());
+    let response = client
+        .send_array(&["KEYS", "user\\*"])
+        .await
+        .expect("Failed to KEYS");
+    assert_eq!(keys(&response), ["user*"]);
+}
@@ -0,0 +1,437 @@
+mod common;
+
+use common::*;
+use tokio::time::{Duration, Instant, sleep, timeout};
+
+/// Sends a command whose reply isn't expected yet.
+async fn send_blocking(client: &mut TestClient, args: &[&str]) {
+    client
+        .write_raw(encode_resp_array(args).as_bytes())
+        .await
+        .expect("Failed to send blocking command");
+    // Give the server time to park the client before the test carries on.
+    sleep(Duration::from_millis(50)).await;
+}
+
+#[tokio::test]
+async fn test_blpop_returns_immediately_when_data_is_available() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "queue", "job1", "job2"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["BLPOP", "missing", "queue", "0"])
+        .await
+        .expect("Failed to BLPOP");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$4\r\njob1\r\n");
+}
+
+#[tokio::test]
+async fn test_brpop_times_out_with_null() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let started = Instant::now();
+    let response = client
+        .send_array(&["BRPOP", "queue", "0.1"])
+        .await
+        .expect("Failed to BRPOP");
+    assert_eq!(response, "*-1\r\n");
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    let response = client
+        .send_array(&["BLMOVE", "queue", "other", "LEFT", "LEFT", "0.05"])
+        .await
+        .expect("Failed to BLMOVE");
+    assert_eq!(response, "$-1\r\n");
+
+    // The connection still works after the timeout.
+    let response = client.send_array(&["PING"]).await.expect("Failed to PING");
+    assert_eq!(parse_simple_string(&response), Some("PONG"));
+}
+
+#[tokio::test]
+async fn test_push_wakes_blocked_client() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut waiter, &["BRPOP", "queue", "5"]).await;
+
+    let response = pusher
+        .send_array(&["LPUSH", "queue", "job"])
+        .await
+        .expect("Failed to LPUSH");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$3\r\njob\r\n");
+
+    let response = pusher
+        .send_array(&["LLEN", "queue"])
+        .await
+        .expect("Failed to LLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_disconnected_waiter_is_not_served() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut waiter, &["BRPOP", "queue", "0"]).await;
+    drop(waiter);
+    sleep(Duration::from_millis(50)).await;
+
+    let response = pusher
+        .send_array(&["LPUSH", "queue", "job"])
+        .await
+        .expect("Failed to LPUSH");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    // The job waits for someone who can still take it.
+    let response = pusher
+        .send_array(&["LLEN", "queue"])
+        .await
+        .expect("Failed to LLEN");
+    assert_eq!(parse_integer(&response), Some(1));
+}
+
+#[tokio::test]
+async fn test_commands_pipelined_behind_a_blocked_one_run_after_it() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut waiter, &["BRPOP", "queue", "0"]).await;
+    send_blocking(&mut waiter, &["PING"]).await;
+
+    pusher
+        .send_array(&["LPUSH", "queue", "job"])
+        .await
+        .expect("Failed to LPUSH");
+
+    let expected = "*2\r\n$5\r\nqueue\r\n$3\r\njob\r\n+PONG\r\n";
+    let response = waiter
+        .read_exact(expected.len())
+        .await
+        .expect("Failed to read");
+    assert_eq!(response, expected);
+}
+
+#[tokio::test]
+async fn test_blocked_clients_are_served_first_come_first_served() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut first = server.connect().await.expect("Failed to connect");
+    let mut second = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut first, &["BLPOP", "queue", "0"]).await;
+    send_blocking(&mut second, &["BLPOP", "queue", "0"]).await;
+
+    pusher
+        .send_array(&["RPUSH", "queue", "a"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = first.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$1\r\na\r\n");
+    assert!(
+        timeout(Duration::from_millis(100), second.read_response())
+            .await
+            .is_err()
+    );
+
+    pusher
+        .send_array(&["RPUSH", "queue", "b"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = second.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$1\r\nb\r\n");
+}
+
+#[tokio::test]
+async fn test_one_push_serves_several_waiters() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut first = server.connect().await.expect("Failed to connect");
+    let mut second = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut first, &["BLPOP", "queue", "0"]).await;
+    send_blocking(
+        &mut second,
+        &["BLMPOP", "0", "1", "queue", "LEFT", "COUNT", "5"],
+    )
+    .await;
+
+    pusher
+        .send_array(&["RPUSH", "queue", "a", "b", "c"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = first.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$1\r\na\r\n");
+    let response = second.read_response().await.expect("Failed to read");
+    assert_eq!(
+        response,
+        "*2\r\n$5\r\nqueue\r\n*2\r\n$1\r\nb\r\n$1\r\nc\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_blmove_chains_into_other_waiters() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut mover = server.connect().await.expect("Failed to connect");
+    let mut consumer = server.connect().await.expect("Failed to connect");
+    let mut pusher = server.connect().await.expect("Failed to connect");
+
+    send_blocking(&mut mover, &["BRPOPLPUSH", "incoming", "processing", "0"]).await;
+    send_blocking(&mut consumer, &["BLPOP", "processing", "0"]).await;
+
+    pusher
+        .send_array(&["RPUSH", "incoming", "job"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = mover.read_response().await.expect("Failed to read");
+    assert_eq!(parse_bulk_string(&response), Some("job".to_string()));
+    let response = consumer.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$10\r\nprocessing\r\n$3\r\njob\r\n");
+}
+
+#[tokio::test]
+async fn test_blocking_timeout_errors() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["BLPOP", "queue", "-1"])
+        .await
+        .expect("Failed to BLPOP");
+    assert_eq!(response, "-ERR timeout is negative\r\n");
+
+    let response = client
+        .send_array(&["BLPOP", "queue", "soon"])
+        .await
+        .expect("Failed to BLPOP");
+    assert_eq!(response, "-ERR timeout is not a float or out of range\r\n");
+
+    for command in ["BLPOP", "BZPOPMIN"] {
+        let response = client
+            .send_array(&[command, "queue", "1e300"])
+            .await
+            .expect("Failed to send blocking command");
+        assert_eq!(response, "-ERR timeout is out of range\r\n");
+    }
+    let response = client.send_array(&["PING"]).await.expect("Failed to PING");
+    assert_eq!(parse_simple_string(&response), Some("PONG"));
+}
+
+#[tokio::test]
+async fn test_bzpopmin_and_bzmpop() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    writer
+        .send_array(&["ZADD", "ready", "2", "b", "1", "a"])
+        .await
+        .expect("Failed to ZADD");
+    let response = waiter
+        .send_array(&["BZPOPMIN", "missing", "ready", "0"])
+        .await
+        .expect("Failed to BZPOPMIN");
+    assert_eq!(response, "*3\r\n$5\r\nready\r\n$1\r\na\r\n$1\r\n1\r\n");
+
+    send_blocking(&mut waiter, &["BZPOPMAX", "board", "5"]).await;
+    writer
+        .send_array(&["ZADD", "board", "1", "low", "9", "high"])
+        .await
+        .expect("Failed to ZADD");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*3\r\n$5\r\nboard\r\n$4\r\nhigh\r\n$1\r\n9\r\n");
+
+    send_blocking(
+        &mut waiter,
+        &["BZMPOP", "5", "2", "none", "jobs", "MIN", "COUNT", "5"],
+    )
+    .await;
+    writer
+        .send_array(&["ZUNIONSTORE", "jobs", "1", "board"])
+        .await
+        .expect("Failed to ZUNIONSTORE");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(
+        response,
+        "*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\nlow\r\n$1\r\n1\r\n"
+    );
+
+    let response = waiter
+        .send_array(&["BZPOPMIN", "board", "jobs", "0.05"])
+        .await
+        .expect("Failed to BZPOPMIN");
+    assert_eq!(response, "*3\r\n$5\r\nboard\r\n$3\r\nlow\r\n$1\r\n1\r\n");
+    let response = waiter
+        .send_array(&["BZPOPMIN", "board", "0.05"])
+        .await
+        .expect("Failed to BZPOPMIN");
+    assert_eq!(response, "*-1\r\n");
+}
+
+#[tokio::test]
+async fn test_xread_block_waits_for_new_entries() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    writer
+        .send_array(&["XADD", "feed", "1-0", "n", "old"])
+        .await
+        .expect("Failed to XADD");
+    send_blocking(
+        &mut waiter,
+        &["XREAD", "BLOCK", "0", "STREAMS", "feed", "$"],
+    )
+    .await;
+    writer
+        .send_array(&["XADD", "feed", "2-0", "n", "new"])
+        .await
+        .expect("Failed to XADD");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\nfeed\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$1\r\nn\r\n$3\r\nnew\r\n"
+    );
+
+    let started = Instant::now();
+    let response = waiter
+        .send_array(&["XREAD", "BLOCK", "50", "STREAMS", "feed", "+"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\nfeed\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$1\r\nn\r\n$3\r\nnew\r\n"
+    );
+    let response = waiter
+        .send_array(&["XREAD", "BLOCK", "50", "STREAMS", "feed", "$"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(response, "*-1\r\n");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_xread_waiter_still_blocked_does_not_hold_up_others() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut ahead = server.connect().await.expect("Failed to connect");
+    let mut behind = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    send_blocking(
+        &mut ahead,
+        &["XREAD", "BLOCK", "0", "STREAMS", "feed", "9999999999999-0"],
+    )
+    .await;
+    send_blocking(
+        &mut behind,
+        &["XREAD", "BLOCK", "0", "STREAMS", "feed", "$"],
+    )
+    .await;
+    writer
+        .send_array(&["XADD", "feed", "1-0", "n", "new"])
+        .await
+        .expect("Failed to XADD");
+
+    let response = timeout(Duration::from_secs(1), behind.read_response())
+        .await
+        .expect("XREAD behind a blocked one was never served")
+        .expect("Failed to read");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\nfeed\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\nn\r\n$3\r\nnew\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_xreadgroup_block_waits_for_new_entries() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    writer
+        .send_array(&["XGROUP", "CREATE", "feed", "readers", "$", "MKSTREAM"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    send_blocking(
+        &mut waiter,
+        &[
+            "XREADGROUP",
+            "GROUP",
+            "readers",
+            "alice",
+            "BLOCK",
+            "0",
+            "STREAMS",
+            "feed",
+            ">",
+        ],
+    )
+    .await;
+    writer
+        .send_array(&["XADD", "feed", "1-0", "n", "new"])
+        .await
+        .expect("Failed to XADD");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\nfeed\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\nn\r\n$3\r\nnew\r\n"
+    );
+    let response = writer
+        .send_array(&["XPENDING", "feed", "readers"])
+        .await
+        .expect("Failed to XPENDING");
+    assert!(response.starts_with("*4\r\n:1\r\n"));
+
+    // Destroying the group wakes its readers with an error.
+    send_blocking(
+        &mut waiter,
+        &[
+            "XREADGROUP",
+            "GROUP",
+            "readers",
+            "alice",
+            "BLOCK",
+            "0",
+            "STREAMS",
+            "feed",
+            ">",
+        ],
+    )
+    .await;
+    writer
+        .send_array(&["XGROUP", "DESTROY", "feed", "readers"])
+        .await
+        .expect("Failed to XGROUP DESTROY");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert!(response.starts_with("-NOGROUP"));
+}
+
+#[tokio::test]
+async fn test_rename_onto_a_watched_list_wakes_waiters() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut waiter = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    writer
+        .send_array(&["RPUSH", "staging", "job"])
+        .await
+        .expect("Failed to RPUSH");
+    send_blocking(&mut waiter, &["BLPOP", "queue", "5"]).await;
+    writer
+        .send_array(&["RENAME", "staging", "queue"])
+        .await
+        .expect("Failed to RENAME");
+    let response = waiter.read_response().await.expect("Failed to read");
+    assert_eq!(response, "*2\r\n$5\r\nqueue\r\n$3\r\njob\r\n");
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn test_command_count_and_info() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["COMMAND", "COUNT"])
+        .await
+        .expect("Failed to send COMMAND COUNT");
+    assert!(parse_integer(&response).is_some_and(|count| count > 0));
+
+    let response = client
+        .send_array(&["COMMAND", "INFO", "get", "nosuchcommand"])
+        .await
+        .expect("Failed to send COMMAND INFO");
+    assert!(response.starts_with(
+        "*2\r\n*10\r\n$3\r\nget\r\n:2\r\n*2\r\n+readonly\r\n+fast\r\n:1\r\n:1\r\n:1\r\n"
+    ));
+    assert!(response.ends_with("*0\r\n$-1\r\n"));
+}
+
+#[tokio::test]
+async fn test_command_getkeys() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["COMMAND", "GETKEYS", "SET", "mykey", "value"])
+        .await
+        .expect("Failed to send COMMAND GETKEYS");
+    assert_eq!(parse_array(&response), Some(vec!["mykey".to_string()]));
+
+    let response = client
+        .send_array(&["COMMAND", "GETKEYS", "PING", "hello"])
+        .await
+        .expect("Failed to send COMMAND GETKEYS");
+    assert_eq!(response, "-ERR The command has no key arguments\r\n");
+
+    let response = client
+        .send_array(&["COMMAND", "GETKEYS", "NOPE", "x"])
+        .await
+        .expect("Failed to send COMMAND GETKEYS");
+    assert_eq!(response, "-ERR Invalid command specified\r\n");
+}
+
+#[tokio::test]
+async fn test_command_list_filters() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["COMMAND", "LIST", "FILTERBY", "ACLCAT", "list"])
+        .await
+        .expect("Failed to send COMMAND LIST");
+    let names = parse_array(&response).expect("Failed to parse array");
+    assert!(names.contains(&"rpush".to_string()));
+    assert!(!names.contains(&"get".to_string()));
+
+    let response = client
+        .send_array(&["COMMAND", "LIST", "FILTERBY", "PATTERN", "config*"])
+        .await
+        .expect("Failed to send COMMAND LIST");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["config".to_string(), "config|get".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn test_command_docs_on_resp3() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HELLO", "3"])
+        .await
+        .expect("Failed to send HELLO");
+    let response = client
+        .send_array(&["COMMAND", "DOCS", "get"])
+        .await
+        .expect("Failed to send COMMAND DOCS");
+    assert_eq!(
+        response,
+        "%1\r\n$3\r\nget\r\n%2\r\n$7\r\nsummary\r\n$34\r\nReturns the string value of a key.\r\n$5\r\ngroup\r\n$6\r\nstring\r\n"
+    );
+}
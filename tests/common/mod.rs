@@ -78,13 +78,32 @@ impl TestClient {
     pub async fn send_command(&mut self, cmd: &[u8]) -> anyhow::Result<String> {
         self.stream.write_all(cmd).await?;
         self.stream.flush().await?;
+        self.read_response().await
+    }
 
+    /// Read whatever the server sends next, e.g. the reply to a blocked command
+    pub async fn read_response(&mut self) -> anyhow::Result<String> {
         let mut buffer = vec![0u8; 4096];
         let n = self.stream.read(&mut buffer).await?;
 
         Ok(String::from_utf8_lossy(&buffer[..n]).to_string())
     }
 
+    /// Write raw bytes without waiting for a response
+    pub async fn write_raw(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.stream.write_all(bytes).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Read exactly `len` bytes, across as many socket reads as needed
+    pub async fn read_exact(&mut self, len: usize) -> anyhow::Result<String> {
+        let mut buffer = vec![0u8; len];
+        self.stream.read_exact(&mut buffer).await?;
+
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
+
     /// Helper to send RESP array commands
     pub async fn send_array(&mut self, args: &[&str]) -> anyhow::Result<String> {
         let cmd = encode_resp_array(args);
@@ -103,20 +122,16 @@ pub fn encode_resp_array(args: &[&str]) -> String {
 
 /// Parse a simple RESP string response
 pub fn parse_simple_string(resp: &str) -> Option<&str> {
-    if resp.starts_with('+') {
-        Some(resp[1..].trim_end_matches("\r\n"))
-    } else {
-        None
-    }
+    resp.strip_prefix('+')
+        .map(|rest| rest.trim_end_matches("\r\n"))
 }
 
 /// Parse a RESP integer response
 pub fn parse_integer(resp: &str) -> Option<i64> {
-    if resp.starts_with(':') {
-        resp[1..].trim_end_matches("\r\n").parse().ok()
-    } else {
-        None
-    }
+    resp.strip_prefix(':')?
+        .trim_end_matches("\r\n")
+        .parse()
+        .ok()
 }
 
 /// Parse a RESP bulk string response
@@ -160,3 +175,11 @@ pub fn parse_array(resp: &str) -> Option<Vec<String>> {
         None
     }
 }
+
+/// Split a SCAN-family reply into the next cursor and the items returned
+pub fn parse_scan_reply(resp: &str) -> (String, Vec<String>) {
+    let lines: Vec<&str> = resp.split("\r\n").collect();
+    assert_eq!(lines[0], "*2", "Unexpected SCAN reply {resp:?}");
+    let items = parse_array(&lines[3..].join("\r\n")).expect("Expected an array of items");
+    (lines[2].to_string(), items)
+}
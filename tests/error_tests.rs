@@ -45,3 +45,118 @@ async fn test_wrong_type_operation() {
         .expect("Failed to RPUSH");
     assert!(response.starts_with("-WRONGTYPE") || response.starts_with("-ERR"));
 }
+
+#[tokio::test]
+async fn test_error_reply_carries_full_message() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SET", "key"])
+        .await
+        .expect("Failed to send SET");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'set' command\r\n"
+    );
+
+    let response = client
+        .send_array(&["FOO", "bar", "baz"])
+        .await
+        .expect("Failed to send command");
+    assert_eq!(
+        response,
+        "-ERR unknown command 'FOO', with args beginning with: 'bar' 'baz' \r\n"
+    );
+
+    client
+        .send_array(&["SET", "mykey", "string_value"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["RPUSH", "mykey", "element"])
+        .await
+        .expect("Failed to RPUSH");
+    assert_eq!(
+        response,
+        "-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_connection_survives_command_error() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let mut pipeline = encode_resp_array(&["NOPE"]);
+    pipeline.push_str(&encode_resp_array(&["PING"]));
+    client
+        .write_raw(pipeline.as_bytes())
+        .await
+        .expect("Failed to write pipeline");
+
+    let expected = "-ERR unknown command 'NOPE', with args beginning with: \r\n+PONG\r\n";
+    let response = client
+        .read_exact(expected.len())
+        .await
+        .expect("Failed to read responses");
+    assert_eq!(response, expected);
+}
+
+#[tokio::test]
+async fn test_connection_survives_protocol_error() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_command(b"*1\r\n$abc\r\n")
+        .await
+        .expect("Failed to send malformed frame");
+    assert_eq!(response, "-ERR Protocol error: invalid bulk length\r\n");
+
+    let response = client
+        .send_array(&["PING"])
+        .await
+        .expect("Failed to send PING");
+    assert_eq!(parse_simple_string(&response), Some("PONG"));
+}
+
+#[tokio::test]
+async fn test_arity_is_checked_before_dispatch() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["ECHO"])
+        .await
+        .expect("Failed to send ECHO");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'echo' command\r\n"
+    );
+
+    let response = client
+        .send_array(&["get", "a", "b"])
+        .await
+        .expect("Failed to send GET");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'get' command\r\n"
+    );
+
+    let response = client
+        .send_array(&["CONFIG", "GET"])
+        .await
+        .expect("Failed to send CONFIG GET");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'config|get' command\r\n"
+    );
+
+    // The connection is still usable afterwards.
+    let response = client
+        .send_array(&["PING"])
+        .await
+        .expect("Failed to send PING");
+    assert_eq!(parse_simple_string(&response), Some("PONG"));
+}
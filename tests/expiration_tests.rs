@@ -1,7 +1,7 @@
 mod common;
 
 use common::*;
-use tokio::time::{sleep, Duration};
+use tokio::time::{Duration, sleep};
 
 #[tokio::test]
 async fn test_set_with_expiry_px() {
@@ -57,3 +57,99 @@ async fn test_set_with_expiry_ex() {
         .expect("Failed to GET");
     assert!(response.starts_with("$-1"));
 }
+
+#[tokio::test]
+async fn test_keys_skips_expired_keys() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "session:short", "x", "PX", "50"])
+        .await
+        .expect("Failed to SET");
+    client
+        .send_array(&["SET", "session:long", "x"])
+        .await
+        .expect("Failed to SET");
+    sleep(Duration::from_millis(100)).await;
+
+    let response = client
+        .send_array(&["KEYS", "session:*"])
+        .await
+        .expect("Failed to KEYS");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["session:long".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn test_scan_skips_expired_keys() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "session:short", "x", "PX", "50"])
+        .await
+        .expect("Failed to SET");
+    client
+        .send_array(&["SET", "session:long", "x"])
+        .await
+        .expect("Failed to SET");
+    sleep(Duration::from_millis(100)).await;
+
+    let response = client
+        .send_array(&["SCAN", "0", "MATCH", "session:*", "COUNT", "100"])
+        .await
+        .expect("Failed to SCAN");
+    assert_eq!(response, "*2\r\n$1\r\n0\r\n*1\r\n$12\r\nsession:long\r\n");
+}
+
+#[tokio::test]
+async fn test_keyspace_commands_treat_expired_keys_as_absent() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "gone", "x", "PX", "50"])
+        .await
+        .expect("Failed to SET");
+    client
+        .send_array(&["SET", "kept", "x"])
+        .await
+        .expect("Failed to SET");
+    sleep(Duration::from_millis(100)).await;
+
+    let response = client
+        .send_array(&["EXISTS", "gone", "kept"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(1));
+    // DBSIZE counts expired keys until something removes them, as EXISTS
+    // just did; this leaves "banana" from the RDB file and "kept".
+    let response = client
+        .send_array(&["DBSIZE"])
+        .await
+        .expect("Failed to DBSIZE");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["TYPE", "gone"])
+        .await
+        .expect("Failed to TYPE");
+    assert_eq!(parse_simple_string(&response), Some("none"));
+    let response = client
+        .send_array(&["RENAME", "gone", "other"])
+        .await
+        .expect("Failed to RENAME");
+    assert_eq!(response, "-ERR no such key\r\n");
+    let response = client
+        .send_array(&["COPY", "gone", "other"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["DEL", "gone"])
+        .await
+        .expect("Failed to DEL");
+    assert_eq!(parse_integer(&response), Some(0));
+}
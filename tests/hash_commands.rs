@@ -0,0 +1,372 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+
+fn sorted(mut items: Vec<String>) -> Vec<String> {
+    items.sort();
+    items
+}
+
+#[tokio::test]
+async fn test_hset_hget_and_hdel() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["HSET", "user:1", "name", "ada", "lang", "en"])
+        .await
+        .expect("Failed to HSET");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["HSET", "user:1", "lang", "fr", "age", "36"])
+        .await
+        .expect("Failed to HSET");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = client
+        .send_array(&["HGET", "user:1", "lang"])
+        .await
+        .expect("Failed to HGET");
+    assert_eq!(parse_bulk_string(&response), Some("fr".to_string()));
+    let response = client
+        .send_array(&["HMGET", "user:1", "name", "missing"])
+        .await
+        .expect("Failed to HMGET");
+    assert_eq!(response, "*2\r\n$3\r\nada\r\n$-1\r\n");
+
+    let response = client
+        .send_array(&["HSETNX", "user:1", "name", "bob"])
+        .await
+        .expect("Failed to HSETNX");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["HEXISTS", "user:1", "age"])
+        .await
+        .expect("Failed to HEXISTS");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["HSTRLEN", "user:1", "name"])
+        .await
+        .expect("Failed to HSTRLEN");
+    assert_eq!(parse_integer(&response), Some(3));
+
+    let response = client
+        .send_array(&["HDEL", "user:1", "name", "lang", "nope"])
+        .await
+        .expect("Failed to HDEL");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["HDEL", "user:1", "age"])
+        .await
+        .expect("Failed to HDEL");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    // Deleting the last field deletes the key.
+    let response = client
+        .send_array(&["HLEN", "user:1"])
+        .await
+        .expect("Failed to HLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["SET", "user:1", "v", "XX"])
+        .await
+        .expect("Failed to SET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_hkeys_hvals_and_hgetall() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "a", "1", "b", "2"])
+        .await
+        .expect("Failed to HSET");
+
+    let response = client
+        .send_array(&["HKEYS", "h"])
+        .await
+        .expect("Failed to HKEYS");
+    assert_eq!(
+        sorted(parse_array(&response).unwrap()),
+        vec!["a".to_string(), "b".to_string()]
+    );
+    let response = client
+        .send_array(&["HVALS", "h"])
+        .await
+        .expect("Failed to HVALS");
+    assert_eq!(
+        sorted(parse_array(&response).unwrap()),
+        vec!["1".to_string(), "2".to_string()]
+    );
+
+    let response = client
+        .send_array(&["HGETALL", "h"])
+        .await
+        .expect("Failed to HGETALL");
+    let flat = parse_array(&response).unwrap();
+    let mut pairs = flat
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    assert_eq!(
+        pairs,
+        vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string())
+        ]
+    );
+
+    client
+        .send_array(&["HELLO", "3"])
+        .await
+        .expect("Failed to HELLO");
+    let response = client
+        .send_array(&["HGETALL", "h"])
+        .await
+        .expect("Failed to HGETALL");
+    assert!(response.starts_with("%2\r\n"));
+
+    let response = client
+        .send_array(&["HGETALL", "missing"])
+        .await
+        .expect("Failed to HGETALL");
+    assert_eq!(response, "%0\r\n");
+}
+
+#[tokio::test]
+async fn test_hincrby_and_hincrbyfloat() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["HINCRBY", "stats", "hits", "5"])
+        .await
+        .expect("Failed to HINCRBY");
+    assert_eq!(parse_integer(&response), Some(5));
+    let response = client
+        .send_array(&["HINCRBY", "stats", "hits", "-7"])
+        .await
+        .expect("Failed to HINCRBY");
+    assert_eq!(parse_integer(&response), Some(-2));
+
+    let response = client
+        .send_array(&["HINCRBYFLOAT", "stats", "ratio", "10.5"])
+        .await
+        .expect("Failed to HINCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("10.5".to_string()));
+    let response = client
+        .send_array(&["HINCRBYFLOAT", "stats", "ratio", "0.1"])
+        .await
+        .expect("Failed to HINCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("10.6".to_string()));
+    let response = client
+        .send_array(&["HINCRBYFLOAT", "stats", "ratio", "0.2"])
+        .await
+        .expect("Failed to HINCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("10.8".to_string()));
+
+    let response = client
+        .send_array(&["HINCRBY", "stats", "ratio", "1"])
+        .await
+        .expect("Failed to HINCRBY");
+    assert_eq!(response, "-ERR hash value is not an integer\r\n");
+
+    client
+        .send_array(&["HSET", "stats", "name", "x"])
+        .await
+        .expect("Failed to HSET");
+    let response = client
+        .send_array(&["HINCRBYFLOAT", "stats", "name", "1"])
+        .await
+        .expect("Failed to HINCRBYFLOAT");
+    assert_eq!(response, "-ERR hash value is not a float\r\n");
+
+    // A failed increment on a missing key doesn't leave an empty hash behind.
+    let response = client
+        .send_array(&["HINCRBYFLOAT", "newh", "f", "inf"])
+        .await
+        .expect("Failed to HINCRBYFLOAT");
+    assert_eq!(response, "-ERR increment would produce NaN or Infinity\r\n");
+    let response = client
+        .send_array(&["EXISTS", "newh"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_hrandfield() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "a", "1", "b", "2", "c", "3"])
+        .await
+        .expect("Failed to HSET");
+
+    let response = client
+        .send_array(&["HRANDFIELD", "h"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    let field = parse_bulk_string(&response).unwrap();
+    assert!(["a", "b", "c"].contains(&field.as_str()));
+
+    let response = client
+        .send_array(&["HRANDFIELD", "h", "10"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    assert_eq!(
+        sorted(parse_array(&response).unwrap()),
+        vec!["a".to_string(), "b".to_string(), "c".to_string()]
+    );
+
+    let response = client
+        .send_array(&["HRANDFIELD", "h", "-5"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    assert_eq!(parse_array(&response).unwrap().len(), 5);
+
+    let response = client
+        .send_array(&["HRANDFIELD", "h", "2", "WITHVALUES"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    let flat = parse_array(&response).unwrap();
+    assert_eq!(flat.len(), 4);
+    for pair in flat.chunks(2) {
+        let expected = match pair[0].as_str() {
+            "a" => "1",
+            "b" => "2",
+            _ => "3",
+        };
+        assert_eq!(pair[1], expected);
+    }
+
+    // Huge counts are clamped or refused rather than taking the server down.
+    let response = client
+        .send_array(&["HRANDFIELD", "h", "9223372036854775807"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    assert_eq!(parse_array(&response).unwrap().len(), 3);
+    let response = client
+        .send_array(&["HRANDFIELD", "h", "-9223372036854775807"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    assert_eq!(response, "-ERR value is out of range\r\n");
+
+    let response = client
+        .send_array(&["HRANDFIELD", "missing"])
+        .await
+        .expect("Failed to HRANDFIELD");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_hscan_and_wrongtype() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "name", "ada", "nick", "a", "age", "36"])
+        .await
+        .expect("Failed to HSET");
+
+    let response = client
+        .send_array(&["HSCAN", "h", "0", "MATCH", "n*"])
+        .await
+        .expect("Failed to HSCAN");
+    assert!(response.starts_with("*2\r\n$1\r\n0\r\n*4\r\n"));
+    assert!(response.contains("$4\r\nname\r\n$3\r\nada\r\n"));
+    assert!(response.contains("$4\r\nnick\r\n$1\r\na\r\n"));
+
+    let response = client
+        .send_array(&["HSCAN", "h", "abc"])
+        .await
+        .expect("Failed to HSCAN");
+    assert_eq!(response, "-ERR invalid cursor\r\n");
+
+    client
+        .send_array(&["SET", "s", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["HGET", "s", "f"])
+        .await
+        .expect("Failed to HGET");
+    assert!(response.starts_with("-WRONGTYPE"));
+    let response = client
+        .send_array(&["HSET", "s", "f", "v"])
+        .await
+        .expect("Failed to HSET");
+    assert!(response.starts_with("-WRONGTYPE"));
+    let response = client
+        .send_array(&["GET", "h"])
+        .await
+        .expect("Failed to GET");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_hscan_walks_large_hashes_with_a_cursor() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let mut args = vec!["HSET".to_string(), "big".to_string()];
+    for n in 0..300 {
+        args.extend([format!("f{n}"), format!("v{n}")]);
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    client.send_array(&args).await.expect("Failed to HSET");
+
+    let mut fields = HashMap::new();
+    let mut cursor = "0".to_string();
+    let mut pages = 0;
+    loop {
+        let response = client
+            .send_array(&["HSCAN", "big", &cursor, "COUNT", "20"])
+            .await
+            .expect("Failed to HSCAN");
+        let (next, items) = parse_scan_reply(&response);
+        for pair in items.chunks(2) {
+            fields.insert(pair[0].clone(), pair[1].clone());
+        }
+        pages += 1;
+        if next == "0" {
+            break;
+        }
+        cursor = next;
+    }
+    assert!(pages > 1);
+    assert_eq!(fields.len(), 300);
+    assert!((0..300).all(|n| fields.get(&format!("f{n}")) == Some(&format!("v{n}"))));
+}
+
+#[tokio::test]
+async fn test_hscan_novalues() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "name", "ada", "age", "36"])
+        .await
+        .expect("Failed to HSET");
+    let response = client
+        .send_array(&["HSCAN", "h", "0", "NOVALUES", "MATCH", "n*"])
+        .await
+        .expect("Failed to HSCAN");
+    assert_eq!(response, "*2\r\n$1\r\n0\r\n*1\r\n$4\r\nname\r\n");
+
+    // NOVALUES is only an HSCAN option.
+    client
+        .send_array(&["SADD", "s", "a"])
+        .await
+        .expect("Failed to SADD");
+    let response = client
+        .send_array(&["SSCAN", "s", "0", "NOVALUES"])
+        .await
+        .expect("Failed to SSCAN");
+    assert_eq!(response, "-ERR syntax error\r\n");
+}
@@ -0,0 +1,277 @@
+mod common;
+
+use common::*;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_hexpire_and_httl() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "a", "1", "b", "2", "c", "3"])
+        .await
+        .expect("Failed to HSET");
+
+    let response = client
+        .send_array(&["HEXPIRE", "h", "100", "FIELDS", "2", "a", "missing"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(response, "*2\r\n:1\r\n:-2\r\n");
+    let response = client
+        .send_array(&["HEXPIRE", "h", "200", "NX", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HEXPIRE NX");
+    assert_eq!(response, "*2\r\n:0\r\n:1\r\n");
+    let response = client
+        .send_array(&["HEXPIRE", "h", "150", "GT", "FIELDS", "3", "a", "b", "c"])
+        .await
+        .expect("Failed to HEXPIRE GT");
+    assert_eq!(response, "*3\r\n:1\r\n:0\r\n:0\r\n");
+    let response = client
+        .send_array(&["HEXPIRE", "h", "50", "LT", "FIELDS", "1", "c"])
+        .await
+        .expect("Failed to HEXPIRE LT");
+    assert_eq!(response, "*1\r\n:1\r\n");
+
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "4", "a", "b", "c", "missing"])
+        .await
+        .expect("Failed to HTTL");
+    assert_eq!(response, "*4\r\n:150\r\n:200\r\n:50\r\n:-2\r\n");
+    let response = client
+        .send_array(&["HPTTL", "h", "FIELDS", "1", "c"])
+        .await
+        .expect("Failed to HPTTL");
+    let ttl = response
+        .strip_prefix("*1\r\n:")
+        .and_then(|rest| rest.trim_end().parse::<i64>().ok())
+        .expect("Expected one integer");
+    assert!(ttl > 49_000 && ttl <= 50_000);
+
+    let response = client
+        .send_array(&["HPEXPIREAT", "h", "4102444800000", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HPEXPIREAT");
+    assert_eq!(response, "*1\r\n:1\r\n");
+    let response = client
+        .send_array(&["HEXPIRETIME", "h", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HEXPIRETIME");
+    assert_eq!(response, "*1\r\n:4102444800\r\n");
+    let response = client
+        .send_array(&["HPEXPIRETIME", "h", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HPEXPIRETIME");
+    assert_eq!(response, "*1\r\n:4102444800000\r\n");
+
+    let response = client
+        .send_array(&["HPERSIST", "h", "FIELDS", "2", "a", "missing"])
+        .await
+        .expect("Failed to HPERSIST");
+    assert_eq!(response, "*2\r\n:1\r\n:-2\r\n");
+    let response = client
+        .send_array(&["HPERSIST", "h", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HPERSIST");
+    assert_eq!(response, "*1\r\n:-1\r\n");
+
+    // HSET overwrites clear the TTL; missing keys report -2 for every field.
+    client
+        .send_array(&["HSET", "h", "b", "20"])
+        .await
+        .expect("Failed to HSET");
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "1", "b"])
+        .await
+        .expect("Failed to HTTL");
+    assert_eq!(response, "*1\r\n:-1\r\n");
+    let response = client
+        .send_array(&["HEXPIRE", "nokey", "10", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(response, "*2\r\n:-2\r\n:-2\r\n");
+}
+
+#[tokio::test]
+async fn test_fields_expire_lazily_and_in_the_background() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "a", "1", "b", "2"])
+        .await
+        .expect("Failed to HSET");
+    let response = client
+        .send_array(&["HPEXPIRE", "h", "50", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HPEXPIRE");
+    assert_eq!(response, "*1\r\n:1\r\n");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let response = client
+        .send_array(&["HGETALL", "h"])
+        .await
+        .expect("Failed to HGETALL");
+    assert_eq!(response, "*2\r\n$1\r\nb\r\n$1\r\n2\r\n");
+    let response = client
+        .send_array(&["HLEN", "h"])
+        .await
+        .expect("Failed to HLEN");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    // Once the last field expires the key is gone, even if nobody reads it.
+    client
+        .send_array(&["HSET", "gone", "x", "1"])
+        .await
+        .expect("Failed to HSET");
+    client
+        .send_array(&["HPEXPIRE", "gone", "50", "FIELDS", "1", "x"])
+        .await
+        .expect("Failed to HPEXPIRE");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let response = client
+        .send_array(&["KEYS", "*"])
+        .await
+        .expect("Failed to KEYS");
+    let keys = parse_array(&response).expect("Expected an array");
+    assert!(keys.contains(&"h".to_string()));
+    assert!(!keys.contains(&"gone".to_string()));
+
+    // A time in the past deletes the field right away.
+    let response = client
+        .send_array(&["HEXPIRE", "h", "0", "FIELDS", "1", "b"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(response, "*1\r\n:2\r\n");
+    let response = client
+        .send_array(&["HLEN", "h"])
+        .await
+        .expect("Failed to HLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_hgetex_and_hsetex() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&[
+            "HSETEX", "h", "EX", "100", "FIELDS", "2", "a", "1", "b", "2",
+        ])
+        .await
+        .expect("Failed to HSETEX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HTTL");
+    assert_eq!(response, "*2\r\n:100\r\n:100\r\n");
+
+    let response = client
+        .send_array(&["HSETEX", "h", "FNX", "FIELDS", "2", "a", "9", "c", "3"])
+        .await
+        .expect("Failed to HSETEX FNX");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["HSETEX", "h", "FXX", "KEEPTTL", "FIELDS", "1", "a", "10"])
+        .await
+        .expect("Failed to HSETEX FXX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["HSETEX", "h", "FIELDS", "1", "b", "20"])
+        .await
+        .expect("Failed to HSETEX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HTTL");
+    assert_eq!(response, "*2\r\n:100\r\n:-1\r\n");
+
+    let response = client
+        .send_array(&["HGETEX", "h", "PERSIST", "FIELDS", "2", "a", "missing"])
+        .await
+        .expect("Failed to HGETEX PERSIST");
+    assert_eq!(response, "*2\r\n$2\r\n10\r\n$-1\r\n");
+    let response = client
+        .send_array(&["HGETEX", "h", "PX", "5000", "FIELDS", "1", "b"])
+        .await
+        .expect("Failed to HGETEX PX");
+    assert_eq!(response, "*1\r\n$2\r\n20\r\n");
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HTTL");
+    assert_eq!(response, "*2\r\n:-1\r\n:5\r\n");
+
+    // A deadline in the past still returns the values, then deletes them.
+    let response = client
+        .send_array(&["HGETEX", "h", "EXAT", "1", "FIELDS", "2", "a", "b"])
+        .await
+        .expect("Failed to HGETEX EXAT");
+    assert_eq!(response, "*2\r\n$2\r\n10\r\n$2\r\n20\r\n");
+    let response = client
+        .send_array(&["HGET", "h", "a"])
+        .await
+        .expect("Failed to HGET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_field_ttl_errors() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HSET", "h", "a", "1"])
+        .await
+        .expect("Failed to HSET");
+    let response = client
+        .send_array(&["HEXPIRE", "h", "10", "FIELDS", "2", "a"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(
+        response,
+        "-ERR The `numfields` parameter must match the number of arguments\r\n"
+    );
+    let response = client
+        .send_array(&["HEXPIRE", "h", "10", "FIELDS", "0", "a"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(
+        response,
+        "-ERR Parameter `numFields` should be greater than 0\r\n"
+    );
+    let response = client
+        .send_array(&["HEXPIRE", "h", "10", "NOPE", "1", "a"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(
+        response,
+        "-ERR Mandatory argument FIELDS is missing or not at the right position\r\n"
+    );
+    let response = client
+        .send_array(&["HEXPIRE", "h", "-1", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HEXPIRE");
+    assert_eq!(
+        response,
+        "-ERR invalid expire time, must be >= 0 and <= 281474976710655\r\n"
+    );
+    let response = client
+        .send_array(&["HTTL", "h", "FIELDS", "1"])
+        .await
+        .expect("Failed to HTTL");
+    assert!(response.starts_with("-ERR"));
+
+    client
+        .send_array(&["SET", "s", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["HTTL", "s", "FIELDS", "1", "a"])
+        .await
+        .expect("Failed to HTTL");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
@@ -0,0 +1,423 @@
+mod common;
+
+use common::*;
+use std::collections::HashSet;
+use tokio::time::{Duration, sleep};
+
+/// Runs SCAN from cursor 0 to the end.
+async fn scan_all(client: &mut TestClient, options: &[&str]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut cursor = "0".to_string();
+    loop {
+        let mut args = vec!["SCAN", &cursor];
+        args.extend_from_slice(options);
+        let response = client.send_array(&args).await.expect("Failed to SCAN");
+        let (next, keys) = parse_scan_reply(&response);
+        seen.extend(keys);
+        if next == "0" {
+            return seen;
+        }
+        cursor = next;
+    }
+}
+
+#[tokio::test]
+async fn test_scan_returns_every_key() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for n in 0..200 {
+        client
+            .send_array(&["SET", &format!("key:{n}"), "x"])
+            .await
+            .expect("Failed to SET");
+    }
+    let seen = scan_all(&mut client, &["COUNT", "20"]).await;
+    // The test server also loads "banana" from its RDB file.
+    assert_eq!(seen.len(), 201);
+    assert!((0..200).all(|n| seen.contains(&format!("key:{n}"))));
+}
+
+#[tokio::test]
+async fn test_scan_keeps_its_place_while_the_keyspace_grows() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    let mut writer = server.connect().await.expect("Failed to connect");
+
+    for n in 0..50 {
+        client
+            .send_array(&["SET", &format!("old:{n}"), "x"])
+            .await
+            .expect("Failed to SET");
+    }
+    // Keep adding keys between pages so the table grows mid-iteration.
+    let mut seen = HashSet::new();
+    let mut cursor = "0".to_string();
+    let mut next = 0;
+    loop {
+        let response = client
+            .send_array(&["SCAN", &cursor, "COUNT", "5"])
+            .await
+            .expect("Failed to SCAN");
+        let (page_cursor, keys) = parse_scan_reply(&response);
+        seen.extend(keys);
+        if page_cursor == "0" {
+            break;
+        }
+        cursor = page_cursor;
+        for _ in 0..5 {
+            writer
+                .send_array(&["SET", &format!("new:{next}"), "x"])
+                .await
+                .expect("Failed to SET");
+            next += 1;
+        }
+    }
+    assert!((0..50).all(|n| seen.contains(&format!("old:{n}"))));
+}
+
+#[tokio::test]
+async fn test_scan_match_and_type() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for args in [
+        &["SET", "user:1", "x"][..],
+        &["RPUSH", "user:2", "x"],
+        &["HSET", "user:3", "f", "x"],
+        &["SET", "other", "x"],
+    ] {
+        client.send_array(args).await.expect("Failed to write");
+    }
+    let sorted = |seen: HashSet<String>| {
+        let mut keys: Vec<String> = seen.into_iter().collect();
+        keys.sort();
+        keys
+    };
+
+    let seen = scan_all(&mut client, &["MATCH", "user:*"]).await;
+    assert_eq!(sorted(seen), ["user:1", "user:2", "user:3"]);
+    let seen = scan_all(&mut client, &["TYPE", "list"]).await;
+    assert_eq!(sorted(seen), ["user:2"]);
+    let seen = scan_all(&mut client, &["TYPE", "STRING", "MATCH", "*r*"]).await;
+    assert_eq!(sorted(seen), ["other", "user:1"]);
+}
+
+#[tokio::test]
+async fn test_scan_errors() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SCAN", "abc"])
+        .await
+        .expect("Failed to SCAN");
+    assert_eq!(response, "-ERR invalid cursor\r\n");
+    let response = client
+        .send_array(&["SCAN", "0", "TYPE", "nope"])
+        .await
+        .expect("Failed to SCAN");
+    assert_eq!(response, "-ERR unknown type name 'nope'\r\n");
+    let response = client
+        .send_array(&["SCAN", "0", "COUNT", "0"])
+        .await
+        .expect("Failed to SCAN");
+    assert_eq!(response, "-ERR syntax error\r\n");
+    let response = client
+        .send_array(&["SCAN", "0", "MATCH"])
+        .await
+        .expect("Failed to SCAN");
+    assert_eq!(response, "-ERR syntax error\r\n");
+}
+
+#[tokio::test]
+async fn test_del_unlink_and_exists() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for key in ["a", "b"] {
+        client
+            .send_array(&["SET", key, "x"])
+            .await
+            .expect("Failed to SET");
+    }
+    // Keys named twice are counted twice.
+    let response = client
+        .send_array(&["EXISTS", "a", "a", "b", "missing"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["TOUCH", "a", "missing"])
+        .await
+        .expect("Failed to TOUCH");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = client
+        .send_array(&["DEL", "a", "missing", "a"])
+        .await
+        .expect("Failed to DEL");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["UNLINK", "b"])
+        .await
+        .expect("Failed to UNLINK");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["EXISTS", "a", "b"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    // Big values are freed in the background, but are gone all the same.
+    let members: Vec<String> = (0..100).map(|n| n.to_string()).collect();
+    let mut args = vec!["SADD", "big"];
+    args.extend(members.iter().map(String::as_str));
+    client.send_array(&args).await.expect("Failed to SADD");
+    let response = client
+        .send_array(&["UNLINK", "big"])
+        .await
+        .expect("Failed to UNLINK");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SCARD", "big"])
+        .await
+        .expect("Failed to SCARD");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_type() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for args in [
+        &["SET", "s", "x"][..],
+        &["RPUSH", "l", "x"],
+        &["HSET", "h", "f", "x"],
+        &["SADD", "set", "x"],
+        &["ZADD", "z", "1", "x"],
+        &["XADD", "x", "1-0", "f", "x"],
+    ] {
+        client.send_array(args).await.expect("Failed to write");
+    }
+    for (key, type_name) in [
+        ("s", "string"),
+        ("l", "list"),
+        ("h", "hash"),
+        ("set", "set"),
+        ("z", "zset"),
+        ("x", "stream"),
+        ("missing", "none"),
+    ] {
+        let response = client
+            .send_array(&["TYPE", key])
+            .await
+            .expect("Failed to TYPE");
+        assert_eq!(parse_simple_string(&response), Some(type_name));
+    }
+}
+
+#[tokio::test]
+async fn test_rename_and_renamenx() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "src", "v", "PX", "200"])
+        .await
+        .expect("Failed to SET");
+    client
+        .send_array(&["SET", "dst", "old"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["RENAME", "src", "dst"])
+        .await
+        .expect("Failed to RENAME");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["GET", "dst"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("v".to_string()));
+    let response = client
+        .send_array(&["EXISTS", "src"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["RENAME", "src", "other"])
+        .await
+        .expect("Failed to RENAME");
+    assert_eq!(response, "-ERR no such key\r\n");
+    let response = client
+        .send_array(&["RENAME", "dst", "dst"])
+        .await
+        .expect("Failed to RENAME");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    client
+        .send_array(&["SET", "taken", "x"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["RENAMENX", "dst", "taken"])
+        .await
+        .expect("Failed to RENAMENX");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["RENAMENX", "dst", "free"])
+        .await
+        .expect("Failed to RENAMENX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["GET", "free"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("v".to_string()));
+
+    // The expiry moved with the value.
+    sleep(Duration::from_millis(250)).await;
+    let response = client
+        .send_array(&["EXISTS", "free"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_copy() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "src", "a", "b"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["COPY", "src", "dst"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(parse_integer(&response), Some(1));
+    // The copy is independent of the original.
+    client
+        .send_array(&["RPUSH", "src", "c"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["LRANGE", "dst", "0", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+
+    let response = client
+        .send_array(&["COPY", "src", "dst"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["COPY", "src", "dst", "DB", "0", "REPLACE"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["LLEN", "dst"])
+        .await
+        .expect("Failed to LLEN");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["COPY", "missing", "dst", "REPLACE"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["COPY", "src", "src"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(
+        response,
+        "-ERR source and destination objects are the same\r\n"
+    );
+    let response = client
+        .send_array(&["COPY", "src", "dst", "DB", "1"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(response, "-ERR DB index is out of range\r\n");
+    let response = client
+        .send_array(&["COPY", "src", "dst", "NOPE"])
+        .await
+        .expect("Failed to COPY");
+    assert_eq!(response, "-ERR syntax error\r\n");
+}
+
+#[tokio::test]
+async fn test_randomkey_dbsize_and_flush() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for key in ["a", "b", "c"] {
+        client
+            .send_array(&["SET", key, "x"])
+            .await
+            .expect("Failed to SET");
+    }
+    // "banana" comes from the RDB file.
+    let response = client
+        .send_array(&["DBSIZE"])
+        .await
+        .expect("Failed to DBSIZE");
+    assert_eq!(parse_integer(&response), Some(4));
+    let response = client
+        .send_array(&["RANDOMKEY"])
+        .await
+        .expect("Failed to RANDOMKEY");
+    let key = parse_bulk_string(&response).expect("Expected a key");
+    assert!(["a", "b", "c", "banana"].contains(&key.as_str()));
+
+    let response = client
+        .send_array(&["FLUSHALL"])
+        .await
+        .expect("Failed to FLUSHALL");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["DBSIZE"])
+        .await
+        .expect("Failed to DBSIZE");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["RANDOMKEY"])
+        .await
+        .expect("Failed to RANDOMKEY");
+    assert_eq!(response, "$-1\r\n");
+
+    client
+        .send_array(&["SET", "a", "x"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["FLUSHDB", "ASYNC"])
+        .await
+        .expect("Failed to FLUSHDB");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["EXISTS", "a"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["FLUSHALL", "SYNC"])
+        .await
+        .expect("Failed to FLUSHALL");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["FLUSHALL", "LATER"])
+        .await
+        .expect("Failed to FLUSHALL");
+    assert_eq!(response, "-ERR syntax error\r\n");
+}
@@ -3,7 +3,6 @@ mod common;
 use common::*;
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_single_element() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -16,7 +15,6 @@ async fn test_rpush_single_element() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_multiple_elements() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -29,7 +27,6 @@ async fn test_rpush_multiple_elements() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lpush_single_element() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -42,7 +39,6 @@ async fn test_lpush_single_element() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lrange_command() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -64,7 +60,6 @@ async fn test_lrange_command() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lrange_partial() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -84,7 +79,6 @@ async fn test_lrange_partial() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_then_lpush() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -110,3 +104,348 @@ async fn test_rpush_then_lpush() {
 
     assert_eq!(elements, vec!["first", "middle", "last"]);
 }
+
+#[tokio::test]
+async fn test_pop_with_count_deletes_empty_list() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "queue", "a", "b", "c"])
+        .await
+        .expect("Failed to RPUSH");
+
+    let response = client
+        .send_array(&["LPOP", "queue"])
+        .await
+        .expect("Failed to LPOP");
+    assert_eq!(parse_bulk_string(&response), Some("a".to_string()));
+
+    let response = client
+        .send_array(&["RPOP", "queue", "5"])
+        .await
+        .expect("Failed to RPOP");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["c".to_string(), "b".to_string()])
+    );
+
+    let response = client
+        .send_array(&["LLEN", "queue"])
+        .await
+        .expect("Failed to LLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["LPOP", "queue"])
+        .await
+        .expect("Failed to LPOP");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["LPOP", "queue", "2"])
+        .await
+        .expect("Failed to LPOP");
+    assert_eq!(response, "*-1\r\n");
+
+    // The key is gone, so it can be reused as another type.
+    let response = client
+        .send_array(&["SET", "queue", "v", "XX"])
+        .await
+        .expect("Failed to SET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_pushx_only_touches_existing_lists() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["LPUSHX", "list", "a"])
+        .await
+        .expect("Failed to LPUSHX");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    client
+        .send_array(&["LPUSH", "list", "b", "a"])
+        .await
+        .expect("Failed to LPUSH");
+    let response = client
+        .send_array(&["RPUSHX", "list", "c", "d"])
+        .await
+        .expect("Failed to RPUSHX");
+    assert_eq!(parse_integer(&response), Some(4));
+
+    let response = client
+        .send_array(&["LRANGE", "list", "0", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(
+            vec!["a", "b", "c", "d"]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_index_commands() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "list", "a", "b", "c", "d", "e"])
+        .await
+        .expect("Failed to RPUSH");
+
+    let response = client
+        .send_array(&["LRANGE", "list", "-3", "100"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["c", "d", "e"].into_iter().map(String::from).collect())
+    );
+    let response = client
+        .send_array(&["LRANGE", "list", "3", "1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(response, "*0\r\n");
+
+    let response = client
+        .send_array(&["LINDEX", "list", "-1"])
+        .await
+        .expect("Failed to LINDEX");
+    assert_eq!(parse_bulk_string(&response), Some("e".to_string()));
+    let response = client
+        .send_array(&["LINDEX", "list", "10"])
+        .await
+        .expect("Failed to LINDEX");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["LSET", "list", "-2", "D"])
+        .await
+        .expect("Failed to LSET");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["LSET", "list", "5", "x"])
+        .await
+        .expect("Failed to LSET");
+    assert_eq!(response, "-ERR index out of range\r\n");
+    let response = client
+        .send_array(&["LSET", "missing", "0", "x"])
+        .await
+        .expect("Failed to LSET");
+    assert_eq!(response, "-ERR no such key\r\n");
+
+    let response = client
+        .send_array(&["LTRIM", "list", "1", "-2"])
+        .await
+        .expect("Failed to LTRIM");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["LRANGE", "list", "0", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["b", "c", "D"].into_iter().map(String::from).collect())
+    );
+}
+
+#[tokio::test]
+async fn test_linsert_and_lrem() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "list", "x", "a", "x", "b", "x"])
+        .await
+        .expect("Failed to RPUSH");
+
+    let response = client
+        .send_array(&["LINSERT", "list", "BEFORE", "b", "y"])
+        .await
+        .expect("Failed to LINSERT");
+    assert_eq!(parse_integer(&response), Some(6));
+    let response = client
+        .send_array(&["LINSERT", "list", "AFTER", "nope", "y"])
+        .await
+        .expect("Failed to LINSERT");
+    assert_eq!(parse_integer(&response), Some(-1));
+
+    let response = client
+        .send_array(&["LREM", "list", "-2", "x"])
+        .await
+        .expect("Failed to LREM");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["LRANGE", "list", "0", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(
+            vec!["x", "a", "y", "b"]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_lpos() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "list", "a", "b", "c", "1", "2", "3", "c", "c"])
+        .await
+        .expect("Failed to RPUSH");
+
+    let response = client
+        .send_array(&["LPOS", "list", "c"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["LPOS", "list", "c", "RANK", "-1"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(parse_integer(&response), Some(7));
+    let response = client
+        .send_array(&["LPOS", "list", "c", "COUNT", "0"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(response, "*3\r\n:2\r\n:6\r\n:7\r\n");
+    let response = client
+        .send_array(&["LPOS", "list", "c", "RANK", "2", "COUNT", "1"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(response, "*1\r\n:6\r\n");
+    let response = client
+        .send_array(&["LPOS", "list", "c", "COUNT", "0", "MAXLEN", "3"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(response, "*1\r\n:2\r\n");
+    let response = client
+        .send_array(&["LPOS", "list", "z"])
+        .await
+        .expect("Failed to LPOS");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["LPOS", "list", "c", "RANK", "0"])
+        .await
+        .expect("Failed to LPOS");
+    assert!(response.starts_with("-ERR RANK can't be zero"));
+}
+
+#[tokio::test]
+async fn test_lmove_rpoplpush_and_lmpop() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["RPUSH", "src", "one", "two", "three"])
+        .await
+        .expect("Failed to RPUSH");
+
+    let response = client
+        .send_array(&["LMOVE", "src", "dst", "LEFT", "RIGHT"])
+        .await
+        .expect("Failed to LMOVE");
+    assert_eq!(parse_bulk_string(&response), Some("one".to_string()));
+    let response = client
+        .send_array(&["RPOPLPUSH", "src", "dst"])
+        .await
+        .expect("Failed to RPOPLPUSH");
+    assert_eq!(parse_bulk_string(&response), Some("three".to_string()));
+    let response = client
+        .send_array(&["LRANGE", "dst", "0", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["three", "one"].into_iter().map(String::from).collect())
+    );
+
+    client
+        .send_array(&["SET", "string", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["LMOVE", "src", "string", "LEFT", "LEFT"])
+        .await
+        .expect("Failed to LMOVE");
+    assert!(response.starts_with("-WRONGTYPE"));
+    let response = client
+        .send_array(&["LLEN", "src"])
+        .await
+        .expect("Failed to LLEN");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = client
+        .send_array(&["LMPOP", "2", "missing", "dst", "RIGHT", "COUNT", "5"])
+        .await
+        .expect("Failed to LMPOP");
+    assert_eq!(
+        response,
+        "*2\r\n$3\r\ndst\r\n*2\r\n$3\r\none\r\n$5\r\nthree\r\n"
+    );
+    let response = client
+        .send_array(&["LMPOP", "1", "dst", "LEFT"])
+        .await
+        .expect("Failed to LMPOP");
+    assert_eq!(response, "*-1\r\n");
+    let response = client
+        .send_array(&["LMPOP", "0", "dst", "LEFT"])
+        .await
+        .expect("Failed to LMPOP");
+    assert_eq!(response, "-ERR numkeys should be greater than 0\r\n");
+}
+
+#[tokio::test]
+async fn test_long_list_spanning_many_nodes() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let elements = (0..2000).map(|i| format!("job-{i}")).collect::<Vec<_>>();
+    let mut command = vec!["RPUSH", "queue"];
+    command.extend(elements.iter().map(String::as_str));
+    let response = client.send_array(&command).await.expect("Failed to RPUSH");
+    assert_eq!(parse_integer(&response), Some(2000));
+
+    client
+        .send_array(&["LPUSH", "queue", "urgent"])
+        .await
+        .expect("Failed to LPUSH");
+    let response = client
+        .send_array(&["LINDEX", "queue", "1500"])
+        .await
+        .expect("Failed to LINDEX");
+    assert_eq!(parse_bulk_string(&response), Some("job-1499".to_string()));
+
+    let response = client
+        .send_array(&["LPOP", "queue"])
+        .await
+        .expect("Failed to LPOP");
+    assert_eq!(parse_bulk_string(&response), Some("urgent".to_string()));
+    let response = client
+        .send_array(&["RPOP", "queue"])
+        .await
+        .expect("Failed to RPOP");
+    assert_eq!(parse_bulk_string(&response), Some("job-1999".to_string()));
+    let response = client
+        .send_array(&["LRANGE", "queue", "-2", "-1"])
+        .await
+        .expect("Failed to LRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["job-1997".to_string(), "job-1998".to_string()])
+    );
+}
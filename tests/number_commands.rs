@@ -0,0 +1,190 @@
+mod common;
+
+use common::*;
+use tokio::time::{Duration, sleep};
+
+#[tokio::test]
+async fn test_incr_family() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["INCR", "counter"])
+        .await
+        .expect("Failed to INCR");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = client
+        .send_array(&["INCRBY", "counter", "41"])
+        .await
+        .expect("Failed to INCRBY");
+    assert_eq!(parse_integer(&response), Some(42));
+
+    let response = client
+        .send_array(&["DECRBY", "counter", "50"])
+        .await
+        .expect("Failed to DECRBY");
+    assert_eq!(parse_integer(&response), Some(-8));
+
+    let response = client
+        .send_array(&["DECR", "counter"])
+        .await
+        .expect("Failed to DECR");
+    assert_eq!(parse_integer(&response), Some(-9));
+
+    let response = client
+        .send_array(&["GET", "counter"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("-9".to_string()));
+}
+
+#[tokio::test]
+async fn test_incr_errors() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "text", "abc"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["INCR", "text"])
+        .await
+        .expect("Failed to INCR");
+    assert_eq!(response, "-ERR value is not an integer or out of range\r\n");
+
+    let response = client
+        .send_array(&["INCRBY", "counter", "1.5"])
+        .await
+        .expect("Failed to INCRBY");
+    assert_eq!(response, "-ERR value is not an integer or out of range\r\n");
+
+    client
+        .send_array(&["SET", "big", "9223372036854775807"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["INCR", "big"])
+        .await
+        .expect("Failed to INCR");
+    assert_eq!(response, "-ERR increment or decrement would overflow\r\n");
+
+    let response = client
+        .send_array(&["DECRBY", "counter", "-9223372036854775808"])
+        .await
+        .expect("Failed to DECRBY");
+    assert_eq!(response, "-ERR decrement would overflow\r\n");
+
+    client
+        .send_array(&["RPUSH", "list", "a"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["INCR", "list"])
+        .await
+        .expect("Failed to INCR");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_incr_keeps_ttl() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "counter", "10", "PX", "100"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["INCR", "counter"])
+        .await
+        .expect("Failed to INCR");
+    assert_eq!(parse_integer(&response), Some(11));
+
+    sleep(Duration::from_millis(150)).await;
+    let response = client
+        .send_array(&["GET", "counter"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_incrbyfloat() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "price", "10.50"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["INCRBYFLOAT", "price", "0.1"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("10.6".to_string()));
+
+    let response = client
+        .send_array(&["INCRBYFLOAT", "price", "-5"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("5.6".to_string()));
+
+    client
+        .send_array(&["SET", "sci", "5.0e3"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["INCRBYFLOAT", "sci", "2.0e2"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("5200".to_string()));
+
+    let response = client
+        .send_array(&["INCRBYFLOAT", "price", "nan"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(response, "-ERR value is not a valid float\r\n");
+
+    let response = client
+        .send_array(&["INCRBYFLOAT", "price", "inf"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(response, "-ERR increment would produce NaN or Infinity\r\n");
+}
+
+#[tokio::test]
+async fn test_incrbyfloat_rounds_like_redis() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let mut response = String::new();
+    for _ in 0..3 {
+        response = client
+            .send_array(&["INCRBYFLOAT", "total", "0.1"])
+            .await
+            .expect("Failed to INCRBYFLOAT");
+    }
+    assert_eq!(parse_bulk_string(&response), Some("0.3".to_string()));
+    let response = client
+        .send_array(&["GET", "total"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("0.3".to_string()));
+
+    // Fixed point, never an exponent; what is past 17 decimals is dropped.
+    let response = client
+        .send_array(&["INCRBYFLOAT", "big", "1e20"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(
+        parse_bulk_string(&response),
+        Some("100000000000000000000".to_string())
+    );
+    let response = client
+        .send_array(&["INCRBYFLOAT", "tiny", "1e-20"])
+        .await
+        .expect("Failed to INCRBYFLOAT");
+    assert_eq!(parse_bulk_string(&response), Some("0".to_string()));
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use common::*;
+use tokio::time::{Duration, sleep};
+
+#[tokio::test]
+async fn test_command_split_across_reads() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let cmd = encode_resp_array(&["ECHO", "split payload"]);
+    let (head, tail) = cmd.as_bytes().split_at(cmd.len() - 7);
+
+    client.write_raw(head).await.expect("Failed to write head");
+    sleep(Duration::from_millis(50)).await;
+    let response = client
+        .send_command(tail)
+        .await
+        .expect("Failed to write tail");
+
+    assert_eq!(
+        parse_bulk_string(&response),
+        Some("split payload".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_pipelined_commands() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let mut pipeline = String::new();
+    for i in 0..50 {
+        pipeline.push_str(&encode_resp_array(&["SET", &format!("key{i}"), "v"]));
+    }
+    pipeline.push_str(&encode_resp_array(&["GET", "key49"]));
+    client
+        .write_raw(pipeline.as_bytes())
+        .await
+        .expect("Failed to write pipeline");
+
+    let expected = format!("{}$1\r\nv\r\n", "+OK\r\n".repeat(50));
+    let response = client
+        .read_exact(expected.len())
+        .await
+        .expect("Failed to read responses");
+    assert_eq!(response, expected);
+}
+
+#[tokio::test]
+async fn test_large_value_with_crlf() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let value = "line\r\n".repeat(2000);
+    let response = client
+        .send_array(&["SET", "big", &value])
+        .await
+        .expect("Failed to SET");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    client
+        .write_raw(encode_resp_array(&["GET", "big"]).as_bytes())
+        .await
+        .expect("Failed to GET");
+    let expected = format!("${}\r\n{}\r\n", value.len(), value);
+    let response = client
+        .read_exact(expected.len())
+        .await
+        .expect("Failed to read response");
+    assert_eq!(response, expected);
+}
+
+#[tokio::test]
+async fn test_binary_key_and_value() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let key: &[u8] = b"\xffkey\x00";
+    let value: &[u8] = b"\x89PNG\r\n\x1a\n\x00\xfe";
+    let mut set = b"*3\r\n$3\r\nSET\r\n$5\r\n".to_vec();
+    set.extend_from_slice(key);
+    set.extend_from_slice(b"\r\n$10\r\n");
+    set.extend_from_slice(value);
+    set.extend_from_slice(b"\r\n");
+    let response = client.send_command(&set).await.expect("Failed to SET");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let mut get = b"*2\r\n$3\r\nGET\r\n$5\r\n".to_vec();
+    get.extend_from_slice(key);
+    get.extend_from_slice(b"\r\n");
+    client.write_raw(&get).await.expect("Failed to GET");
+
+    let mut expected = b"$10\r\n".to_vec();
+    expected.extend_from_slice(value);
+    expected.extend_from_slice(b"\r\n");
+    let response = client
+        .read_exact(expected.len())
+        .await
+        .expect("Failed to read response");
+    assert_eq!(response, String::from_utf8_lossy(&expected));
+}
+
+#[tokio::test]
+async fn test_multibyte_utf8_length() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["ECHO", "héllo wörld"])
+        .await
+        .expect("Failed to ECHO");
+    assert_eq!(response, "$13\r\nhéllo wörld\r\n");
+}
+
+#[tokio::test]
+async fn test_hello_switches_to_resp3() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["HELLO", "3", "SETNAME", "worker-1"])
+        .await
+        .expect("Failed to send HELLO");
+    assert!(response.starts_with("%7\r\n$6\r\nserver\r\n$5\r\nredis\r\n"));
+    assert!(response.contains("$5\r\nproto\r\n:3\r\n"));
+
+    let response = client
+        .send_array(&["CONFIG", "GET", "dir"])
+        .await
+        .expect("Failed to send CONFIG GET");
+    assert_eq!(response, "%1\r\n$3\r\ndir\r\n$15\r\n/tmp/redis-data\r\n");
+
+    let response = client
+        .send_array(&["GET", "missing"])
+        .await
+        .expect("Failed to send GET");
+    assert_eq!(response, "_\r\n");
+}
+
+#[tokio::test]
+async fn test_resp3_doubles_use_exponents_like_redis() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["HELLO", "3"])
+        .await
+        .expect("Failed to send HELLO");
+    client
+        .send_array(&[
+            "ZADD", "z", "1e21", "big", "1.5e-7", "small", "0.25", "plain",
+        ])
+        .await
+        .expect("Failed to ZADD");
+
+    let response = client
+        .send_array(&["ZSCORE", "z", "big"])
+        .await
+        .expect("Failed to ZSCORE");
+    assert_eq!(response, ",1e+21\r\n");
+    let response = client
+        .send_array(&["ZSCORE", "z", "small"])
+        .await
+        .expect("Failed to ZSCORE");
+    assert_eq!(response, ",1.5e-07\r\n");
+    let response = client
+        .send_array(&["ZSCORE", "z", "plain"])
+        .await
+        .expect("Failed to ZSCORE");
+    assert_eq!(response, ",0.25\r\n");
+}
+
+#[tokio::test]
+async fn test_hello_resp2_keeps_flat_arrays() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["HELLO", "2"])
+        .await
+        .expect("Failed to send HELLO");
+    assert!(response.starts_with("*14\r\n"));
+    assert!(response.contains("$5\r\nproto\r\n:2\r\n"));
+
+    let response = client
+        .send_array(&["CONFIG", "GET", "dir"])
+        .await
+        .expect("Failed to send CONFIG GET");
+    assert_eq!(
+        parse_array(&response),
+        Some(vec!["dir".to_string(), "/tmp/redis-data".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn test_hello_rejects_unknown_protocol() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["HELLO", "4"])
+        .await
+        .expect("Failed to send HELLO");
+    assert_eq!(response, "-NOPROTO unsupported protocol version\r\n");
+
+    let response = client
+        .send_array(&["HELLO", "3", "FOO"])
+        .await
+        .expect("Failed to send HELLO");
+    assert_eq!(response, "-ERR Syntax error in HELLO option 'FOO'\r\n");
+}
+
+#[tokio::test]
+async fn test_inline_commands() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_command(b"PING\r\n")
+        .await
+        .expect("Failed to send inline PING");
+    assert_eq!(parse_simple_string(&response), Some("PONG"));
+
+    let response = client
+        .send_command(b"SET greeting \"hello world\"\n")
+        .await
+        .expect("Failed to send inline SET");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let response = client
+        .send_array(&["GET", "greeting"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(
+        parse_bulk_string(&response),
+        Some("hello world".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_nested_arrays_are_rejected() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .write_raw(&b"*1\r\n".repeat(10_000))
+        .await
+        .expect("Failed to write nested arrays");
+    sleep(Duration::from_millis(100)).await;
+    let response = client.read_response().await.expect("Failed to read");
+    assert!(
+        response.starts_with("-ERR Protocol error: expected '$', got '*'\r\n"),
+        "{response}"
+    );
+
+    // The server is still up and the connection usable, once the error
+    // replies to the rest of the flood are read.
+    client
+        .write_raw(encode_resp_array(&["PING"]).as_bytes())
+        .await
+        .expect("Failed to PING");
+    let mut replies = String::new();
+    while !replies.ends_with("+PONG\r\n") {
+        let response = client.read_response().await.expect("Failed to read");
+        assert!(!response.is_empty(), "connection closed after {replies}");
+        replies.push_str(&response);
+    }
+}
@@ -3,7 +3,6 @@ mod common;
 use common::*;
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_single_element() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -16,7 +15,6 @@ async fn test_rpush_single_element() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_multiple_elements() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -29,7 +27,6 @@ async fn test_rpush_multiple_elements() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lpush_single_element() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -42,7 +39,6 @@ async fn test_lpush_single_element() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lrange_command() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -64,7 +60,6 @@ async fn test_lrange_command() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_lrange_partial() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
@@ -84,7 +79,6 @@ async fn test_lrange_partial() {
 }
 
 #[tokio::test]
-#[ignore]
 async fn test_rpush_then_lpush() {
     let server = TestServer::start().await.expect("Failed to start server");
     let mut client = server.connect().await.expect("Failed to connect");
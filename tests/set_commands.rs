@@ -0,0 +1,372 @@
+mod common;
+
+use common::*;
+use std::collections::HashSet;
+
+fn sorted(mut items: Vec<String>) -> Vec<String> {
+    items.sort();
+    items
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_sadd_srem_and_membership() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SADD", "tags", "rust", "go", "rust", "c"])
+        .await
+        .expect("Failed to SADD");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["SCARD", "tags"])
+        .await
+        .expect("Failed to SCARD");
+    assert_eq!(parse_integer(&response), Some(3));
+
+    let response = client
+        .send_array(&["SISMEMBER", "tags", "go"])
+        .await
+        .expect("Failed to SISMEMBER");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SMISMEMBER", "tags", "c", "java"])
+        .await
+        .expect("Failed to SMISMEMBER");
+    assert_eq!(response, "*2\r\n:1\r\n:0\r\n");
+    let response = client
+        .send_array(&["SMEMBERS", "tags"])
+        .await
+        .expect("Failed to SMEMBERS");
+    assert_eq!(
+        sorted(parse_array(&response).expect("Expected an array")),
+        strings(&["c", "go", "rust"])
+    );
+
+    let response = client
+        .send_array(&["SREM", "tags", "go", "java"])
+        .await
+        .expect("Failed to SREM");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SREM", "tags", "rust", "c"])
+        .await
+        .expect("Failed to SREM");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["SMEMBERS", "tags"])
+        .await
+        .expect("Failed to SMEMBERS");
+    assert_eq!(response, "*0\r\n");
+
+    client
+        .send_array(&["SET", "s", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["SADD", "s", "x"])
+        .await
+        .expect("Failed to SADD");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_spop_and_srandmember() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SADD", "s", "a", "b", "c"])
+        .await
+        .expect("Failed to SADD");
+
+    let response = client
+        .send_array(&["SRANDMEMBER", "s", "5"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    assert_eq!(
+        sorted(parse_array(&response).expect("Expected an array")),
+        strings(&["a", "b", "c"])
+    );
+    let response = client
+        .send_array(&["SRANDMEMBER", "s", "-7"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    let picked = parse_array(&response).expect("Expected an array");
+    assert_eq!(picked.len(), 7);
+    assert!(
+        picked
+            .iter()
+            .all(|member| ["a", "b", "c"].contains(&member.as_str()))
+    );
+    // Huge counts are clamped or refused rather than taking the server down.
+    let response = client
+        .send_array(&["SRANDMEMBER", "s", "9223372036854775807"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    assert_eq!(parse_array(&response).map(|items| items.len()), Some(3));
+    let response = client
+        .send_array(&["SRANDMEMBER", "s", "-9223372036854775807"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    assert_eq!(response, "-ERR value is out of range\r\n");
+    let response = client
+        .send_array(&["SRANDMEMBER", "missing"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["SPOP", "s"])
+        .await
+        .expect("Failed to SPOP");
+    let popped = parse_bulk_string(&response).expect("Expected a member");
+    let response = client
+        .send_array(&["SISMEMBER", "s", &popped])
+        .await
+        .expect("Failed to SISMEMBER");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["SPOP", "s", "-1"])
+        .await
+        .expect("Failed to SPOP");
+    assert_eq!(response, "-ERR value is out of range, must be positive\r\n");
+    let response = client
+        .send_array(&["SPOP", "s", "9223372036854775807"])
+        .await
+        .expect("Failed to SPOP");
+    assert_eq!(parse_array(&response).map(|items| items.len()), Some(2));
+    let response = client
+        .send_array(&["SCARD", "s"])
+        .await
+        .expect("Failed to SCARD");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    // Small counts out of a big set are drawn one at a time, still distinct.
+    let members = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+    let mut sadd = vec!["SADD", "big"];
+    sadd.extend(members.iter().map(String::as_str));
+    client.send_array(&sadd).await.expect("Failed to SADD");
+    let response = client
+        .send_array(&["SRANDMEMBER", "big", "10"])
+        .await
+        .expect("Failed to SRANDMEMBER");
+    let picked = parse_array(&response).expect("Expected an array");
+    assert_eq!(picked.iter().collect::<HashSet<_>>().len(), 10);
+    let response = client
+        .send_array(&["SPOP", "big", "10"])
+        .await
+        .expect("Failed to SPOP");
+    let popped = parse_array(&response).expect("Expected an array");
+    assert_eq!(popped.iter().collect::<HashSet<_>>().len(), 10);
+    let response = client
+        .send_array(&["SCARD", "big"])
+        .await
+        .expect("Failed to SCARD");
+    assert_eq!(parse_integer(&response), Some(990));
+}
+
+#[tokio::test]
+async fn test_smove() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SADD", "from", "a", "b"])
+        .await
+        .expect("Failed to SADD");
+    let response = client
+        .send_array(&["SMOVE", "from", "to", "a"])
+        .await
+        .expect("Failed to SMOVE");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SMOVE", "from", "to", "zzz"])
+        .await
+        .expect("Failed to SMOVE");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["SMEMBERS", "to"])
+        .await
+        .expect("Failed to SMEMBERS");
+    assert_eq!(parse_array(&response), Some(strings(&["a"])));
+
+    client
+        .send_array(&["SET", "str", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["SMOVE", "from", "str", "b"])
+        .await
+        .expect("Failed to SMOVE");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_set_algebra() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SADD", "k1", "a", "b", "c", "d"])
+        .await
+        .expect("Failed to SADD");
+    client
+        .send_array(&["SADD", "k2", "c", "d", "e"])
+        .await
+        .expect("Failed to SADD");
+    client
+        .send_array(&["SADD", "k3", "a", "c", "e"])
+        .await
+        .expect("Failed to SADD");
+
+    let response = client
+        .send_array(&["SINTER", "k1", "k2", "k3"])
+        .await
+        .expect("Failed to SINTER");
+    assert_eq!(parse_array(&response), Some(strings(&["c"])));
+    let response = client
+        .send_array(&["SINTER", "k1", "missing"])
+        .await
+        .expect("Failed to SINTER");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&["SUNION", "k1", "k2", "missing"])
+        .await
+        .expect("Failed to SUNION");
+    assert_eq!(
+        sorted(parse_array(&response).expect("Expected an array")),
+        strings(&["a", "b", "c", "d", "e"])
+    );
+    let response = client
+        .send_array(&["SDIFF", "k1", "k2", "k3"])
+        .await
+        .expect("Failed to SDIFF");
+    assert_eq!(parse_array(&response), Some(strings(&["b"])));
+
+    let response = client
+        .send_array(&["SINTERCARD", "2", "k1", "k2"])
+        .await
+        .expect("Failed to SINTERCARD");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["SINTERCARD", "2", "k1", "k2", "LIMIT", "1"])
+        .await
+        .expect("Failed to SINTERCARD");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SINTERCARD", "0", "k1"])
+        .await
+        .expect("Failed to SINTERCARD");
+    assert_eq!(response, "-ERR numkeys should be greater than 0\r\n");
+    let response = client
+        .send_array(&["SINTERCARD", "3", "k1", "k2"])
+        .await
+        .expect("Failed to SINTERCARD");
+    assert_eq!(
+        response,
+        "-ERR Number of keys can't be greater than number of args\r\n"
+    );
+    let response = client
+        .send_array(&["SINTERCARD", "1", "k1", "LIMIT", "-1"])
+        .await
+        .expect("Failed to SINTERCARD");
+    assert_eq!(response, "-ERR LIMIT can't be negative\r\n");
+
+    let response = client
+        .send_array(&["SUNIONSTORE", "dest", "k2", "k3"])
+        .await
+        .expect("Failed to SUNIONSTORE");
+    assert_eq!(parse_integer(&response), Some(4));
+    let response = client
+        .send_array(&["SDIFFSTORE", "dest", "k1", "k2"])
+        .await
+        .expect("Failed to SDIFFSTORE");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["SMEMBERS", "dest"])
+        .await
+        .expect("Failed to SMEMBERS");
+    assert_eq!(
+        sorted(parse_array(&response).expect("Expected an array")),
+        strings(&["a", "b"])
+    );
+    // An empty result deletes the destination.
+    let response = client
+        .send_array(&["SINTERSTORE", "dest", "k1", "missing"])
+        .await
+        .expect("Failed to SINTERSTORE");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["SCARD", "dest"])
+        .await
+        .expect("Failed to SCARD");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    client
+        .send_array(&["SET", "str", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["SUNION", "k1", "str"])
+        .await
+        .expect("Failed to SUNION");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_sscan() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SADD", "s", "apple", "avocado", "banana"])
+        .await
+        .expect("Failed to SADD");
+    let response = client
+        .send_array(&["SSCAN", "s", "0", "MATCH", "a*"])
+        .await
+        .expect("Failed to SSCAN");
+    assert!(response.starts_with("*2\r\n$1\r\n0\r\n*2\r\n"));
+    assert!(response.contains("apple") && response.contains("avocado"));
+    assert!(!response.contains("banana"));
+}
+
+#[tokio::test]
+async fn test_sscan_walks_large_sets_with_a_cursor() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let members: Vec<String> = (0..300).map(|n| format!("m{n}")).collect();
+    let mut args = vec!["SADD", "big"];
+    args.extend(members.iter().map(String::as_str));
+    client.send_array(&args).await.expect("Failed to SADD");
+
+    let mut seen = HashSet::new();
+    let mut cursor = "0".to_string();
+    let mut pages = 0;
+    loop {
+        let response = client
+            .send_array(&["SSCAN", "big", &cursor, "COUNT", "20", "MATCH", "m1*"])
+            .await
+            .expect("Failed to SSCAN");
+        let (next, items) = parse_scan_reply(&response);
+        seen.extend(items);
+        pages += 1;
+        if next == "0" {
+            break;
+        }
+        cursor = next;
+    }
+    assert!(pages > 1);
+    let expected: HashSet<String> = members
+        .into_iter()
+        .filter(|member| member.starts_with("m1"))
+        .collect();
+    assert_eq!(seen, expected);
+}
@@ -0,0 +1,655 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+async fn leaderboard(client: &mut TestClient) {
+    let response = client
+        .send_array(&[
+            "ZADD", "board", "10", "ada", "20", "bob", "20", "cyd", "30", "dee", "-5", "eve",
+        ])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(parse_integer(&response), Some(5));
+}
+
+#[tokio::test]
+async fn test_zadd_options() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZADD", "board", "NX", "99", "ada", "1", "fay"])
+        .await
+        .expect("Failed to ZADD NX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["ZADD", "board", "XX", "CH", "11", "ada", "1", "gus"])
+        .await
+        .expect("Failed to ZADD XX CH");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["ZADD", "board", "GT", "CH", "5", "ada", "50", "bob"])
+        .await
+        .expect("Failed to ZADD GT");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["ZMSCORE", "board", "ada", "bob", "gus"])
+        .await
+        .expect("Failed to ZMSCORE");
+    assert_eq!(response, "*3\r\n$2\r\n11\r\n$2\r\n50\r\n$-1\r\n");
+
+    let response = client
+        .send_array(&["ZADD", "board", "INCR", "2.5", "ada"])
+        .await
+        .expect("Failed to ZADD INCR");
+    assert_eq!(parse_bulk_string(&response), Some("13.5".to_string()));
+    let response = client
+        .send_array(&["ZADD", "board", "LT", "INCR", "1", "ada"])
+        .await
+        .expect("Failed to ZADD LT INCR");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["ZINCRBY", "board", "-3.5", "ada"])
+        .await
+        .expect("Failed to ZINCRBY");
+    assert_eq!(parse_bulk_string(&response), Some("10".to_string()));
+
+    let response = client
+        .send_array(&["ZADD", "board", "NX", "XX", "1", "a"])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(
+        response,
+        "-ERR XX and NX options at the same time are not compatible\r\n"
+    );
+    let response = client
+        .send_array(&["ZADD", "board", "GT", "LT", "1", "a"])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(
+        response,
+        "-ERR GT, LT, and/or NX options at the same time are not compatible\r\n"
+    );
+    let response = client
+        .send_array(&["ZADD", "board", "INCR", "1", "a", "2", "b"])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(
+        response,
+        "-ERR INCR option supports a single increment-element pair\r\n"
+    );
+    let response = client
+        .send_array(&["ZADD", "board", "nope", "a"])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(response, "-ERR value is not a valid float\r\n");
+    let response = client
+        .send_array(&["ZADD", "inf", "+inf", "a"])
+        .await
+        .expect("Failed to ZADD");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["ZINCRBY", "inf", "-inf", "a"])
+        .await
+        .expect("Failed to ZINCRBY");
+    assert_eq!(response, "-ERR resulting score is not a number (NaN)\r\n");
+}
+
+#[tokio::test]
+async fn test_zrem_zcard_zcount_and_ranks() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZCOUNT", "board", "10", "(30"])
+        .await
+        .expect("Failed to ZCOUNT");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["ZCOUNT", "board", "-inf", "+inf"])
+        .await
+        .expect("Failed to ZCOUNT");
+    assert_eq!(parse_integer(&response), Some(5));
+    let response = client
+        .send_array(&["ZCOUNT", "board", "x", "1"])
+        .await
+        .expect("Failed to ZCOUNT");
+    assert_eq!(response, "-ERR min or max is not a float\r\n");
+
+    let response = client
+        .send_array(&["ZRANK", "board", "cyd"])
+        .await
+        .expect("Failed to ZRANK");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["ZREVRANK", "board", "cyd", "WITHSCORE"])
+        .await
+        .expect("Failed to ZREVRANK");
+    assert_eq!(response, "*2\r\n:1\r\n$2\r\n20\r\n");
+    let response = client
+        .send_array(&["ZRANK", "board", "nobody"])
+        .await
+        .expect("Failed to ZRANK");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["ZRANK", "board", "nobody", "WITHSCORE"])
+        .await
+        .expect("Failed to ZRANK");
+    assert_eq!(response, "*-1\r\n");
+
+    let response = client
+        .send_array(&["ZREM", "board", "ada", "eve", "nobody"])
+        .await
+        .expect("Failed to ZREM");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["ZCARD", "board"])
+        .await
+        .expect("Failed to ZCARD");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["ZSCORE", "board", "ada"])
+        .await
+        .expect("Failed to ZSCORE");
+    assert_eq!(response, "$-1\r\n");
+
+    client
+        .send_array(&["SET", "str", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["ZCARD", "str"])
+        .await
+        .expect("Failed to ZCARD");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_zrange() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZRANGE", "board", "0", "-1"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["eve", "ada", "bob", "cyd", "dee"]))
+    );
+    let response = client
+        .send_array(&["ZRANGE", "board", "0", "1", "REV", "WITHSCORES"])
+        .await
+        .expect("Failed to ZRANGE REV");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["dee", "30", "cyd", "20"]))
+    );
+    let response = client
+        .send_array(&["ZRANGE", "board", "-2", "100"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(parse_array(&response), Some(strings(&["cyd", "dee"])));
+    let response = client
+        .send_array(&["ZRANGE", "board", "3", "1"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(response, "*0\r\n");
+
+    let response = client
+        .send_array(&["ZRANGE", "board", "(10", "20", "BYSCORE"])
+        .await
+        .expect("Failed to ZRANGE BYSCORE");
+    assert_eq!(parse_array(&response), Some(strings(&["bob", "cyd"])));
+    let response = client
+        .send_array(&[
+            "ZRANGE", "board", "+inf", "10", "BYSCORE", "REV", "LIMIT", "1", "2",
+        ])
+        .await
+        .expect("Failed to ZRANGE BYSCORE REV");
+    assert_eq!(parse_array(&response), Some(strings(&["cyd", "bob"])));
+    let response = client
+        .send_array(&[
+            "ZRANGE", "board", "-inf", "+inf", "BYSCORE", "LIMIT", "3", "-1",
+        ])
+        .await
+        .expect("Failed to ZRANGE BYSCORE LIMIT");
+    assert_eq!(parse_array(&response), Some(strings(&["cyd", "dee"])));
+
+    client
+        .send_array(&["ZADD", "lex", "0", "a", "0", "b", "0", "c", "0", "d"])
+        .await
+        .expect("Failed to ZADD");
+    let response = client
+        .send_array(&["ZRANGE", "lex", "[b", "(d", "BYLEX"])
+        .await
+        .expect("Failed to ZRANGE BYLEX");
+    assert_eq!(parse_array(&response), Some(strings(&["b", "c"])));
+    let response = client
+        .send_array(&["ZRANGE", "lex", "+", "-", "BYLEX", "REV", "LIMIT", "0", "2"])
+        .await
+        .expect("Failed to ZRANGE BYLEX REV");
+    assert_eq!(parse_array(&response), Some(strings(&["d", "c"])));
+
+    let response = client
+        .send_array(&["ZRANGE", "lex", "b", "d", "BYLEX"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(response, "-ERR min or max not valid string range item\r\n");
+    let response = client
+        .send_array(&["ZRANGE", "lex", "0", "1", "LIMIT", "0", "1"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(
+        response,
+        "-ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX\r\n"
+    );
+    let response = client
+        .send_array(&["ZRANGE", "lex", "-", "+", "BYLEX", "WITHSCORES"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(
+        response,
+        "-ERR syntax error, WITHSCORES not supported in combination with BYLEX\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_zpop_and_zrandmember() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZPOPMIN", "board"])
+        .await
+        .expect("Failed to ZPOPMIN");
+    assert_eq!(parse_array(&response), Some(strings(&["eve", "-5"])));
+    let response = client
+        .send_array(&["ZPOPMAX", "board", "2"])
+        .await
+        .expect("Failed to ZPOPMAX");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["dee", "30", "cyd", "20"]))
+    );
+    let response = client
+        .send_array(&["ZPOPMAX", "board", "-1"])
+        .await
+        .expect("Failed to ZPOPMAX");
+    assert_eq!(response, "-ERR value is out of range, must be positive\r\n");
+
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board", "-5", "WITHSCORES"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    let picked = parse_array(&response).expect("Expected an array");
+    assert_eq!(picked.len(), 10);
+    assert!(
+        picked
+            .chunks(2)
+            .all(|pair| pair == ["ada", "10"] || pair == ["bob", "20"])
+    );
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board", "5"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    assert_eq!(parse_array(&response).map(|items| items.len()), Some(2));
+    // Huge counts are clamped or refused rather than taking the server down.
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board", "9223372036854775807"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    assert_eq!(parse_array(&response).map(|items| items.len()), Some(2));
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board", "-9223372036854775807"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    assert_eq!(response, "-ERR value is out of range\r\n");
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    let member = parse_bulk_string(&response).expect("Expected a member");
+    assert!(member == "ada" || member == "bob");
+
+    let response = client
+        .send_array(&["ZPOPMIN", "board", "10"])
+        .await
+        .expect("Failed to ZPOPMIN");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["ada", "10", "bob", "20"]))
+    );
+    let response = client
+        .send_array(&["ZPOPMIN", "board"])
+        .await
+        .expect("Failed to ZPOPMIN");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&["ZRANDMEMBER", "board"])
+        .await
+        .expect("Failed to ZRANDMEMBER");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_resp3_score_replies() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    client
+        .send_array(&["HELLO", "3"])
+        .await
+        .expect("Failed to HELLO");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZSCORE", "board", "ada"])
+        .await
+        .expect("Failed to ZSCORE");
+    assert_eq!(response, ",10\r\n");
+    let response = client
+        .send_array(&["ZRANGE", "board", "0", "0", "WITHSCORES"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(response, "*1\r\n*2\r\n$3\r\neve\r\n,-5\r\n");
+    let response = client
+        .send_array(&["ZPOPMIN", "board"])
+        .await
+        .expect("Failed to ZPOPMIN");
+    assert_eq!(response, "*2\r\n$3\r\neve\r\n,-5\r\n");
+}
+
+async fn regions(client: &mut TestClient) {
+    client
+        .send_array(&["ZADD", "eu", "1", "ada", "2", "bob", "3", "cyd"])
+        .await
+        .expect("Failed to ZADD");
+    client
+        .send_array(&["ZADD", "us", "10", "bob", "20", "cyd", "30", "dee"])
+        .await
+        .expect("Failed to ZADD");
+}
+
+#[tokio::test]
+async fn test_zunion_zinter_and_zdiff() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    regions(&mut client).await;
+
+    let response = client
+        .send_array(&["ZUNION", "2", "eu", "us", "WITHSCORES"])
+        .await
+        .expect("Failed to ZUNION");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&[
+            "ada", "1", "bob", "12", "cyd", "23", "dee", "30"
+        ]))
+    );
+    let response = client
+        .send_array(&[
+            "ZINTER",
+            "2",
+            "eu",
+            "us",
+            "WEIGHTS",
+            "10",
+            "1",
+            "AGGREGATE",
+            "MAX",
+            "WITHSCORES",
+        ])
+        .await
+        .expect("Failed to ZINTER");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["bob", "20", "cyd", "30"]))
+    );
+    let response = client
+        .send_array(&["ZDIFF", "2", "us", "eu", "WITHSCORES"])
+        .await
+        .expect("Failed to ZDIFF");
+    assert_eq!(parse_array(&response), Some(strings(&["dee", "30"])));
+    let response = client
+        .send_array(&["ZINTERCARD", "2", "eu", "us", "LIMIT", "1"])
+        .await
+        .expect("Failed to ZINTERCARD");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    // Plain sets join in with a score of 1.
+    client
+        .send_array(&["SADD", "vip", "ada", "dee"])
+        .await
+        .expect("Failed to SADD");
+    let response = client
+        .send_array(&["ZUNIONSTORE", "out", "2", "us", "vip", "AGGREGATE", "MIN"])
+        .await
+        .expect("Failed to ZUNIONSTORE");
+    assert_eq!(parse_integer(&response), Some(4));
+    let response = client
+        .send_array(&["ZRANGE", "out", "0", "-1", "WITHSCORES"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["ada", "1", "dee", "1", "bob", "10", "cyd", "20"]))
+    );
+    let response = client
+        .send_array(&["ZINTERSTORE", "out", "2", "eu", "missing"])
+        .await
+        .expect("Failed to ZINTERSTORE");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["ZCARD", "out"])
+        .await
+        .expect("Failed to ZCARD");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["ZDIFFSTORE", "out", "2", "eu", "us"])
+        .await
+        .expect("Failed to ZDIFFSTORE");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    let response = client
+        .send_array(&["ZUNION", "0", "eu"])
+        .await
+        .expect("Failed to ZUNION");
+    assert_eq!(
+        response,
+        "-ERR at least 1 input key is needed for 'zunion' command\r\n"
+    );
+    let response = client
+        .send_array(&["ZUNION", "2", "eu", "us", "WEIGHTS", "1", "x"])
+        .await
+        .expect("Failed to ZUNION");
+    assert_eq!(response, "-ERR weight value is not a float\r\n");
+    let response = client
+        .send_array(&["ZDIFF", "2", "eu", "us", "AGGREGATE", "SUM"])
+        .await
+        .expect("Failed to ZDIFF");
+    assert_eq!(response, "-ERR syntax error\r\n");
+    let response = client
+        .send_array(&["ZUNIONSTORE", "out", "1", "eu", "WITHSCORES"])
+        .await
+        .expect("Failed to ZUNIONSTORE");
+    assert_eq!(response, "-ERR syntax error\r\n");
+    client
+        .send_array(&["SET", "str", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["ZINTER", "2", "eu", "str"])
+        .await
+        .expect("Failed to ZINTER");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_zrangestore_zlexcount_and_zremrange() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&[
+            "ZRANGESTORE",
+            "top",
+            "board",
+            "+inf",
+            "15",
+            "BYSCORE",
+            "REV",
+        ])
+        .await
+        .expect("Failed to ZRANGESTORE");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["ZRANGE", "top", "0", "-1"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(
+        parse_array(&response),
+        Some(strings(&["bob", "cyd", "dee"]))
+    );
+
+    let response = client
+        .send_array(&["ZREMRANGEBYSCORE", "board", "-inf", "(10"])
+        .await
+        .expect("Failed to ZREMRANGEBYSCORE");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["ZREMRANGEBYRANK", "board", "-2", "-1"])
+        .await
+        .expect("Failed to ZREMRANGEBYRANK");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["ZRANGE", "board", "0", "-1"])
+        .await
+        .expect("Failed to ZRANGE");
+    assert_eq!(parse_array(&response), Some(strings(&["ada", "bob"])));
+
+    client
+        .send_array(&[
+            "ZADD", "lex", "0", "a", "0", "b", "0", "c", "0", "d", "0", "e",
+        ])
+        .await
+        .expect("Failed to ZADD");
+    let response = client
+        .send_array(&["ZLEXCOUNT", "lex", "(a", "[c"])
+        .await
+        .expect("Failed to ZLEXCOUNT");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["ZREMRANGEBYLEX", "lex", "-", "(c"])
+        .await
+        .expect("Failed to ZREMRANGEBYLEX");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["ZREMRANGEBYLEX", "lex", "-", "+"])
+        .await
+        .expect("Failed to ZREMRANGEBYLEX");
+    assert_eq!(parse_integer(&response), Some(3));
+    let response = client
+        .send_array(&["ZCARD", "lex"])
+        .await
+        .expect("Failed to ZCARD");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_zmpop() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    leaderboard(&mut client).await;
+
+    let response = client
+        .send_array(&["ZMPOP", "2", "missing", "board", "MAX", "COUNT", "2"])
+        .await
+        .expect("Failed to ZMPOP");
+    assert_eq!(
+        response,
+        "*2\r\n$5\r\nboard\r\n*2\r\n*2\r\n$3\r\ndee\r\n$2\r\n30\r\n*2\r\n$3\r\ncyd\r\n$2\r\n20\r\n"
+    );
+    let response = client
+        .send_array(&["ZMPOP", "1", "missing", "MIN"])
+        .await
+        .expect("Failed to ZMPOP");
+    assert_eq!(response, "*-1\r\n");
+    let response = client
+        .send_array(&["ZMPOP", "1", "board", "MIN", "COUNT", "0"])
+        .await
+        .expect("Failed to ZMPOP");
+    assert_eq!(response, "-ERR count should be greater than 0\r\n");
+}
+
+#[tokio::test]
+async fn test_zscan() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["ZADD", "z", "1", "a", "2.5", "b", "3", "c"])
+        .await
+        .expect("Failed to ZADD");
+    let response = client
+        .send_array(&["ZSCAN", "z", "0", "MATCH", "[ab]"])
+        .await
+        .expect("Failed to ZSCAN");
+    let (cursor, items) = parse_scan_reply(&response);
+    assert_eq!(cursor, "0");
+    let mut pairs: Vec<&[String]> = items.chunks(2).collect();
+    pairs.sort();
+    assert_eq!(pairs, [&["a", "1"][..], &["b", "2.5"][..]]);
+
+    let response = client
+        .send_array(&["ZSCAN", "missing", "0"])
+        .await
+        .expect("Failed to ZSCAN");
+    assert_eq!(response, "*2\r\n$1\r\n0\r\n*0\r\n");
+
+    let mut args = vec!["ZADD".to_string(), "big".to_string()];
+    for n in 0..300 {
+        args.extend([n.to_string(), format!("m{n}")]);
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    client.send_array(&args).await.expect("Failed to ZADD");
+
+    let mut scores = HashMap::new();
+    let mut cursor = "0".to_string();
+    loop {
+        let response = client
+            .send_array(&["ZSCAN", "big", &cursor, "COUNT", "20"])
+            .await
+            .expect("Failed to ZSCAN");
+        let (next, items) = parse_scan_reply(&response);
+        for pair in items.chunks(2) {
+            scores.insert(pair[0].clone(), pair[1].clone());
+        }
+        if next == "0" {
+            break;
+        }
+        assert_ne!(next, cursor);
+        cursor = next;
+    }
+    assert_eq!(scores.len(), 300);
+    assert!((0..300).all(|n| scores.get(&format!("m{n}")) == Some(&n.to_string())));
+
+    client
+        .send_array(&["SET", "s", "v"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["ZSCAN", "s", "0"])
+        .await
+        .expect("Failed to ZSCAN");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
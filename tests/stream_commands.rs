@@ -0,0 +1,245 @@
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn test_xadd_ids() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["XADD", "events", "5-1", "kind", "login"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(parse_bulk_string(&response), Some("5-1".to_string()));
+    let response = client
+        .send_array(&["XADD", "events", "5-*", "kind", "logout"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(parse_bulk_string(&response), Some("5-2".to_string()));
+    let response = client
+        .send_array(&["XADD", "events", "7", "kind", "login"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(parse_bulk_string(&response), Some("7-0".to_string()));
+
+    let response = client
+        .send_array(&["XADD", "events", "7-0", "kind", "again"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(
+        response,
+        "-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"
+    );
+    let response = client
+        .send_array(&["XADD", "events", "6-*", "kind", "again"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(
+        response,
+        "-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"
+    );
+    let response = client
+        .send_array(&["XADD", "fresh", "0-0", "kind", "zero"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(
+        response,
+        "-ERR The ID specified in XADD must be greater than 0-0\r\n"
+    );
+    let response = client
+        .send_array(&["XADD", "events", "1-x", "kind", "bad"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(
+        response,
+        "-ERR Invalid stream ID specified as stream command argument\r\n"
+    );
+    let response = client
+        .send_array(&["XADD", "events", "*", "kind"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'xadd' command\r\n"
+    );
+
+    let response = client
+        .send_array(&["XADD", "events", "*", "kind", "auto"])
+        .await
+        .expect("Failed to XADD");
+    let id = parse_bulk_string(&response).expect("Expected an ID");
+    let (ms, seq) = id.split_once('-').expect("Expected ms-seq");
+    assert!(ms.parse::<u64>().unwrap() > 7);
+    assert_eq!(seq, "0");
+
+    let response = client
+        .send_array(&["XADD", "missing", "NOMKSTREAM", "*", "kind", "none"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["XLEN", "missing"])
+        .await
+        .expect("Failed to XLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["XLEN", "events"])
+        .await
+        .expect("Failed to XLEN");
+    assert_eq!(parse_integer(&response), Some(4));
+}
+
+#[tokio::test]
+async fn test_xrange_and_xrevrange() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for id in ["1-1", "1-2", "2-0", "3-0"] {
+        client
+            .send_array(&["XADD", "log", id, "id", id])
+            .await
+            .expect("Failed to XADD");
+    }
+
+    let response = client
+        .send_array(&["XRANGE", "log", "-", "1"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(
+        response,
+        "*2\r\n\
+         *2\r\n$3\r\n1-1\r\n*2\r\n$2\r\nid\r\n$3\r\n1-1\r\n\
+         *2\r\n$3\r\n1-2\r\n*2\r\n$2\r\nid\r\n$3\r\n1-2\r\n"
+    );
+    let response = client
+        .send_array(&["XRANGE", "log", "(1-2", "+", "COUNT", "1"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$2\r\nid\r\n$3\r\n2-0\r\n"
+    );
+    let response = client
+        .send_array(&["XREVRANGE", "log", "+", "(1-2", "COUNT", "1"])
+        .await
+        .expect("Failed to XREVRANGE");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$3\r\n3-0\r\n*2\r\n$2\r\nid\r\n$3\r\n3-0\r\n"
+    );
+    let response = client
+        .send_array(&["XRANGE", "log", "3", "1"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&["XRANGE", "log", "-", "+", "COUNT", "0"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&[
+            "XRANGE",
+            "log",
+            "(18446744073709551615-18446744073709551615",
+            "+",
+        ])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(response, "-ERR invalid start ID for the interval\r\n");
+
+    let response = client
+        .send_array(&["XDEL", "log", "1-2", "2", "9-9"])
+        .await
+        .expect("Failed to XDEL");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["XRANGE", "log", "-", "+"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(
+        response,
+        "*2\r\n\
+         *2\r\n$3\r\n1-1\r\n*2\r\n$2\r\nid\r\n$3\r\n1-1\r\n\
+         *2\r\n$3\r\n3-0\r\n*2\r\n$2\r\nid\r\n$3\r\n3-0\r\n"
+    );
+    // Deleting entries never lowers the top ID.
+    let response = client
+        .send_array(&["XDEL", "log", "3-0"])
+        .await
+        .expect("Failed to XDEL");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["XADD", "log", "3-0", "id", "again"])
+        .await
+        .expect("Failed to XADD");
+    assert!(response.starts_with("-ERR The ID specified in XADD is equal or smaller"));
+}
+
+#[tokio::test]
+async fn test_xread() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["XADD", "a", "1-0", "n", "1"])
+        .await
+        .expect("Failed to XADD");
+    client
+        .send_array(&["XADD", "a", "2-0", "n", "2"])
+        .await
+        .expect("Failed to XADD");
+    client
+        .send_array(&["XADD", "b", "5-0", "n", "5"])
+        .await
+        .expect("Failed to XADD");
+
+    let response = client
+        .send_array(&["XREAD", "COUNT", "1", "STREAMS", "a", "b", "0", "4"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(
+        response,
+        "*2\r\n\
+         *2\r\n$1\r\na\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\nn\r\n$1\r\n1\r\n\
+         *2\r\n$1\r\nb\r\n*1\r\n*2\r\n$3\r\n5-0\r\n*2\r\n$1\r\nn\r\n$1\r\n5\r\n"
+    );
+    let response = client
+        .send_array(&["XREAD", "STREAMS", "a", "b", "+", "$"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$1\r\na\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$1\r\nn\r\n$1\r\n2\r\n"
+    );
+    let response = client
+        .send_array(&["XREAD", "STREAMS", "a", "missing", "2-0", "0"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(response, "*-1\r\n");
+
+    let response = client
+        .send_array(&["XREAD", "STREAMS", "a", "b", "0"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(
+        response,
+        "-ERR Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified.\r\n"
+    );
+    let response = client
+        .send_array(&["XREAD", "BLOCK", "-1", "STREAMS", "a", "0"])
+        .await
+        .expect("Failed to XREAD");
+    assert_eq!(response, "-ERR timeout is negative\r\n");
+
+    client
+        .send_array(&["SET", "plain", "value"])
+        .await
+        .expect("Failed to SET");
+    let response = client
+        .send_array(&["XREAD", "STREAMS", "plain", "0"])
+        .await
+        .expect("Failed to XREAD");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
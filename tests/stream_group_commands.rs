@@ -0,0 +1,479 @@
+mod common;
+
+use common::*;
+
+/// A stream `jobs` with entries 1-0 to 3-0 and a group `workers` reading it
+/// from the start.
+async fn setup(client: &mut TestClient) {
+    for id in ["1-0", "2-0", "3-0"] {
+        client
+            .send_array(&["XADD", "jobs", id, "job", id])
+            .await
+            .expect("Failed to XADD");
+    }
+    let response = client
+        .send_array(&["XGROUP", "CREATE", "jobs", "workers", "0"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+}
+
+#[tokio::test]
+async fn test_xgroup_subcommands() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    setup(&mut client).await;
+
+    let response = client
+        .send_array(&["XGROUP", "CREATE", "jobs", "workers", "$"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    assert_eq!(
+        response,
+        "-BUSYGROUP Consumer Group name already exists\r\n"
+    );
+    let response = client
+        .send_array(&["XGROUP", "CREATE", "missing", "workers", "$"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    assert!(response.starts_with("-ERR The XGROUP subcommand requires the key to exist."));
+    let response = client
+        .send_array(&["XGROUP", "CREATE", "fresh", "workers", "$", "MKSTREAM"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["XLEN", "fresh"])
+        .await
+        .expect("Failed to XLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+    // A bad ID is refused before MKSTREAM creates the stream.
+    let response = client
+        .send_array(&["XGROUP", "CREATE", "gk", "g", "notanid", "MKSTREAM"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    assert!(response.starts_with("-ERR Invalid stream ID"), "{response}");
+    let response = client
+        .send_array(&["EXISTS", "gk"])
+        .await
+        .expect("Failed to EXISTS");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["XGROUP", "CREATECONSUMER", "jobs", "workers", "alice"])
+        .await
+        .expect("Failed to XGROUP CREATECONSUMER");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["XGROUP", "CREATECONSUMER", "jobs", "workers", "alice"])
+        .await
+        .expect("Failed to XGROUP CREATECONSUMER");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["XGROUP", "CREATECONSUMER", "jobs", "nobody", "alice"])
+        .await
+        .expect("Failed to XGROUP CREATECONSUMER");
+    assert_eq!(
+        response,
+        "-NOGROUP No such consumer group 'nobody' for key name 'jobs'\r\n"
+    );
+
+    client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    let response = client
+        .send_array(&["XGROUP", "DELCONSUMER", "jobs", "workers", "alice"])
+        .await
+        .expect("Failed to XGROUP DELCONSUMER");
+    assert_eq!(parse_integer(&response), Some(3));
+
+    // Rewinding makes everything new again.
+    let response = client
+        .send_array(&["XGROUP", "SETID", "jobs", "workers", "1"])
+        .await
+        .expect("Failed to XGROUP SETID");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "bob",
+            "COUNT",
+            "1",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$3\r\njob\r\n$3\r\n2-0\r\n"
+    );
+
+    let response = client
+        .send_array(&["XGROUP", "DESTROY", "jobs", "workers"])
+        .await
+        .expect("Failed to XGROUP DESTROY");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["XGROUP", "DESTROY", "jobs", "workers"])
+        .await
+        .expect("Failed to XGROUP DESTROY");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "bob",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(
+        response,
+        "-NOGROUP No such key 'jobs' or consumer group 'workers' in XREADGROUP with GROUP option\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_xreadgroup_history_and_xack() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    setup(&mut client).await;
+
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "COUNT",
+            "2",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\njobs\r\n*2\r\n\
+         *2\r\n$3\r\n1-0\r\n*2\r\n$3\r\njob\r\n$3\r\n1-0\r\n\
+         *2\r\n$3\r\n2-0\r\n*2\r\n$3\r\njob\r\n$3\r\n2-0\r\n"
+    );
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "bob",
+            "NOACK",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\n3-0\r\n*2\r\n$3\r\njob\r\n$3\r\n3-0\r\n"
+    );
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "bob",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(response, "*-1\r\n");
+
+    // History holds only what alice hasn't acknowledged, deleted entries as nil.
+    client
+        .send_array(&["XDEL", "jobs", "2-0"])
+        .await
+        .expect("Failed to XDEL");
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "STREAMS",
+            "jobs",
+            "0",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$4\r\njobs\r\n*2\r\n\
+         *2\r\n$3\r\n1-0\r\n*2\r\n$3\r\njob\r\n$3\r\n1-0\r\n\
+         *2\r\n$3\r\n2-0\r\n*-1\r\n"
+    );
+    let response = client
+        .send_array(&["XACK", "jobs", "workers", "1-0", "2-0", "9-0"])
+        .await
+        .expect("Failed to XACK");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "STREAMS",
+            "jobs",
+            "0",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert_eq!(response, "*1\r\n*2\r\n$4\r\njobs\r\n*0\r\n");
+
+    let response = client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "STREAMS",
+            "jobs",
+            "$",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    assert!(response.starts_with("-ERR The $ ID is meaningless"));
+    let response = client
+        .send_array(&["XACK", "missing", "workers", "1-0"])
+        .await
+        .expect("Failed to XACK");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_xpending() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    setup(&mut client).await;
+
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers"])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(response, "*4\r\n:0\r\n$-1\r\n$-1\r\n*-1\r\n");
+
+    client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "COUNT",
+            "2",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "bob",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers"])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(
+        response,
+        "*4\r\n:3\r\n$3\r\n1-0\r\n$3\r\n3-0\r\n\
+         *2\r\n*2\r\n$5\r\nalice\r\n$1\r\n2\r\n*2\r\n$3\r\nbob\r\n$1\r\n1\r\n"
+    );
+
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers", "-", "+", "10", "bob"])
+        .await
+        .expect("Failed to XPENDING");
+    assert!(response.starts_with("*1\r\n*4\r\n$3\r\n3-0\r\n$3\r\nbob\r\n:"));
+    assert!(response.ends_with(":1\r\n"));
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers", "(1-0", "+", "1"])
+        .await
+        .expect("Failed to XPENDING");
+    assert!(response.starts_with("*1\r\n*4\r\n$3\r\n2-0\r\n$5\r\nalice\r\n"));
+    let response = client
+        .send_array(&[
+            "XPENDING", "jobs", "workers", "IDLE", "60000", "-", "+", "10",
+        ])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers", "-", "+"])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(response, "-ERR syntax error\r\n");
+    let response = client
+        .send_array(&["XPENDING", "jobs", "nobody"])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(
+        response,
+        "-NOGROUP No such key 'jobs' or consumer group 'nobody'\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_xclaim_and_xautoclaim() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    setup(&mut client).await;
+    client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "workers",
+            "alice",
+            "STREAMS",
+            "jobs",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+
+    // Nothing has been idle for a minute yet.
+    let response = client
+        .send_array(&["XCLAIM", "jobs", "workers", "bob", "60000", "1-0"])
+        .await
+        .expect("Failed to XCLAIM");
+    assert_eq!(response, "*0\r\n");
+    let response = client
+        .send_array(&["XCLAIM", "jobs", "workers", "bob", "0", "1-0", "JUSTID"])
+        .await
+        .expect("Failed to XCLAIM");
+    assert_eq!(response, "*1\r\n$3\r\n1-0\r\n");
+    let response = client
+        .send_array(&[
+            "XCLAIM",
+            "jobs",
+            "workers",
+            "bob",
+            "0",
+            "2-0",
+            "IDLE",
+            "5000",
+            "RETRYCOUNT",
+            "7",
+        ])
+        .await
+        .expect("Failed to XCLAIM");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$3\r\njob\r\n$3\r\n2-0\r\n"
+    );
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers", "2-0", "2-0", "1"])
+        .await
+        .expect("Failed to XPENDING");
+    assert!(response.starts_with("*1\r\n*4\r\n$3\r\n2-0\r\n$3\r\nbob\r\n:5"));
+    assert!(response.ends_with(":7\r\n"));
+    let response = client
+        .send_array(&["XCLAIM", "jobs", "workers", "bob", "0", "1-0", "BOGUS"])
+        .await
+        .expect("Failed to XCLAIM");
+    assert_eq!(response, "-ERR Unrecognized XCLAIM option 'BOGUS'\r\n");
+
+    // 2-0 went idle through XCLAIM's IDLE; deleted entries get dropped.
+    client
+        .send_array(&["XDEL", "jobs", "3-0"])
+        .await
+        .expect("Failed to XDEL");
+    let response = client
+        .send_array(&[
+            "XAUTOCLAIM",
+            "jobs",
+            "workers",
+            "carol",
+            "1000",
+            "0",
+            "COUNT",
+            "1",
+        ])
+        .await
+        .expect("Failed to XAUTOCLAIM");
+    assert_eq!(
+        response,
+        "*3\r\n$3\r\n3-0\r\n\
+         *1\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$3\r\njob\r\n$3\r\n2-0\r\n*0\r\n"
+    );
+    let response = client
+        .send_array(&["XAUTOCLAIM", "jobs", "workers", "carol", "0", "3-0"])
+        .await
+        .expect("Failed to XAUTOCLAIM");
+    assert_eq!(response, "*3\r\n$3\r\n0-0\r\n*0\r\n*1\r\n$3\r\n3-0\r\n");
+    let response = client
+        .send_array(&[
+            "XAUTOCLAIM",
+            "jobs",
+            "workers",
+            "carol",
+            "0",
+            "0",
+            "COUNT",
+            "1",
+            "JUSTID",
+        ])
+        .await
+        .expect("Failed to XAUTOCLAIM");
+    assert_eq!(response, "*3\r\n$3\r\n2-0\r\n*1\r\n$3\r\n1-0\r\n*0\r\n");
+    let response = client
+        .send_array(&[
+            "XAUTOCLAIM",
+            "jobs",
+            "workers",
+            "carol",
+            "0",
+            "0",
+            "COUNT",
+            "0",
+        ])
+        .await
+        .expect("Failed to XAUTOCLAIM");
+    assert_eq!(response, "-ERR COUNT must be > 0\r\n");
+
+    let response = client
+        .send_array(&["XPENDING", "jobs", "workers"])
+        .await
+        .expect("Failed to XPENDING");
+    assert_eq!(
+        response,
+        "*4\r\n:2\r\n$3\r\n1-0\r\n$3\r\n2-0\r\n*1\r\n*2\r\n$5\r\ncarol\r\n$1\r\n2\r\n"
+    );
+}
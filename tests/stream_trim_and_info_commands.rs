@@ -0,0 +1,271 @@
+mod common;
+
+use common::*;
+
+async fn add_entries(client: &mut TestClient, key: &str, count: u64) {
+    for ms in 1..=count {
+        client
+            .send_array(&["XADD", key, &ms.to_string(), "n", &ms.to_string()])
+            .await
+            .expect("Failed to XADD");
+    }
+}
+
+#[tokio::test]
+async fn test_xtrim() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    add_entries(&mut client, "log", 250).await;
+
+    // Approximate trimming only drops whole nodes of 100 entries.
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "~", "120"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(parse_integer(&response), Some(100));
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "~", "120", "LIMIT", "10"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "=", "120"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(parse_integer(&response), Some(30));
+    let response = client
+        .send_array(&["XTRIM", "log", "MINID", "200"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(parse_integer(&response), Some(69));
+    let response = client
+        .send_array(&["XRANGE", "log", "-", "+", "COUNT", "1"])
+        .await
+        .expect("Failed to XRANGE");
+    assert!(response.starts_with("*1\r\n*2\r\n$5\r\n200-0\r\n"));
+
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "10", "LIMIT", "5"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(
+        response,
+        "-ERR syntax error, LIMIT cannot be used without the special ~ option\r\n"
+    );
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "-1"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(response, "-ERR The MAXLEN argument must be >= 0.\r\n");
+    let response = client
+        .send_array(&["XTRIM", "log", "MAXLEN", "1", "MINID", "1"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(
+        response,
+        "-ERR syntax error, MAXLEN and MINID options at the same time are not compatible\r\n"
+    );
+    let response = client
+        .send_array(&["XTRIM", "missing", "MAXLEN", "0"])
+        .await
+        .expect("Failed to XTRIM");
+    assert_eq!(parse_integer(&response), Some(0));
+}
+
+#[tokio::test]
+async fn test_xadd_trims() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    add_entries(&mut client, "log", 3).await;
+
+    let response = client
+        .send_array(&["XADD", "log", "MAXLEN", "2", "4", "n", "4"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(parse_bulk_string(&response), Some("4-0".to_string()));
+    let response = client
+        .send_array(&["XLEN", "log"])
+        .await
+        .expect("Failed to XLEN");
+    assert_eq!(parse_integer(&response), Some(2));
+    let response = client
+        .send_array(&["XADD", "log", "NOMKSTREAM", "MINID", "5", "5", "n", "5"])
+        .await
+        .expect("Failed to XADD");
+    assert_eq!(parse_bulk_string(&response), Some("5-0".to_string()));
+    let response = client
+        .send_array(&["XRANGE", "log", "-", "+"])
+        .await
+        .expect("Failed to XRANGE");
+    assert_eq!(
+        response,
+        "*1\r\n*2\r\n$3\r\n5-0\r\n*2\r\n$1\r\nn\r\n$1\r\n5\r\n"
+    );
+}
+
+#[tokio::test]
+async fn test_xinfo() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    add_entries(&mut client, "log", 3).await;
+    client
+        .send_array(&["XGROUP", "CREATE", "log", "readers", "0"])
+        .await
+        .expect("Failed to XGROUP CREATE");
+    client
+        .send_array(&[
+            "XREADGROUP",
+            "GROUP",
+            "readers",
+            "alice",
+            "COUNT",
+            "1",
+            "STREAMS",
+            "log",
+            ">",
+        ])
+        .await
+        .expect("Failed to XREADGROUP");
+    client
+        .send_array(&["XDEL", "log", "3"])
+        .await
+        .expect("Failed to XDEL");
+
+    let response = client
+        .send_array(&["XINFO", "STREAM", "log"])
+        .await
+        .expect("Failed to XINFO STREAM");
+    assert_eq!(
+        response,
+        "*20\r\n\
+         $6\r\nlength\r\n:2\r\n\
+         $15\r\nradix-tree-keys\r\n:1\r\n\
+         $16\r\nradix-tree-nodes\r\n:1\r\n\
+         $17\r\nlast-generated-id\r\n$3\r\n3-0\r\n\
+         $20\r\nmax-deleted-entry-id\r\n$3\r\n3-0\r\n\
+         $13\r\nentries-added\r\n:3\r\n\
+         $23\r\nrecorded-first-entry-id\r\n$3\r\n1-0\r\n\
+         $6\r\ngroups\r\n:1\r\n\
+         $11\r\nfirst-entry\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\nn\r\n$1\r\n1\r\n\
+         $10\r\nlast-entry\r\n*2\r\n$3\r\n2-0\r\n*2\r\n$1\r\nn\r\n$1\r\n2\r\n"
+    );
+
+    // The deletion past the group's position leaves its lag unknown.
+    let response = client
+        .send_array(&["XINFO", "GROUPS", "log"])
+        .await
+        .expect("Failed to XINFO GROUPS");
+    assert_eq!(
+        response,
+        "*1\r\n*12\r\n\
+         $4\r\nname\r\n$7\r\nreaders\r\n\
+         $9\r\nconsumers\r\n:1\r\n\
+         $7\r\npending\r\n:1\r\n\
+         $17\r\nlast-delivered-id\r\n$3\r\n1-0\r\n\
+         $12\r\nentries-read\r\n:1\r\n\
+         $3\r\nlag\r\n$-1\r\n"
+    );
+
+    let response = client
+        .send_array(&["XINFO", "CONSUMERS", "log", "readers"])
+        .await
+        .expect("Failed to XINFO CONSUMERS");
+    assert!(
+        response.starts_with("*1\r\n*8\r\n$4\r\nname\r\n$5\r\nalice\r\n$7\r\npending\r\n:1\r\n")
+    );
+    let response = client
+        .send_array(&["XINFO", "CONSUMERS", "log", "nobody"])
+        .await
+        .expect("Failed to XINFO CONSUMERS");
+    assert_eq!(
+        response,
+        "-NOGROUP No such consumer group 'nobody' for key name 'log'\r\n"
+    );
+
+    let response = client
+        .send_array(&["XINFO", "STREAM", "log", "FULL", "COUNT", "1"])
+        .await
+        .expect("Failed to XINFO STREAM FULL");
+    assert!(response.contains(
+        "$7\r\nentries\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\nn\r\n$1\r\n1\r\n$6\r\ngroups\r\n*1\r\n"
+    ));
+    assert!(response.contains(
+        "$9\r\npel-count\r\n:1\r\n$7\r\npending\r\n*1\r\n*4\r\n$3\r\n1-0\r\n$5\r\nalice\r\n"
+    ));
+
+    let response = client
+        .send_array(&["XINFO", "STREAM", "missing"])
+        .await
+        .expect("Failed to XINFO STREAM");
+    assert_eq!(response, "-ERR no such key\r\n");
+}
+
+#[tokio::test]
+async fn test_xsetid() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+    add_entries(&mut client, "log", 3).await;
+
+    let response = client
+        .send_array(&["XSETID", "log", "2-0"])
+        .await
+        .expect("Failed to XSETID");
+    assert_eq!(
+        response,
+        "-ERR The ID specified in XSETID is smaller than the target stream top item\r\n"
+    );
+    let response = client
+        .send_array(&["XSETID", "log", "9-0", "ENTRIESADDED", "2"])
+        .await
+        .expect("Failed to XSETID");
+    assert_eq!(
+        response,
+        "-ERR The entries_added specified in XSETID is smaller than the target stream length\r\n"
+    );
+    let response = client
+        .send_array(&["XSETID", "log", "9-0", "MAXDELETEDID", "10-0"])
+        .await
+        .expect("Failed to XSETID");
+    assert_eq!(
+        response,
+        "-ERR The ID specified in XSETID is smaller than the provided max_deleted_entry_id\r\n"
+    );
+    let response = client
+        .send_array(&["XSETID", "missing", "1-0"])
+        .await
+        .expect("Failed to XSETID");
+    assert_eq!(response, "-ERR no such key\r\n");
+
+    let response = client
+        .send_array(&[
+            "XSETID",
+            "log",
+            "9-0",
+            "ENTRIESADDED",
+            "10",
+            "MAXDELETEDID",
+            "8-0",
+        ])
+        .await
+        .expect("Failed to XSETID");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["XADD", "log", "9-0", "n", "9"])
+        .await
+        .expect("Failed to XADD");
+    assert!(response.starts_with("-ERR The ID specified in XADD is equal or smaller"));
+    let response = client
+        .send_array(&["XADD", "log", "*", "n", "now"])
+        .await
+        .expect("Failed to XADD");
+    assert!(parse_bulk_string(&response).is_some());
+    let response = client
+        .send_array(&["XINFO", "STREAM", "log"])
+        .await
+        .expect("Failed to XINFO STREAM");
+    assert!(
+        response.contains(
+            "$20\r\nmax-deleted-entry-id\r\n$3\r\n8-0\r\n$13\r\nentries-added\r\n:11\r\n"
+        )
+    );
+}
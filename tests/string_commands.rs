@@ -0,0 +1,454 @@
+mod common;
+
+use common::*;
+use tokio::time::{Duration, sleep};
+
+#[tokio::test]
+async fn test_set_nx_and_xx() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SET", "lock", "token1", "NX", "PX", "30000"])
+        .await
+        .expect("Failed to SET NX");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let response = client
+        .send_array(&["SET", "lock", "token2", "NX"])
+        .await
+        .expect("Failed to SET NX");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["SET", "missing", "v", "XX"])
+        .await
+        .expect("Failed to SET XX");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["SET", "lock", "token3", "xx"])
+        .await
+        .expect("Failed to SET XX");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let response = client
+        .send_array(&["GET", "lock"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("token3".to_string()));
+}
+
+#[tokio::test]
+async fn test_set_get_returns_old_value() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SET", "key", "first", "GET"])
+        .await
+        .expect("Failed to SET GET");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["SET", "key", "second", "GET"])
+        .await
+        .expect("Failed to SET GET");
+    assert_eq!(parse_bulk_string(&response), Some("first".to_string()));
+
+    client
+        .send_array(&["RPUSH", "list", "a"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["SET", "list", "v", "GET"])
+        .await
+        .expect("Failed to SET GET");
+    assert!(response.starts_with("-WRONGTYPE"));
+}
+
+#[tokio::test]
+async fn test_set_ex_is_seconds_and_keepttl() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "cache", "v1", "EX", "10"])
+        .await
+        .expect("Failed to SET EX");
+    sleep(Duration::from_millis(50)).await;
+    let response = client
+        .send_array(&["GET", "cache"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("v1".to_string()));
+
+    client
+        .send_array(&["SET", "short", "v1", "PX", "100"])
+        .await
+        .expect("Failed to SET PX");
+    client
+        .send_array(&["SET", "short", "v2", "KEEPTTL"])
+        .await
+        .expect("Failed to SET KEEPTTL");
+    sleep(Duration::from_millis(150)).await;
+    let response = client
+        .send_array(&["GET", "short"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_set_pxat_in_the_past_expires_immediately() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["SET", "old", "v", "PXAT", "1000"])
+        .await
+        .expect("Failed to SET PXAT");
+    let response = client
+        .send_array(&["GET", "old"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_set_rejects_bad_options() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    for args in [
+        &["SET", "k", "v", "NX", "XX"][..],
+        &["SET", "k", "v", "EX", "10", "PX", "100"],
+        &["SET", "k", "v", "EX", "10", "KEEPTTL"],
+        &["SET", "k", "v", "EX"],
+        &["SET", "k", "v", "BOGUS"],
+    ] {
+        let response = client.send_array(args).await.expect("Failed to SET");
+        assert_eq!(response, "-ERR syntax error\r\n", "{args:?}");
+    }
+
+    let response = client
+        .send_array(&["SET", "k", "v", "EX", "0"])
+        .await
+        .expect("Failed to SET");
+    assert_eq!(response, "-ERR invalid expire time in 'set' command\r\n");
+
+    let response = client
+        .send_array(&["SET", "k", "v", "PX", "soon"])
+        .await
+        .expect("Failed to SET");
+    assert_eq!(response, "-ERR value is not an integer or out of range\r\n");
+}
+
+#[tokio::test]
+async fn test_append_strlen_and_ranges() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["APPEND", "greeting", "Hello"])
+        .await
+        .expect("Failed to APPEND");
+    assert_eq!(parse_integer(&response), Some(5));
+    let response = client
+        .send_array(&["APPEND", "greeting", " World"])
+        .await
+        .expect("Failed to APPEND");
+    assert_eq!(parse_integer(&response), Some(11));
+
+    let response = client
+        .send_array(&["STRLEN", "greeting"])
+        .await
+        .expect("Failed to STRLEN");
+    assert_eq!(parse_integer(&response), Some(11));
+    let response = client
+        .send_array(&["STRLEN", "missing"])
+        .await
+        .expect("Failed to STRLEN");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["GETRANGE", "greeting", "0", "4"])
+        .await
+        .expect("Failed to GETRANGE");
+    assert_eq!(parse_bulk_string(&response), Some("Hello".to_string()));
+    let response = client
+        .send_array(&["GETRANGE", "greeting", "-5", "-1"])
+        .await
+        .expect("Failed to GETRANGE");
+    assert_eq!(parse_bulk_string(&response), Some("World".to_string()));
+    let response = client
+        .send_array(&["SUBSTR", "greeting", "6", "100"])
+        .await
+        .expect("Failed to SUBSTR");
+    assert_eq!(parse_bulk_string(&response), Some("World".to_string()));
+    let response = client
+        .send_array(&["GETRANGE", "greeting", "-1", "-5"])
+        .await
+        .expect("Failed to GETRANGE");
+    assert_eq!(response, "$0\r\n\r\n");
+}
+
+#[tokio::test]
+async fn test_setrange_pads_and_keeps_ttl() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SETRANGE", "key", "3", "abc"])
+        .await
+        .expect("Failed to SETRANGE");
+    assert_eq!(parse_integer(&response), Some(6));
+    let response = client
+        .send_array(&["GET", "key"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$6\r\n\0\0\0abc\r\n");
+
+    let response = client
+        .send_array(&["SETRANGE", "missing", "10", ""])
+        .await
+        .expect("Failed to SETRANGE");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["GET", "missing"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+
+    let response = client
+        .send_array(&["SETRANGE", "key", "-1", "x"])
+        .await
+        .expect("Failed to SETRANGE");
+    assert_eq!(response, "-ERR offset is out of range\r\n");
+
+    client
+        .send_array(&["SET", "temp", "Hello", "PX", "100"])
+        .await
+        .expect("Failed to SET");
+    client
+        .send_array(&["SETRANGE", "temp", "0", "J"])
+        .await
+        .expect("Failed to SETRANGE");
+    sleep(Duration::from_millis(150)).await;
+    let response = client
+        .send_array(&["GET", "temp"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_mset_msetnx_and_mget() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["MSET", "a", "1", "b", "2"])
+        .await
+        .expect("Failed to MSET");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+
+    let response = client
+        .send_array(&["MSET", "a", "1", "b"])
+        .await
+        .expect("Failed to MSET");
+    assert_eq!(
+        response,
+        "-ERR wrong number of arguments for 'mset' command\r\n"
+    );
+
+    let response = client
+        .send_array(&["MSETNX", "b", "3", "c", "4"])
+        .await
+        .expect("Failed to MSETNX");
+    assert_eq!(parse_integer(&response), Some(0));
+    let response = client
+        .send_array(&["MSETNX", "c", "4", "d", "5"])
+        .await
+        .expect("Failed to MSETNX");
+    assert_eq!(parse_integer(&response), Some(1));
+
+    client
+        .send_array(&["RPUSH", "list", "x"])
+        .await
+        .expect("Failed to RPUSH");
+    let response = client
+        .send_array(&["MGET", "a", "missing", "list", "d"])
+        .await
+        .expect("Failed to MGET");
+    assert_eq!(response, "*4\r\n$1\r\n1\r\n$-1\r\n$-1\r\n$1\r\n5\r\n");
+}
+
+#[tokio::test]
+async fn test_getset_getdel_and_getex() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["GETSET", "key", "one"])
+        .await
+        .expect("Failed to GETSET");
+    assert_eq!(response, "$-1\r\n");
+    let response = client
+        .send_array(&["GETSET", "key", "two"])
+        .await
+        .expect("Failed to GETSET");
+    assert_eq!(parse_bulk_string(&response), Some("one".to_string()));
+
+    let response = client
+        .send_array(&["GETEX", "key", "PX", "100"])
+        .await
+        .expect("Failed to GETEX");
+    assert_eq!(parse_bulk_string(&response), Some("two".to_string()));
+    let response = client
+        .send_array(&["GETEX", "key", "PERSIST"])
+        .await
+        .expect("Failed to GETEX");
+    assert_eq!(parse_bulk_string(&response), Some("two".to_string()));
+    sleep(Duration::from_millis(150)).await;
+    let response = client
+        .send_array(&["GET", "key"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("two".to_string()));
+
+    let response = client
+        .send_array(&["GETEX", "key", "EX", "10", "PERSIST"])
+        .await
+        .expect("Failed to GETEX");
+    assert_eq!(response, "-ERR syntax error\r\n");
+    let response = client
+        .send_array(&["GETEX", "key", "EX", "0"])
+        .await
+        .expect("Failed to GETEX");
+    assert_eq!(response, "-ERR invalid expire time in 'getex' command\r\n");
+
+    let response = client
+        .send_array(&["GETDEL", "key"])
+        .await
+        .expect("Failed to GETDEL");
+    assert_eq!(parse_bulk_string(&response), Some("two".to_string()));
+    let response = client
+        .send_array(&["GETDEL", "key"])
+        .await
+        .expect("Failed to GETDEL");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_setnx_setex_and_psetex() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    let response = client
+        .send_array(&["SETNX", "key", "v"])
+        .await
+        .expect("Failed to SETNX");
+    assert_eq!(parse_integer(&response), Some(1));
+    let response = client
+        .send_array(&["SETNX", "key", "w"])
+        .await
+        .expect("Failed to SETNX");
+    assert_eq!(parse_integer(&response), Some(0));
+
+    let response = client
+        .send_array(&["SETEX", "key", "0", "v"])
+        .await
+        .expect("Failed to SETEX");
+    assert_eq!(response, "-ERR invalid expire time in 'setex' command\r\n");
+
+    let response = client
+        .send_array(&["PSETEX", "key", "100", "short"])
+        .await
+        .expect("Failed to PSETEX");
+    assert_eq!(parse_simple_string(&response), Some("OK"));
+    let response = client
+        .send_array(&["GET", "key"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(parse_bulk_string(&response), Some("short".to_string()));
+    sleep(Duration::from_millis(150)).await;
+    let response = client
+        .send_array(&["GET", "key"])
+        .await
+        .expect("Failed to GET");
+    assert_eq!(response, "$-1\r\n");
+}
+
+#[tokio::test]
+async fn test_lcs() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let mut client = server.connect().await.expect("Failed to connect");
+
+    client
+        .send_array(&["MSET", "key1", "ohmytext", "key2", "mynewtext"])
+        .await
+        .expect("Failed to MSET");
+
+    let response = client
+        .send_array(&["LCS", "key1", "key2"])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(parse_bulk_string(&response), Some("mytext".to_string()));
+
+    let response = client
+        .send_array(&["LCS", "key1", "key2", "LEN"])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(parse_integer(&response), Some(6));
+
+    let response = client
+        .send_array(&[
+            "LCS",
+            "key1",
+            "key2",
+            "IDX",
+            "MINMATCHLEN",
+            "4",
+            "WITHMATCHLEN",
+        ])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(
+        response,
+        "*4\r\n$7\r\nmatches\r\n*1\r\n*3\r\n*2\r\n:4\r\n:7\r\n*2\r\n:5\r\n:8\r\n:4\r\n$3\r\nlen\r\n:6\r\n"
+    );
+
+    let response = client
+        .send_array(&["LCS", "key1", "key2", "IDX"])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(
+        response,
+        "*4\r\n$7\r\nmatches\r\n*2\r\n*2\r\n*2\r\n:4\r\n:7\r\n*2\r\n:5\r\n:8\r\n*2\r\n*2\r\n:2\r\n:3\r\n*2\r\n:0\r\n:1\r\n$3\r\nlen\r\n:6\r\n"
+    );
+
+    let response = client
+        .send_array(&["LCS", "key1", "key2", "LEN", "IDX"])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(
+        response,
+        "-ERR If you want both the length and indexes, please just use IDX.\r\n"
+    );
+
+    // The table would need over 512MB, so it isn't built at all.
+    let long = "a".repeat(12_000);
+    client
+        .send_array(&["MSET", "long1", &long, "long2", &long])
+        .await
+        .expect("Failed to MSET");
+    let response = client
+        .send_array(&["LCS", "long1", "long2", "LEN"])
+        .await
+        .expect("Failed to LCS");
+    assert_eq!(
+        response,
+        "-ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len\r\n"
+    );
+}